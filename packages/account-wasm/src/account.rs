@@ -1,3 +1,4 @@
+use account_sdk::abi::ContractAbi;
 use account_sdk::controller::Controller;
 use account_sdk::errors::ControllerError;
 use account_sdk::signers::Owner;
@@ -10,7 +11,7 @@ use wasm_bindgen::prelude::*;
 
 use crate::errors::JsControllerError;
 use crate::types::call::JsCall;
-use crate::types::invocation::JsInvocationsDetails;
+use crate::types::invocation::{JsInvocationsDetails, JsWaitOptions};
 use crate::types::policy::Policy;
 use crate::types::session::SessionMetadata;
 use crate::types::signer::Signer;
@@ -59,7 +60,7 @@ impl CartridgeAccount {
             Owner::Signer(signer.try_into()?),
             address.0,
             chain_id.0,
-        );
+        )?;
 
         Ok(CartridgeAccount { controller })
     }
@@ -167,6 +168,35 @@ impl CartridgeAccount {
         })
     }
 
+    #[wasm_bindgen(js_name = encodeCall)]
+    pub async fn encode_call(
+        &self,
+        contract_address: JsFelt,
+        entrypoint: String,
+        args: String,
+    ) -> Result<JsCall> {
+        set_panic_hook();
+
+        let abi = ContractAbi::fetch(&self.controller.provider, contract_address.0).await?;
+        let calldata = abi.encode_calldata(&entrypoint, &serde_json::from_str(&args)?)?;
+
+        Ok(JsCall {
+            contract_address: contract_address.0,
+            entrypoint,
+            calldata,
+        })
+    }
+
+    #[wasm_bindgen(js_name = decodeCall)]
+    pub async fn decode_call(&self, call: JsCall) -> Result<String> {
+        set_panic_hook();
+
+        let abi = ContractAbi::fetch(&self.controller.provider, call.contract_address).await?;
+        let args = abi.decode_calldata(&call.entrypoint, &call.calldata)?;
+
+        Ok(args.to_string())
+    }
+
     #[wasm_bindgen(js_name = createSession)]
     pub async fn create_session(&mut self, policies: Vec<Policy>, expires_at: u64) -> Result<()> {
         set_panic_hook();
@@ -215,6 +245,34 @@ impl CartridgeAccount {
         Ok(to_value(&result)?)
     }
 
+    /// Executes `calls` and waits for the transaction, by default until it's accepted on L2 or
+    /// 60 seconds passed. `options` can wait for acceptance on L1, or for longer.
+    #[wasm_bindgen(js_name = executeAndWait)]
+    pub async fn execute_and_wait(
+        &mut self,
+        calls: Vec<JsCall>,
+        details: JsInvocationsDetails,
+        options: Option<JsWaitOptions>,
+    ) -> std::result::Result<JsValue, JsControllerError> {
+        set_panic_hook();
+
+        let calls = calls
+            .into_iter()
+            .map(TryFrom::try_from)
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        let receipt = self
+            .controller
+            .execute_and_wait_with_options(
+                calls,
+                details.max_fee,
+                options.unwrap_or_default().into(),
+            )
+            .await?;
+
+        Ok(to_value(&receipt)?)
+    }
+
     #[wasm_bindgen(js_name = executeFromOutsideV2)]
     pub async fn execute_from_outside_v2(
         &mut self,
@@ -330,4 +388,57 @@ impl CartridgeAccount {
 
         Ok(JsFelt(res))
     }
+
+    #[wasm_bindgen(js_name = addOwner)]
+    pub async fn add_owner(
+        &mut self,
+        signer: Signer,
+        details: JsInvocationsDetails,
+    ) -> std::result::Result<JsValue, JsControllerError> {
+        set_panic_hook();
+
+        let receipt = self
+            .controller
+            .add_owner(signer.try_into()?, details.max_fee)
+            .await?;
+
+        Ok(to_value(&receipt)?)
+    }
+
+    #[wasm_bindgen(js_name = removeOwner)]
+    pub async fn remove_owner(
+        &mut self,
+        signer: Signer,
+        details: JsInvocationsDetails,
+    ) -> std::result::Result<JsValue, JsControllerError> {
+        set_panic_hook();
+
+        let receipt = self
+            .controller
+            .remove_owner(signer.try_into()?, details.max_fee)
+            .await?;
+
+        Ok(to_value(&receipt)?)
+    }
+
+    #[wasm_bindgen(js_name = rotateOwner)]
+    pub async fn rotate_owner(
+        &mut self,
+        old_signer: Signer,
+        new_signer: Signer,
+        details: JsInvocationsDetails,
+    ) -> std::result::Result<JsValue, JsControllerError> {
+        set_panic_hook();
+
+        let receipt = self
+            .controller
+            .rotate_owner(
+                old_signer.try_into()?,
+                new_signer.try_into()?,
+                details.max_fee,
+            )
+            .await?;
+
+        Ok(to_value(&receipt)?)
+    }
 }
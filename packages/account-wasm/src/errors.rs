@@ -2,6 +2,7 @@ use std::fmt;
 
 use account_sdk::{
     errors::ControllerError, provider::ExecuteFromOutsideError, signers::DeviceError,
+    transaction_waiter::TransactionWaitingError,
 };
 use serde::Serialize;
 use starknet::{accounts::AccountError, core::types::StarknetError, providers::ProviderError};
@@ -95,6 +96,11 @@ pub enum ErrorCode {
     UrlParseError = 133,
     Base64DecodeError = 134,
     CoseError = 135,
+    TransactionTimeout = 136,
+    TransactionReverted = 137,
+    OwnerNotUpdated = 138,
+    OfflineVerificationUnsupported = 139,
+    InvalidUsername = 140,
 }
 
 impl From<ControllerError> for JsControllerError {
@@ -161,6 +167,7 @@ impl From<ControllerError> for JsControllerError {
                 message: format!("Failed to parse URL: {}", e),
                 data: None,
             },
+            ControllerError::TransactionWaitingError(e) => e.into(),
             ControllerError::Base64DecodeError(e) => JsControllerError {
                 code: ErrorCode::Base64DecodeError,
                 message: format!("Failed to decode Base64: {}", e),
@@ -171,6 +178,24 @@ impl From<ControllerError> for JsControllerError {
                 message: format!("COSE error: {}", e),
                 data: None,
             },
+            ControllerError::OwnerNotUpdated(guid) => JsControllerError {
+                code: ErrorCode::OwnerNotUpdated,
+                message: format!("Owner {:#x} wasn't updated on chain", guid),
+                data: None,
+            },
+            ControllerError::OfflineVerificationUnsupported(signer) => JsControllerError {
+                code: ErrorCode::OfflineVerificationUnsupported,
+                message: format!("{} signatures can't be verified offline", signer),
+                data: None,
+            },
+            ControllerError::InvalidUsername(username) => JsControllerError {
+                code: ErrorCode::InvalidUsername,
+                message: format!(
+                    "Username {} isn't a short string of at most 31 ASCII characters",
+                    username
+                ),
+                data: None,
+            },
         }
     }
 }
@@ -212,6 +237,30 @@ impl From<ExecuteFromOutsideError> for JsControllerError {
     }
 }
 
+impl From<TransactionWaitingError> for JsControllerError {
+    fn from(error: TransactionWaitingError) -> Self {
+        let (code, message, data) = match error {
+            TransactionWaitingError::Timeout => (
+                ErrorCode::TransactionTimeout,
+                "Timed out waiting for transaction".to_string(),
+                None,
+            ),
+            TransactionWaitingError::TransactionReverted(reason) => (
+                ErrorCode::TransactionReverted,
+                "Transaction reverted".to_string(),
+                Some(reason),
+            ),
+            TransactionWaitingError::Provider(e) => return e.into(),
+        };
+
+        JsControllerError {
+            code,
+            message,
+            data,
+        }
+    }
+}
+
 impl From<DeviceError> for JsControllerError {
     fn from(e: DeviceError) -> Self {
         let (code, message) = match e {
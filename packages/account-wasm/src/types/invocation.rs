@@ -1,7 +1,10 @@
+use std::time::Duration;
+
+use account_sdk::transaction_waiter::WaitOptions;
 use serde::{Deserialize, Serialize};
 use serde_with::serde_as;
 use starknet::core::serde::unsigned_field_element::UfeHex;
-use starknet::core::types::Felt;
+use starknet::core::types::{Felt, TransactionFinalityStatus};
 use tsify_next::Tsify;
 use wasm_bindgen::prelude::*;
 
@@ -14,3 +17,40 @@ pub struct JsInvocationsDetails {
     #[serde_as(as = "UfeHex")]
     pub max_fee: Felt,
 }
+
+/// Finality status `executeAndWait` waits for.
+#[derive(Tsify, Serialize, Deserialize, Debug, Clone, Copy)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum JsFinalityStatus {
+    AcceptedOnL2,
+    AcceptedOnL1,
+}
+
+/// How `executeAndWait` waits for its transaction. Unset fields default to acceptance on L2
+/// within 60 seconds.
+#[derive(Tsify, Serialize, Deserialize, Debug, Clone, Default)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+#[serde(rename_all = "camelCase")]
+pub struct JsWaitOptions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub finality: Option<JsFinalityStatus>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+}
+
+impl From<JsWaitOptions> for WaitOptions {
+    fn from(options: JsWaitOptions) -> Self {
+        let defaults = WaitOptions::default();
+        WaitOptions {
+            finality: match options.finality {
+                Some(JsFinalityStatus::AcceptedOnL2) => TransactionFinalityStatus::AcceptedOnL2,
+                Some(JsFinalityStatus::AcceptedOnL1) => TransactionFinalityStatus::AcceptedOnL1,
+                None => defaults.finality,
+            },
+            timeout: options
+                .timeout_ms
+                .map_or(defaults.timeout, Duration::from_millis),
+        }
+    }
+}
@@ -76,7 +76,7 @@ use std::collections::HashMap;
 use starknet_types_core::felt::Felt;
 use starknet::macros::felt;
 
-#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum Version {{
     {enum_variants}
 }}
@@ -63,7 +63,8 @@ async fn main() {
         owner.clone(),
         address,
         chain_id,
-    );
+    )
+    .unwrap();
 
     match factory
         .deploy_v1(salt)
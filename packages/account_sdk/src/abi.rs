@@ -0,0 +1,800 @@
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+
+use cainome::cairo_serde::{ByteArray, CairoSerde};
+use primitive_types::U256;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use starknet::core::types::{BlockId, BlockTag, Call, ContractClass, Felt};
+use starknet::core::utils::{cairo_short_string_to_felt, get_selector_from_name};
+use starknet::providers::{Provider, ProviderError};
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+#[path = "abi_test.rs"]
+mod abi_test;
+
+const FELT_TYPES: [&str; 5] = [
+    "core::felt252",
+    "core::starknet::contract_address::ContractAddress",
+    "core::starknet::class_hash::ClassHash",
+    "core::starknet::storage_access::StorageAddress",
+    "core::starknet::storage_access::StorageBaseAddress",
+];
+const ETH_ADDRESS_TYPE: &str = "core::starknet::eth_address::EthAddress";
+const U256_TYPE: &str = "core::integer::u256";
+const BYTE_ARRAY_TYPE: &str = "core::byte_array::ByteArray";
+const BOOL_TYPE: &str = "core::bool";
+const UNIT_TYPE: &str = "()";
+
+#[derive(Debug, thiserror::Error)]
+pub enum AbiError {
+    #[error("Invalid ABI: {0}")]
+    InvalidAbi(#[from] serde_json::Error),
+
+    #[error("Cairo 0 classes have no typed ABI")]
+    LegacyClass,
+
+    #[error("Entrypoint {0} isn't in the ABI")]
+    EntrypointNotFound(String),
+
+    #[error("Entrypoint {0} is defined more than once with different inputs")]
+    ConflictingEntrypoint(String),
+
+    #[error("{path}: type {type_name} isn't in the ABI")]
+    UnknownType { path: String, type_name: String },
+
+    #[error("{path}: {message}")]
+    InvalidArgument { path: String, message: String },
+
+    #[error("Invalid calldata at {path}: {message}")]
+    InvalidCalldata { path: String, message: String },
+
+    #[error(transparent)]
+    Provider(#[from] ProviderError),
+}
+
+/// A function, constructor or L1 handler of a contract ABI.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct AbiFunction {
+    pub name: String,
+    pub inputs: Vec<AbiMember>,
+}
+
+/// A named and typed input, struct member or enum variant.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct AbiMember {
+    pub name: String,
+    pub r#type: String,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum AbiEntry {
+    Function(AbiFunction),
+    L1Handler(AbiFunction),
+    Constructor(AbiFunction),
+    Interface {
+        items: Vec<AbiEntry>,
+    },
+    Struct {
+        name: String,
+        members: Vec<AbiMember>,
+    },
+    Enum {
+        name: String,
+        variants: Vec<AbiMember>,
+    },
+    #[serde(other)]
+    Other,
+}
+
+/// ABI of a Sierra contract class, used to serialize the arguments of its entrypoints from JSON
+/// and to render calldata back as JSON.
+///
+/// Arguments are given as an object keyed by input name, or as an array in input order. Values
+/// are written as follows:
+/// - `felt252`, addresses and class hashes: hex or decimal strings, numbers, or short strings.
+/// - Integers: numbers, or hex or decimal strings for values beyond JSON's safe range.
+/// - `u256`: like integers, or as a `{ "low", "high" }` object.
+/// - `ByteArray`: strings.
+/// - Arrays, spans and tuples: arrays.
+/// - Structs: objects keyed by member name, or arrays in member order.
+/// - Enums: `{ "Variant": value }`, or `"Variant"` for variants without data. `null` stands for
+///   `None` in options.
+#[derive(Debug, Clone, Default)]
+pub struct ContractAbi {
+    functions: HashMap<String, AbiFunction>,
+    /// Entrypoints defined more than once with different inputs, left out of `functions`.
+    conflicts: HashSet<String>,
+    structs: HashMap<String, Vec<AbiMember>>,
+    enums: HashMap<String, Vec<AbiMember>>,
+}
+
+impl ContractAbi {
+    /// Parses the `abi` of a Sierra class, as the JSON array of its entries.
+    pub fn from_json(abi: &str) -> Result<Self, AbiError> {
+        let entries: Vec<AbiEntry> = serde_json::from_str(abi)?;
+        Ok(Self::from_entries(entries))
+    }
+
+    /// Parses the ABI of a Sierra class file, such as the `contract_class.json` compiled by
+    /// Scarb. The `abi` field can hold the entries either as an array or as a string.
+    pub fn from_sierra(class: &str) -> Result<Self, AbiError> {
+        #[derive(Deserialize)]
+        struct SierraClass {
+            abi: Value,
+        }
+
+        let class: SierraClass = serde_json::from_str(class)?;
+        match class.abi {
+            Value::String(abi) => Self::from_json(&abi),
+            abi => Ok(Self::from_entries(serde_json::from_value(abi)?)),
+        }
+    }
+
+    /// The ABI of `class`, as returned by `starknet_getClass` and `starknet_getClassAt`.
+    pub fn from_class(class: &ContractClass) -> Result<Self, AbiError> {
+        match class {
+            ContractClass::Sierra(class) => Self::from_json(&class.abi),
+            ContractClass::Legacy(_) => Err(AbiError::LegacyClass),
+        }
+    }
+
+    /// Fetches the ABI of the contract deployed at `address`.
+    pub async fn fetch<P>(provider: &P, address: Felt) -> Result<Self, AbiError>
+    where
+        P: Provider + Sync,
+    {
+        let class = provider
+            .get_class_at(BlockId::Tag(BlockTag::Pending), address)
+            .await?;
+        Self::from_class(&class)
+    }
+
+    fn from_entries(entries: Vec<AbiEntry>) -> Self {
+        let mut abi = Self::default();
+        abi.add_entries(entries);
+        abi
+    }
+
+    fn add_entries(&mut self, entries: Vec<AbiEntry>) {
+        for entry in entries {
+            match entry {
+                AbiEntry::Function(function)
+                | AbiEntry::L1Handler(function)
+                | AbiEntry::Constructor(function) => {
+                    // entrypoints are looked up by name alone, so the ones interfaces disagree on
+                    // can't be used, but the rest of the ABI still can
+                    if self.conflicts.contains(&function.name) {
+                        continue;
+                    }
+                    match self.functions.get(&function.name) {
+                        Some(existing) if *existing != function => {
+                            self.functions.remove(&function.name);
+                            self.conflicts.insert(function.name);
+                        }
+                        _ => {
+                            self.functions.insert(function.name.clone(), function);
+                        }
+                    }
+                }
+                AbiEntry::Interface { items } => self.add_entries(items),
+                AbiEntry::Struct { name, members } => {
+                    self.structs.insert(name, members);
+                }
+                AbiEntry::Enum { name, variants } => {
+                    self.enums.insert(name, variants);
+                }
+                AbiEntry::Other => {}
+            }
+        }
+    }
+
+    /// The definition of `entrypoint`, or `None` when it isn't in the ABI or is defined more
+    /// than once with different inputs.
+    pub fn function(&self, entrypoint: &str) -> Option<&AbiFunction> {
+        self.functions.get(entrypoint)
+    }
+
+    /// Serializes `args` as the calldata of `entrypoint`.
+    pub fn encode_calldata(&self, entrypoint: &str, args: &Value) -> Result<Vec<Felt>, AbiError> {
+        let function = self.get_function(entrypoint)?;
+        let mut calldata = Vec::new();
+
+        match args {
+            Value::Object(args) => {
+                for input in &function.inputs {
+                    let value = args.get(&input.name).ok_or_else(|| {
+                        invalid_argument(&input.name, "missing argument".to_string())
+                    })?;
+                    self.encode_value(&input.r#type, value, &input.name, &mut calldata)?;
+                }
+                if let Some(name) = args
+                    .keys()
+                    .find(|name| !function.inputs.iter().any(|input| &input.name == *name))
+                {
+                    return Err(invalid_argument(name, "unexpected argument".to_string()));
+                }
+            }
+            Value::Array(args) => {
+                if args.len() != function.inputs.len() {
+                    return Err(invalid_argument(
+                        entrypoint,
+                        format!(
+                            "expected {} arguments, got {}",
+                            function.inputs.len(),
+                            args.len()
+                        ),
+                    ));
+                }
+                for (input, value) in function.inputs.iter().zip(args) {
+                    self.encode_value(&input.r#type, value, &input.name, &mut calldata)?;
+                }
+            }
+            Value::Null if function.inputs.is_empty() => {}
+            _ => {
+                return Err(invalid_argument(
+                    entrypoint,
+                    "arguments must be an object or an array".to_string(),
+                ))
+            }
+        }
+
+        Ok(calldata)
+    }
+
+    /// The call of `entrypoint` on the contract at `to` with `args`.
+    pub fn encode_call(&self, to: Felt, entrypoint: &str, args: &Value) -> Result<Call, AbiError> {
+        let calldata = self.encode_calldata(entrypoint, args)?;
+        let selector = get_selector_from_name(entrypoint)
+            .map_err(|e| invalid_argument(entrypoint, format!("invalid entrypoint name: {e}")))?;
+
+        Ok(Call {
+            to,
+            selector,
+            calldata,
+        })
+    }
+
+    /// Renders the calldata of `entrypoint` as a JSON object keyed by input name, written like
+    /// the arguments [`ContractAbi::encode_calldata`] takes.
+    pub fn decode_calldata(&self, entrypoint: &str, calldata: &[Felt]) -> Result<Value, AbiError> {
+        let function = self.get_function(entrypoint)?;
+        let mut offset = 0;
+        let mut args = Map::new();
+
+        for input in &function.inputs {
+            let value = self.decode_value(&input.r#type, calldata, &mut offset, &input.name)?;
+            args.insert(input.name.clone(), value);
+        }
+        if offset != calldata.len() {
+            return Err(invalid_calldata(
+                entrypoint,
+                format!("{} unexpected trailing felts", calldata.len() - offset),
+            ));
+        }
+
+        Ok(Value::Object(args))
+    }
+
+    fn get_function(&self, entrypoint: &str) -> Result<&AbiFunction, AbiError> {
+        if self.conflicts.contains(entrypoint) {
+            return Err(AbiError::ConflictingEntrypoint(entrypoint.to_string()));
+        }
+        self.function(entrypoint)
+            .ok_or_else(|| AbiError::EntrypointNotFound(entrypoint.to_string()))
+    }
+
+    fn encode_value(
+        &self,
+        type_name: &str,
+        value: &Value,
+        path: &str,
+        calldata: &mut Vec<Felt>,
+    ) -> Result<(), AbiError> {
+        let type_name = type_name.trim();
+
+        if type_name == UNIT_TYPE {
+            return match value {
+                Value::Null => Ok(()),
+                Value::Array(values) if values.is_empty() => Ok(()),
+                _ => Err(invalid_argument(path, "expected null".to_string())),
+            };
+        }
+        if let Some(types) = tuple_types(type_name) {
+            let values = expect_array(value, path)?;
+            if values.len() != types.len() {
+                return Err(invalid_argument(
+                    path,
+                    format!("expected {} elements, got {}", types.len(), values.len()),
+                ));
+            }
+            for (i, (inner, value)) in types.iter().zip(values).enumerate() {
+                self.encode_value(inner, value, &format!("{path}.{i}"), calldata)?;
+            }
+            return Ok(());
+        }
+        if let Some(inner) = generic_argument(type_name, "core::array::Array")
+            .or_else(|| generic_argument(type_name, "core::array::Span"))
+        {
+            let values = expect_array(value, path)?;
+            calldata.push(values.len().into());
+            for (i, value) in values.iter().enumerate() {
+                self.encode_value(inner, value, &format!("{path}.{i}"), calldata)?;
+            }
+            return Ok(());
+        }
+        if let Some(inner) = generic_argument(type_name, "core::zeroable::NonZero") {
+            let start = calldata.len();
+            self.encode_value(inner, value, path, calldata)?;
+            if calldata[start..].iter().all(|felt| *felt == Felt::ZERO) {
+                return Err(invalid_argument(
+                    path,
+                    "expected a non-zero value".to_string(),
+                ));
+            }
+            return Ok(());
+        }
+
+        match type_name {
+            _ if FELT_TYPES.contains(&type_name) => calldata.push(parse_felt(value, path)?),
+            ETH_ADDRESS_TYPE => {
+                let felt = parse_felt(value, path)?;
+                if !is_eth_address(felt) {
+                    return Err(invalid_argument(
+                        path,
+                        format!("{felt:#x} is out of range for an Ethereum address"),
+                    ));
+                }
+                calldata.push(felt);
+            }
+            BOOL_TYPE => {
+                let value = match value {
+                    Value::Bool(value) => *value,
+                    Value::String(value) if value == "true" => true,
+                    Value::String(value) if value == "false" => false,
+                    _ => return Err(invalid_argument(path, "expected a boolean".to_string())),
+                };
+                calldata.push(if value { Felt::ONE } else { Felt::ZERO });
+            }
+            U256_TYPE => {
+                let value = parse_u256(value, path)?;
+                calldata.push(Felt::from(value.low_u128()));
+                calldata.push(Felt::from((value >> 128).low_u128()));
+            }
+            BYTE_ARRAY_TYPE => {
+                let Value::String(value) = value else {
+                    return Err(invalid_argument(path, "expected a string".to_string()));
+                };
+                let byte_array = ByteArray::from_string(value)
+                    .map_err(|e| invalid_argument(path, e.to_string()))?;
+                calldata.extend(ByteArray::cairo_serialize(&byte_array));
+            }
+            _ => {
+                if let Some((signed, bits)) = integer_type(type_name) {
+                    calldata.push(parse_integer(value, signed, bits, path)?);
+                } else if let Some(members) = self.structs.get(type_name) {
+                    self.encode_struct(members, value, path, calldata)?;
+                } else if let Some(variants) = self.enums.get(type_name) {
+                    self.encode_enum(variants, value, path, calldata)?;
+                } else {
+                    return Err(AbiError::UnknownType {
+                        path: path.to_string(),
+                        type_name: type_name.to_string(),
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn encode_struct(
+        &self,
+        members: &[AbiMember],
+        value: &Value,
+        path: &str,
+        calldata: &mut Vec<Felt>,
+    ) -> Result<(), AbiError> {
+        match value {
+            Value::Object(values) => {
+                for member in members {
+                    let member_path = format!("{path}.{}", member.name);
+                    let value = values.get(&member.name).ok_or_else(|| {
+                        invalid_argument(&member_path, "missing member".to_string())
+                    })?;
+                    self.encode_value(&member.r#type, value, &member_path, calldata)?;
+                }
+                if let Some(name) = values
+                    .keys()
+                    .find(|name| !members.iter().any(|member| &member.name == *name))
+                {
+                    return Err(invalid_argument(
+                        &format!("{path}.{name}"),
+                        "unexpected member".to_string(),
+                    ));
+                }
+            }
+            Value::Array(values) if values.len() == members.len() => {
+                for (member, value) in members.iter().zip(values) {
+                    let member_path = format!("{path}.{}", member.name);
+                    self.encode_value(&member.r#type, value, &member_path, calldata)?;
+                }
+            }
+            Value::Array(values) => {
+                return Err(invalid_argument(
+                    path,
+                    format!("expected {} members, got {}", members.len(), values.len()),
+                ))
+            }
+            _ => {
+                return Err(invalid_argument(
+                    path,
+                    "expected an object or an array".to_string(),
+                ))
+            }
+        }
+
+        Ok(())
+    }
+
+    fn encode_enum(
+        &self,
+        variants: &[AbiMember],
+        value: &Value,
+        path: &str,
+        calldata: &mut Vec<Felt>,
+    ) -> Result<(), AbiError> {
+        let (name, data) = match value {
+            Value::String(name) => (name.as_str(), &Value::Null),
+            Value::Object(values) if values.len() == 1 => {
+                let (name, data) = values.iter().next().expect("one variant");
+                (name.as_str(), data)
+            }
+            Value::Null => ("None", &Value::Null),
+            _ => {
+                return Err(invalid_argument(
+                    path,
+                    "expected a variant name or a single variant object".to_string(),
+                ))
+            }
+        };
+
+        let (index, variant) = variants
+            .iter()
+            .enumerate()
+            .find(|(_, variant)| variant.name == name)
+            .ok_or_else(|| invalid_argument(path, format!("unknown variant {name}")))?;
+
+        calldata.push(index.into());
+        self.encode_value(&variant.r#type, data, &format!("{path}.{name}"), calldata)
+    }
+
+    fn decode_value(
+        &self,
+        type_name: &str,
+        calldata: &[Felt],
+        offset: &mut usize,
+        path: &str,
+    ) -> Result<Value, AbiError> {
+        let type_name = type_name.trim();
+
+        if type_name == UNIT_TYPE {
+            return Ok(Value::Null);
+        }
+        if let Some(types) = tuple_types(type_name) {
+            let values = types
+                .iter()
+                .enumerate()
+                .map(|(i, inner)| {
+                    self.decode_value(inner, calldata, offset, &format!("{path}.{i}"))
+                })
+                .collect::<Result<_, _>>()?;
+            return Ok(Value::Array(values));
+        }
+        if let Some(inner) = generic_argument(type_name, "core::array::Array")
+            .or_else(|| generic_argument(type_name, "core::array::Span"))
+        {
+            let len = next_felt(calldata, offset, path)?;
+            let len = usize::try_from(len)
+                .ok()
+                .filter(|len| *len <= calldata.len() - *offset)
+                .ok_or_else(|| invalid_calldata(path, format!("invalid length {len}")))?;
+            let values = (0..len)
+                .map(|i| self.decode_value(inner, calldata, offset, &format!("{path}.{i}")))
+                .collect::<Result<_, _>>()?;
+            return Ok(Value::Array(values));
+        }
+        if let Some(inner) = generic_argument(type_name, "core::zeroable::NonZero") {
+            return self.decode_value(inner, calldata, offset, path);
+        }
+
+        match type_name {
+            _ if FELT_TYPES.contains(&type_name) => {
+                let felt = next_felt(calldata, offset, path)?;
+                Ok(Value::String(format!("{felt:#x}")))
+            }
+            ETH_ADDRESS_TYPE => match next_felt(calldata, offset, path)? {
+                felt if is_eth_address(felt) => Ok(Value::String(format!("{felt:#x}"))),
+                felt => Err(invalid_calldata(path, format!("{felt:#x} is out of range"))),
+            },
+            BOOL_TYPE => match next_felt(calldata, offset, path)? {
+                felt if felt == Felt::ZERO => Ok(Value::Bool(false)),
+                felt if felt == Felt::ONE => Ok(Value::Bool(true)),
+                felt => Err(invalid_calldata(path, format!("invalid boolean {felt:#x}"))),
+            },
+            U256_TYPE => {
+                let low = decode_unsigned(next_felt(calldata, offset, path)?, 128, path)?;
+                let high = decode_unsigned(next_felt(calldata, offset, path)?, 128, path)?;
+                let value = (U256::from(high) << 128) | U256::from(low);
+                Ok(Value::String(value.to_string()))
+            }
+            BYTE_ARRAY_TYPE => {
+                let byte_array = ByteArray::cairo_deserialize(calldata, *offset)
+                    .map_err(|e| invalid_calldata(path, e.to_string()))?;
+                *offset += ByteArray::cairo_serialized_size(&byte_array);
+                let value = byte_array
+                    .to_string()
+                    .map_err(|e| invalid_calldata(path, e.to_string()))?;
+                Ok(Value::String(value))
+            }
+            _ => {
+                if let Some((signed, bits)) = integer_type(type_name) {
+                    let felt = next_felt(calldata, offset, path)?;
+                    if signed {
+                        decode_signed(felt, bits, path)
+                    } else {
+                        let value = decode_unsigned(felt, bits, path)?;
+                        Ok(match u64::try_from(value) {
+                            Ok(value) => Value::from(value),
+                            Err(_) => Value::String(value.to_string()),
+                        })
+                    }
+                } else if let Some(members) = self.structs.get(type_name) {
+                    let mut values = Map::new();
+                    for member in members {
+                        let member_path = format!("{path}.{}", member.name);
+                        let value =
+                            self.decode_value(&member.r#type, calldata, offset, &member_path)?;
+                        values.insert(member.name.clone(), value);
+                    }
+                    Ok(Value::Object(values))
+                } else if let Some(variants) = self.enums.get(type_name) {
+                    let index = next_felt(calldata, offset, path)?;
+                    let variant = usize::try_from(index)
+                        .ok()
+                        .and_then(|index| variants.get(index))
+                        .ok_or_else(|| {
+                            invalid_calldata(path, format!("invalid variant index {index}"))
+                        })?;
+                    if variant.r#type.trim() == UNIT_TYPE {
+                        return Ok(Value::String(variant.name.clone()));
+                    }
+                    let data_path = format!("{path}.{}", variant.name);
+                    let data = self.decode_value(&variant.r#type, calldata, offset, &data_path)?;
+                    let mut values = Map::new();
+                    values.insert(variant.name.clone(), data);
+                    Ok(Value::Object(values))
+                } else {
+                    Err(AbiError::UnknownType {
+                        path: path.to_string(),
+                        type_name: type_name.to_string(),
+                    })
+                }
+            }
+        }
+    }
+}
+
+fn invalid_argument(path: &str, message: String) -> AbiError {
+    AbiError::InvalidArgument {
+        path: path.to_string(),
+        message,
+    }
+}
+
+fn invalid_calldata(path: &str, message: String) -> AbiError {
+    AbiError::InvalidCalldata {
+        path: path.to_string(),
+        message,
+    }
+}
+
+fn expect_array<'a>(value: &'a Value, path: &str) -> Result<&'a Vec<Value>, AbiError> {
+    value
+        .as_array()
+        .ok_or_else(|| invalid_argument(path, "expected an array".to_string()))
+}
+
+fn next_felt(calldata: &[Felt], offset: &mut usize, path: &str) -> Result<Felt, AbiError> {
+    let felt = calldata
+        .get(*offset)
+        .copied()
+        .ok_or_else(|| invalid_calldata(path, "calldata is too short".to_string()))?;
+    *offset += 1;
+    Ok(felt)
+}
+
+/// The argument of `type_name` if it is an instance of the generic type `generic`, e.g. `T` for
+/// `core::array::Array::<T>`.
+fn generic_argument<'a>(type_name: &'a str, generic: &str) -> Option<&'a str> {
+    type_name
+        .strip_prefix(generic)?
+        .strip_prefix("::<")?
+        .strip_suffix('>')
+}
+
+/// The element types of a tuple type such as `(core::felt252, core::integer::u128)`.
+fn tuple_types(type_name: &str) -> Option<Vec<&str>> {
+    let inner = type_name.strip_prefix('(')?.strip_suffix(')')?;
+    if inner.trim().is_empty() {
+        return None;
+    }
+
+    let mut types = Vec::new();
+    let mut depth = 0_usize;
+    let mut start = 0;
+    for (i, c) in inner.char_indices() {
+        match c {
+            '(' | '<' => depth += 1,
+            ')' | '>' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                types.push(inner[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    let last = inner[start..].trim();
+    if !last.is_empty() {
+        types.push(last);
+    }
+
+    Some(types)
+}
+
+/// Signedness and width of the Cairo integer type `type_name`.
+fn integer_type(type_name: &str) -> Option<(bool, u32)> {
+    match type_name.strip_prefix("core::integer::")? {
+        "u8" => Some((false, 8)),
+        "u16" => Some((false, 16)),
+        "u32" | "usize" => Some((false, 32)),
+        "u64" => Some((false, 64)),
+        "u128" => Some((false, 128)),
+        "i8" => Some((true, 8)),
+        "i16" => Some((true, 16)),
+        "i32" => Some((true, 32)),
+        "i64" => Some((true, 64)),
+        "i128" => Some((true, 128)),
+        _ => None,
+    }
+}
+
+fn parse_felt(value: &Value, path: &str) -> Result<Felt, AbiError> {
+    match value {
+        Value::Number(number) => number
+            .as_u64()
+            .map(Felt::from)
+            .ok_or_else(|| invalid_argument(path, format!("invalid felt {number}"))),
+        Value::String(value) => {
+            let is_number = value.starts_with("0x") || value.chars().all(|c| c.is_ascii_digit());
+            if is_number && !value.is_empty() {
+                Felt::from_str(value)
+                    .map_err(|_| invalid_argument(path, format!("invalid felt {value}")))
+            } else {
+                cairo_short_string_to_felt(value)
+                    .map_err(|e| invalid_argument(path, format!("invalid short string: {e}")))
+            }
+        }
+        _ => Err(invalid_argument(
+            path,
+            "expected a number or a string".to_string(),
+        )),
+    }
+}
+
+/// Whether `felt` fits in the 160 bits of an Ethereum address.
+fn is_eth_address(felt: Felt) -> bool {
+    felt < Felt::TWO.pow(160_u32)
+}
+
+/// Parses a number, or a hex or decimal string, keeping its sign apart from its magnitude.
+fn parse_number(value: &Value, path: &str) -> Result<(bool, U256), AbiError> {
+    let text = match value {
+        Value::Number(number) => number.to_string(),
+        Value::String(value) => value.trim().to_string(),
+        _ => {
+            return Err(invalid_argument(
+                path,
+                "expected a number or a string".to_string(),
+            ))
+        }
+    };
+    let (negative, digits) = match text.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, text.as_str()),
+    };
+
+    let magnitude = match digits.strip_prefix("0x") {
+        Some(hex) => U256::from_str_radix(hex, 16).ok(),
+        None => U256::from_dec_str(digits).ok(),
+    }
+    .filter(|_| !digits.is_empty())
+    .ok_or_else(|| invalid_argument(path, format!("invalid number {text}")))?;
+
+    Ok((negative && !magnitude.is_zero(), magnitude))
+}
+
+fn parse_u256(value: &Value, path: &str) -> Result<U256, AbiError> {
+    if let Value::Object(values) = value {
+        let limb = |name: &str| {
+            let limb_path = format!("{path}.{name}");
+            let value = values
+                .get(name)
+                .ok_or_else(|| invalid_argument(&limb_path, "missing member".to_string()))?;
+            let felt = parse_integer(value, false, 128, &limb_path)?;
+            Ok::<_, AbiError>(U256::from_big_endian(&felt.to_bytes_be()))
+        };
+        if values.len() != 2 {
+            return Err(invalid_argument(
+                path,
+                "expected only low and high".to_string(),
+            ));
+        }
+        return Ok((limb("high")? << 128) | limb("low")?);
+    }
+
+    match parse_number(value, path)? {
+        (false, value) => Ok(value),
+        (true, _) => Err(invalid_argument(
+            path,
+            "expected an unsigned value".to_string(),
+        )),
+    }
+}
+
+fn parse_integer(value: &Value, signed: bool, bits: u32, path: &str) -> Result<Felt, AbiError> {
+    let (negative, magnitude) = parse_number(value, path)?;
+    let out_of_range = || invalid_argument(path, format!("out of range for {bits} bits"));
+
+    if !signed {
+        if negative || magnitude.bits() > bits as usize {
+            return Err(out_of_range());
+        }
+        return Ok(Felt::from(magnitude.low_u128()));
+    }
+
+    // the magnitude of the minimum is one more than the magnitude of the maximum
+    let limit = U256::one() << (bits - 1);
+    if magnitude > limit || (magnitude == limit && !negative) {
+        return Err(out_of_range());
+    }
+    let felt = Felt::from(magnitude.low_u128());
+    Ok(if negative { -felt } else { felt })
+}
+
+fn decode_unsigned(felt: Felt, bits: u32, path: &str) -> Result<u128, AbiError> {
+    u128::try_from(felt)
+        .ok()
+        .filter(|value| bits == 128 || *value >> bits == 0)
+        .ok_or_else(|| invalid_calldata(path, format!("{felt:#x} is out of range")))
+}
+
+fn decode_signed(felt: Felt, bits: u32, path: &str) -> Result<Value, AbiError> {
+    let limit = 1_u128 << (bits - 1);
+    let value = match u128::try_from(felt) {
+        Ok(value) if value < limit => Some(value as i128),
+        Ok(_) => None,
+        // negative values wrap around the field prime
+        Err(_) => u128::try_from(-felt)
+            .ok()
+            .filter(|magnitude| *magnitude <= limit)
+            .map(|magnitude| 0_i128.wrapping_sub_unsigned(magnitude)),
+    }
+    .ok_or_else(|| invalid_calldata(path, format!("{felt:#x} is out of range")))?;
+
+    Ok(match i64::try_from(value) {
+        Ok(value) => Value::from(value),
+        Err(_) => Value::String(value.to_string()),
+    })
+}
@@ -0,0 +1,450 @@
+use cainome::cairo_serde::{ByteArray, CairoSerde, NonZero};
+use serde_json::json;
+use starknet::{
+    core::types::Felt,
+    macros::{felt, selector},
+};
+
+use crate::{
+    abigen::controller::{
+        Session, Signer as AbiSigner, SignerSignature, StarknetSignature, StarknetSigner,
+    },
+    artifacts::Version,
+    signers::{Owner, Signer},
+    tests::runners::katana::KatanaRunner,
+};
+
+use super::{AbiError, ContractAbi};
+
+const CONTROLLER_CLASS: &str =
+    include_str!("../artifacts/classes/controller.latest.contract_class.json");
+
+const GAME_ABI: &str = r#"
+[
+  {
+    "type": "struct",
+    "name": "core::integer::u256",
+    "members": [
+      { "name": "low", "type": "core::integer::u128" },
+      { "name": "high", "type": "core::integer::u128" }
+    ]
+  },
+  {
+    "type": "struct",
+    "name": "game::Position",
+    "members": [
+      { "name": "x", "type": "core::integer::i32" },
+      { "name": "y", "type": "core::integer::i32" }
+    ]
+  },
+  {
+    "type": "enum",
+    "name": "game::Move",
+    "variants": [
+      { "name": "Stay", "type": "()" },
+      { "name": "Walk", "type": "game::Position" },
+      { "name": "Teleport", "type": "(core::felt252, core::integer::u8)" }
+    ]
+  },
+  {
+    "type": "enum",
+    "name": "core::option::Option::<core::integer::u64>",
+    "variants": [
+      { "name": "Some", "type": "core::integer::u64" },
+      { "name": "None", "type": "()" }
+    ]
+  },
+  {
+    "type": "enum",
+    "name": "core::bool",
+    "variants": [
+      { "name": "False", "type": "()" },
+      { "name": "True", "type": "()" }
+    ]
+  },
+  {
+    "type": "interface",
+    "name": "game::IGame",
+    "items": [
+      {
+        "type": "function",
+        "name": "play",
+        "inputs": [
+          { "name": "player", "type": "core::starknet::contract_address::ContractAddress" },
+          { "name": "name", "type": "core::byte_array::ByteArray" },
+          { "name": "moves", "type": "core::array::Span::<game::Move>" },
+          { "name": "stake", "type": "core::integer::u256" },
+          { "name": "deadline", "type": "core::option::Option::<core::integer::u64>" },
+          { "name": "ranked", "type": "core::bool" }
+        ],
+        "outputs": [],
+        "state_mutability": "external"
+      },
+      {
+        "type": "function",
+        "name": "score",
+        "inputs": [],
+        "outputs": [{ "type": "core::integer::u128" }],
+        "state_mutability": "view"
+      }
+    ]
+  },
+  {
+    "type": "event",
+    "name": "game::Played",
+    "kind": "struct",
+    "members": []
+  }
+]
+"#;
+
+fn game_abi() -> ContractAbi {
+    ContractAbi::from_json(GAME_ABI).unwrap()
+}
+
+fn play_args() -> serde_json::Value {
+    json!({
+        "player": "0x1234",
+        "name": "Loot Survivor adventurer",
+        "moves": [
+            "Stay",
+            { "Walk": { "x": -1, "y": 2 } },
+            { "Teleport": ["home", 3] }
+        ],
+        "stake": "0x100000000000000000000000000000002",
+        "deadline": { "Some": 1700000000 },
+        "ranked": true
+    })
+}
+
+#[test]
+fn test_encode_play() {
+    let calldata = game_abi().encode_calldata("play", &play_args()).unwrap();
+
+    let name = ByteArray::from_string("Loot Survivor adventurer").unwrap();
+    let mut expected = vec![felt!("0x1234")];
+    expected.extend(ByteArray::cairo_serialize(&name));
+    expected.extend([
+        Felt::from(3_u32),
+        // Stay
+        Felt::ZERO,
+        // Walk, with x wrapping around the field prime
+        Felt::ONE,
+        -Felt::ONE,
+        Felt::TWO,
+        // Teleport
+        Felt::TWO,
+        felt!("0x686f6d65"),
+        Felt::from(3_u32),
+        // stake
+        Felt::TWO,
+        Felt::ONE,
+        // deadline
+        Felt::ZERO,
+        Felt::from(1700000000_u64),
+        // ranked
+        Felt::ONE,
+    ]);
+    assert_eq!(calldata, expected);
+}
+
+#[test]
+fn test_encode_positional_arguments() {
+    let abi = game_abi();
+    let args = play_args();
+    let positional = json!([
+        args["player"],
+        args["name"],
+        args["moves"],
+        { "low": 2, "high": "1" },
+        null,
+        false
+    ]);
+
+    let calldata = abi.encode_calldata("play", &positional).unwrap();
+    let expected = abi
+        .encode_calldata(
+            "play",
+            &json!({
+                "player": args["player"],
+                "name": args["name"],
+                "moves": args["moves"],
+                "stake": args["stake"],
+                "deadline": "None",
+                "ranked": false
+            }),
+        )
+        .unwrap();
+    assert_eq!(calldata, expected);
+    assert_eq!(
+        abi.encode_calldata("score", &serde_json::Value::Null)
+            .unwrap(),
+        Vec::<Felt>::new()
+    );
+}
+
+#[test]
+fn test_decode_play() {
+    let abi = game_abi();
+    let calldata = abi.encode_calldata("play", &play_args()).unwrap();
+
+    assert_eq!(
+        abi.decode_calldata("play", &calldata).unwrap(),
+        json!({
+            "player": "0x1234",
+            "name": "Loot Survivor adventurer",
+            "moves": [
+                "Stay",
+                { "Walk": { "x": -1, "y": 2 } },
+                { "Teleport": ["0x686f6d65", 3] }
+            ],
+            "stake": "340282366920938463463374607431768211458",
+            "deadline": { "Some": 1700000000 },
+            "ranked": true
+        })
+    );
+
+    let decoded = abi.decode_calldata("play", &calldata).unwrap();
+    assert_eq!(abi.encode_calldata("play", &decoded).unwrap(), calldata);
+}
+
+#[test]
+fn test_decode_invalid_calldata() {
+    let abi = game_abi();
+    let mut calldata = abi.encode_calldata("play", &play_args()).unwrap();
+
+    calldata.push(Felt::ZERO);
+    assert!(matches!(
+        abi.decode_calldata("play", &calldata),
+        Err(AbiError::InvalidCalldata { .. })
+    ));
+
+    calldata.truncate(calldata.len() - 2);
+    assert!(matches!(
+        abi.decode_calldata("play", &calldata),
+        Err(AbiError::InvalidCalldata { .. })
+    ));
+}
+
+#[test]
+fn test_invalid_arguments() {
+    let abi = game_abi();
+    let invalid_path = |args: serde_json::Value| match abi.encode_calldata("play", &args) {
+        Err(AbiError::InvalidArgument { path, .. }) => path,
+        result => panic!("unexpected result {result:?}"),
+    };
+
+    let mut args = play_args();
+    args["moves"][1]["Walk"]["y"] = json!(2147483648_u64);
+    assert_eq!(invalid_path(args), "moves.1.Walk.y");
+
+    let mut args = play_args();
+    args["moves"][2] = json!({ "Fly": null });
+    assert_eq!(invalid_path(args), "moves.2");
+
+    let mut args = play_args();
+    args["stake"] = json!("-1");
+    assert_eq!(invalid_path(args), "stake");
+
+    let mut args = play_args();
+    args["moves"][1]["Walk"]["z"] = json!(0);
+    assert_eq!(invalid_path(args), "moves.1.Walk.z");
+
+    let mut args = play_args();
+    args.as_object_mut().unwrap().remove("ranked");
+    assert_eq!(invalid_path(args), "ranked");
+
+    let mut args = play_args();
+    args["referrer"] = json!("0x1");
+    assert_eq!(invalid_path(args), "referrer");
+
+    assert!(matches!(
+        abi.encode_calldata("fold", &json!({})),
+        Err(AbiError::EntrypointNotFound(_))
+    ));
+}
+
+#[test]
+fn test_eth_address_range() {
+    let abi = ContractAbi::from_json(
+        r#"[{
+            "type": "function",
+            "name": "bridge",
+            "inputs": [{ "name": "to", "type": "core::starknet::eth_address::EthAddress" }],
+            "outputs": [],
+            "state_mutability": "external"
+        }]"#,
+    )
+    .unwrap();
+
+    let address = "0xcd2a3d9f938e13cd947ec05abc7fe734df8dd826";
+    let calldata = abi
+        .encode_calldata("bridge", &json!({ "to": address }))
+        .unwrap();
+    assert_eq!(
+        calldata,
+        vec![felt!("0xcd2a3d9f938e13cd947ec05abc7fe734df8dd826")]
+    );
+    assert_eq!(
+        abi.decode_calldata("bridge", &calldata).unwrap(),
+        json!({ "to": address })
+    );
+
+    let too_large = "0x10000000000000000000000000000000000000000";
+    assert!(matches!(
+        abi.encode_calldata("bridge", &json!({ "to": too_large })),
+        Err(AbiError::InvalidArgument { path, .. }) if path == "to"
+    ));
+    assert!(matches!(
+        abi.decode_calldata(
+            "bridge",
+            &[felt!("0x10000000000000000000000000000000000000000")]
+        ),
+        Err(AbiError::InvalidCalldata { .. })
+    ));
+}
+
+#[test]
+fn test_conflicting_entrypoints() {
+    let interface = |name: &str, input: &str| {
+        json!({
+            "type": "interface",
+            "name": name,
+            "items": [{
+                "type": "function",
+                "name": "transfer",
+                "inputs": [{ "name": "amount", "type": input }],
+                "outputs": [],
+                "state_mutability": "external"
+            }]
+        })
+    };
+
+    // the same definition exposed twice is fine
+    let abi = json!([
+        interface("token::IToken", "core::integer::u256"),
+        interface("token::ITokenCamel", "core::integer::u256"),
+    ]);
+    assert!(ContractAbi::from_json(&abi.to_string()).is_ok());
+
+    // a conflict only rejects the entrypoint it's about
+    let mut abi = json!([
+        interface("token::IToken", "core::integer::u256"),
+        interface("token::ILegacyToken", "core::felt252"),
+        interface("token::ILegacyTokenCamel", "core::integer::u256"),
+    ]);
+    abi.as_array_mut().unwrap().push(json!({
+        "type": "function",
+        "name": "approve",
+        "inputs": [{ "name": "amount", "type": "core::felt252" }],
+        "outputs": [],
+        "state_mutability": "external"
+    }));
+    let abi = ContractAbi::from_json(&abi.to_string()).unwrap();
+
+    assert!(abi.function("transfer").is_none());
+    assert!(matches!(
+        abi.encode_calldata("transfer", &json!({ "amount": 1 })),
+        Err(AbiError::ConflictingEntrypoint(name)) if name == "transfer"
+    ));
+    assert!(matches!(
+        abi.decode_calldata("transfer", &[Felt::ONE]),
+        Err(AbiError::ConflictingEntrypoint(name)) if name == "transfer"
+    ));
+    assert_eq!(
+        abi.encode_calldata("approve", &json!({ "amount": 1 }))
+            .unwrap(),
+        vec![Felt::ONE]
+    );
+}
+
+#[test]
+fn test_encode_controller_calls() {
+    let abi = ContractAbi::from_sierra(CONTROLLER_CLASS).unwrap();
+
+    let session = Session {
+        expires_at: 1700000000,
+        allowed_policies_root: felt!("0x1111"),
+        metadata_hash: felt!("0x2222"),
+        session_key_guid: felt!("0x3333"),
+        guardian_key_guid: Felt::ZERO,
+    };
+    let call = abi
+        .encode_call(
+            felt!("0x42"),
+            "register_session",
+            &json!({
+                "session": {
+                    "expires_at": 1700000000,
+                    "allowed_policies_root": "0x1111",
+                    "metadata_hash": "0x2222",
+                    "session_key_guid": "0x3333",
+                    "guardian_key_guid": "0x0"
+                },
+                "guid_or_address": "0x42"
+            }),
+        )
+        .unwrap();
+    let mut expected = Session::cairo_serialize(&session);
+    expected.push(felt!("0x42"));
+    assert_eq!(call.to, felt!("0x42"));
+    assert_eq!(call.selector, selector!("register_session"));
+    assert_eq!(call.calldata, expected);
+
+    let signer = StarknetSigner {
+        pubkey: NonZero::new(felt!("0x1234")).unwrap(),
+    };
+    let signature = SignerSignature::Starknet((
+        signer.clone(),
+        StarknetSignature {
+            r: felt!("0x5"),
+            s: felt!("0x6"),
+        },
+    ));
+    let args = json!({
+        "owner": { "Starknet": { "pubkey": "0x1234" } },
+        "signature": { "Starknet": [{ "pubkey": "0x1234" }, { "r": "0x5", "s": "0x6" }] }
+    });
+    let calldata = abi.encode_calldata("add_owner", &args).unwrap();
+    let mut expected = AbiSigner::cairo_serialize(&AbiSigner::Starknet(signer));
+    expected.extend(SignerSignature::cairo_serialize(&signature));
+    assert_eq!(calldata, expected);
+    assert_eq!(abi.decode_calldata("add_owner", &calldata).unwrap(), args);
+
+    assert!(matches!(
+        abi.encode_calldata(
+            "add_owner",
+            &json!({
+                "owner": { "Starknet": { "pubkey": "0x0" } },
+                "signature": args["signature"]
+            })
+        ),
+        Err(AbiError::InvalidArgument { path, .. }) if path == "owner.Starknet.pubkey"
+    ));
+}
+
+#[tokio::test]
+async fn test_fetch_abi() {
+    let runner = KatanaRunner::load();
+    let controller = runner
+        .deploy_controller(
+            "username".to_owned(),
+            Owner::Signer(Signer::new_starknet_random()),
+            Version::LATEST,
+        )
+        .await;
+
+    let abi = ContractAbi::fetch(runner.client(), controller.address)
+        .await
+        .unwrap();
+    let call = abi
+        .encode_call(
+            controller.address,
+            "register_external_owner",
+            &json!(["0x1234"]),
+        )
+        .unwrap();
+
+    assert_eq!(call.selector, selector!("register_external_owner"));
+    assert_eq!(call.calldata, vec![felt!("0x1234")]);
+}
@@ -106,7 +106,8 @@ macro_rules! impl_account {
                 _: &starknet::accounts::RawLegacyDeclaration,
                 _: bool,
             ) -> Result<Vec<starknet::core::types::Felt>, Self::SignError> {
-                unimplemented!("sign_legacy_declaration")
+                // the contract only validates declarations from version 2 on
+                Err($crate::signers::SignError::LegacyDeclarationUnsupported)
             }
 
             fn execute_v1(&self, calls: Vec<starknet::core::types::Call>) -> starknet::accounts::ExecutionV1<Self> {
@@ -26,6 +26,20 @@ impl OutsideExecution {
             OutsideExecution::V3(v3) => v3.caller,
         }
     }
+
+    pub fn execute_after(&self) -> u64 {
+        match self {
+            OutsideExecution::V2(v2) => v2.execute_after,
+            OutsideExecution::V3(v3) => v3.execute_after,
+        }
+    }
+
+    pub fn execute_before(&self) -> u64 {
+        match self {
+            OutsideExecution::V2(v2) => v2.execute_before,
+            OutsideExecution::V3(v3) => v3.execute_before,
+        }
+    }
 }
 
 impl Serialize for OutsideExecution {
@@ -144,7 +158,7 @@ where
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct SignedOutsideExecution {
     pub outside_execution: OutsideExecution,
     pub signature: Vec<Felt>,
@@ -15,14 +15,14 @@ use crate::{
     constants::GUARDIAN_SIGNER,
     hash::StructHashRev1,
     impl_account, impl_execution_encoder,
-    provider::CartridgeJsonRpcProvider,
+    provider::{shared::SharedProvider, CartridgeProvider},
     signers::{HashSigner, SessionPolicyError, SignError, Signer},
 };
 
 use super::{hash::Session, policy::Policy, AccountHashAndCallsSigner, TypedData};
 
 pub struct SessionAccount {
-    provider: CartridgeJsonRpcProvider,
+    provider: SharedProvider,
     signer: Signer,
     address: Felt,
     chain_id: Felt,
@@ -32,16 +32,19 @@ pub struct SessionAccount {
 }
 
 impl SessionAccount {
-    pub fn new(
-        provider: CartridgeJsonRpcProvider,
+    pub fn new<P>(
+        provider: P,
         signer: Signer,
         address: Felt,
         chain_id: Felt,
         session_authorization: Vec<Felt>,
         session: Session,
-    ) -> Self {
+    ) -> Self
+    where
+        P: CartridgeProvider + Send + Sync + 'static,
+    {
         Self {
-            provider,
+            provider: SharedProvider::new(provider),
             signer,
             address,
             chain_id,
@@ -51,14 +54,17 @@ impl SessionAccount {
         }
     }
 
-    pub fn new_as_registered(
-        provider: CartridgeJsonRpcProvider,
+    pub fn new_as_registered<P>(
+        provider: P,
         signer: Signer,
         address: Felt,
         chain_id: Felt,
         owner_guid: Felt,
         session: Session,
-    ) -> Self {
+    ) -> Self
+    where
+        P: CartridgeProvider + Send + Sync + 'static,
+    {
         Self::new(
             provider,
             signer,
@@ -156,7 +162,7 @@ impl_execution_encoder!(SessionAccount);
 impl_account!(SessionAccount, |_, _| false);
 
 impl ConnectedAccount for SessionAccount {
-    type Provider = CartridgeJsonRpcProvider;
+    type Provider = SharedProvider;
 
     fn provider(&self) -> &Self::Provider {
         &self.provider
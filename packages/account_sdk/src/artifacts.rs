@@ -4,7 +4,7 @@ use starknet::macros::felt;
 use starknet_types_core::felt::Felt;
 use std::collections::HashMap;
 
-#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum Version {
     V1_0_4,
     V1_0_5,
@@ -1,26 +1,30 @@
 use crate::abigen::controller::SignerSignature;
 use crate::account::session::policy::Policy;
 use crate::account::{AccountHashAndCallsSigner, CallEncoder};
-use crate::constants::{ETH_CONTRACT_ADDRESS, WEBAUTHN_GAS};
+use crate::constants::WEBAUTHN_GAS;
 use crate::errors::ControllerError;
 use crate::factory::ControllerFactory;
+use crate::fees::{felt_to_u128, FeeToken, ResourceBounds};
 use crate::impl_account;
-use crate::provider::CartridgeJsonRpcProvider;
+use crate::provider::shared::SharedProvider;
 use crate::signers::Owner;
-use crate::storage::{ControllerMetadata, Storage, StorageBackend};
+use crate::storage::selectors::Selectors;
+use crate::storage::{ControllerMetadata, SharedStorage, StorageBackend, StorageValue};
+use crate::transaction_waiter::{TransactionWaiter, WaitOptions};
 use crate::typed_data::TypedData;
 use crate::{
     abigen::{self},
     signers::{HashSigner, SignError},
 };
 use async_trait::async_trait;
-use cainome::cairo_serde::{CairoSerde, U256};
-use starknet::accounts::{AccountDeploymentV1, AccountError, AccountFactory, ExecutionV1};
+use cainome::cairo_serde::CairoSerde;
+use starknet::accounts::{
+    AccountDeploymentV1, AccountDeploymentV3, AccountError, AccountFactory, ExecutionV1,
+};
 use starknet::core::types::{
-    BlockTag, Call, FeeEstimate, FunctionCall, InvokeTransactionResult, StarknetError,
+    BlockTag, Call, FeeEstimate, InvokeTransactionResult, StarknetError,
+    TransactionReceiptWithBlockInfo,
 };
-use starknet::core::utils::cairo_short_string_to_felt;
-use starknet::macros::selector;
 use starknet::providers::{Provider, ProviderError};
 use starknet::signers::SignerInteractivityContext;
 use starknet::{
@@ -29,6 +33,13 @@ use starknet::{
 };
 use url::Url;
 
+pub mod builder;
+mod counterfactual;
+pub mod nonce;
+
+pub use builder::ControllerBuilder;
+pub use nonce::NonceManager;
+
 #[cfg(all(test, not(target_arch = "wasm32")))]
 #[path = "controller_test.rs"]
 mod controller_test;
@@ -42,16 +53,23 @@ pub struct Controller {
     pub rpc_url: Url,
     pub username: String,
     pub(crate) salt: Felt,
-    pub provider: CartridgeJsonRpcProvider,
+    pub provider: SharedProvider,
     pub(crate) owner: Owner,
+    pub(crate) guardian: Felt,
+    deploy_on_first_use: bool,
     contract: Option<Box<abigen::controller::Controller<Self>>>,
     factory: ControllerFactory,
-    pub storage: Storage,
-    nonce: Felt,
+    pub storage: SharedStorage,
+    nonce: NonceManager,
     pub(crate) execute_from_outside_nonce: (Felt, u128),
 }
 
 impl Controller {
+    /// Creates a Controller talking to `rpc_url` and stores it as the active account of `app_id`.
+    /// Use [`ControllerBuilder`] to inject a provider or storage, or to skip storing it.
+    ///
+    /// Fails when the username isn't a valid short string, or when the Controller can't be
+    /// stored.
     pub fn new(
         app_id: String,
         username: String,
@@ -60,56 +78,19 @@ impl Controller {
         owner: Owner,
         address: Felt,
         chain_id: Felt,
-    ) -> Self {
-        let provider = CartridgeJsonRpcProvider::new(rpc_url.clone());
-        let salt = cairo_short_string_to_felt(&username).unwrap();
-
-        let factory = ControllerFactory::new(class_hash, chain_id, owner.clone(), provider.clone());
-
-        let mut controller = Self {
-            app_id: app_id.clone(),
-            address,
-            chain_id,
-            class_hash,
-            rpc_url,
-            username,
-            salt,
-            provider,
-            owner,
-            contract: None,
-            factory,
-            storage: Storage::default(),
-            nonce: Felt::ZERO,
-            execute_from_outside_nonce: (
-                starknet::signers::SigningKey::from_random().secret_scalar(),
-                0,
-            ),
-        };
-
-        let contract = Box::new(abigen::controller::Controller::new(
-            address,
-            controller.clone(),
-        ));
-        controller.contract = Some(contract);
-
-        controller
-            .storage
-            .set_controller(
-                app_id.as_str(),
-                address,
-                ControllerMetadata::from(&controller),
-            )
-            .expect("Should store controller");
-
-        controller
+    ) -> Result<Self, ControllerError> {
+        ControllerBuilder::new(
+            app_id, username, class_hash, rpc_url, owner, address, chain_id,
+        )
+        .build()
     }
 
     pub fn from_storage(app_id: String) -> Result<Option<Self>, ControllerError> {
-        let storage = Storage::default();
+        let storage = SharedStorage::default();
         let metadata = storage.controller(&app_id).map_err(ControllerError::from)?;
         if let Some(m) = metadata {
             let rpc_url = Url::parse(&m.rpc_url).map_err(ControllerError::from)?;
-            Ok(Some(Controller::new(
+            ControllerBuilder::new(
                 app_id,
                 m.username,
                 m.class_hash,
@@ -117,7 +98,10 @@ impl Controller {
                 m.owner.try_into().map_err(ControllerError::from)?,
                 m.address,
                 m.chain_id,
-            )))
+            )
+            .with_storage(storage)
+            .build()
+            .map(Some)
         } else {
             Ok(None)
         }
@@ -127,6 +111,12 @@ impl Controller {
         self.factory.deploy_v1(self.salt)
     }
 
+    /// Deploys a Controller owned by an [`Owner::Account`] through the connected owner
+    /// account. See [`ControllerFactory::deploy_through_owner`].
+    pub async fn deploy_through_owner(&self) -> Result<Felt, ControllerError> {
+        Ok(self.factory.deploy_through_owner(self.salt).await?)
+    }
+
     pub fn disconnect(&mut self) -> Result<(), ControllerError> {
         self.storage.clear().map_err(ControllerError::from)
     }
@@ -139,26 +129,50 @@ impl Controller {
         self.owner = owner;
     }
 
+    /// Rewrites the stored metadata of the Controller after its owner or class hash changed,
+    /// keeping the stored owners. Nothing is written when the Controller isn't stored.
+    pub(crate) fn update_stored_metadata(&mut self) -> Result<(), ControllerError> {
+        self.update_stored_owners(|_| {})
+    }
+
+    /// Rewrites the stored metadata of the Controller like
+    /// [`Controller::update_stored_metadata`], with its owners changed by `update`.
+    pub(crate) fn update_stored_owners(
+        &mut self,
+        update: impl FnOnce(&mut Vec<Felt>),
+    ) -> Result<(), ControllerError> {
+        let key = Selectors::account(&self.address, &self.chain_id);
+        if let Some(StorageValue::Controller(stored)) = self.storage.get(&key)? {
+            let mut metadata = ControllerMetadata::from(&*self);
+            if !stored.owners.is_empty() {
+                metadata.owners = stored.owners;
+            }
+            update(&mut metadata.owners);
+            self.storage
+                .set(&key, &StorageValue::Controller(metadata))?;
+        }
+        Ok(())
+    }
+
     pub fn owner_guid(&self) -> Felt {
         self.owner.clone().into()
     }
 
-    async fn build_not_deployed_err(&self) -> ControllerError {
-        let balance = match self.eth_balance().await {
+    pub fn deploy_v3(&self) -> AccountDeploymentV3<'_, ControllerFactory> {
+        self.factory.deploy_v3(self.salt)
+    }
+
+    async fn build_not_deployed_err(&self, fee_token: FeeToken) -> ControllerError {
+        let balance = match self.fee_token_balance(fee_token).await {
             Ok(balance) => balance,
             Err(e) => return e,
         };
 
-        let mut fee_estimate = match ControllerFactory::new(
-            self.class_hash,
-            self.chain_id,
-            self.owner.clone(),
-            self.provider.clone(),
-        )
-        .deploy_v1(self.salt)
-        .estimate_fee()
-        .await
-        {
+        let fee_estimate = match fee_token {
+            FeeToken::Eth => self.deploy().estimate_fee().await,
+            FeeToken::Strk => self.deploy_v3().estimate_fee().await,
+        };
+        let mut fee_estimate = match fee_estimate {
             Ok(estimate) => estimate,
             Err(e) => return ControllerError::from(e),
         };
@@ -180,12 +194,36 @@ impl Controller {
             .estimate_fee()
             .await;
 
-        let balance = self.eth_balance().await?;
+        self.check_fee_estimate(&calls, est, FeeToken::Eth).await
+    }
+
+    /// Estimates the fee of executing `calls` in a V3 transaction, paid in STRK. Use
+    /// [`ResourceBounds::from_fee_estimate`] to get bounds to execute them with.
+    pub async fn estimate_invoke_fee_v3(
+        &self,
+        calls: Vec<Call>,
+    ) -> Result<FeeEstimate, ControllerError> {
+        let est = self
+            .execute_v3(calls.clone())
+            .nonce(Felt::from(u64::MAX))
+            .estimate_fee()
+            .await;
+
+        self.check_fee_estimate(&calls, est, FeeToken::Strk).await
+    }
+
+    async fn check_fee_estimate(
+        &self,
+        calls: &[Call],
+        est: Result<FeeEstimate, AccountError<SignError>>,
+        fee_token: FeeToken,
+    ) -> Result<FeeEstimate, ControllerError> {
+        let balance = self.fee_token_balance(fee_token).await?;
 
         match est {
             Ok(mut fee_estimate) => {
                 if self
-                    .session_metadata(&Policy::from_calls(&calls), None)
+                    .session_metadata(&Policy::from_calls(calls), None)
                     .map_or(true, |(_, metadata)| !metadata.is_registered)
                 {
                     fee_estimate.overall_fee += WEBAUTHN_GAS * fee_estimate.gas_price;
@@ -213,7 +251,10 @@ impl Controller {
                         .execution_error
                         .contains(&format!("{:x} is not deployed.", self.address))
                     {
-                        return Err(self.build_not_deployed_err().await);
+                        if self.deploy_on_first_use {
+                            return self.estimate_deploy_and_invoke_fee(calls, fee_token).await;
+                        }
+                        return Err(self.build_not_deployed_err(fee_token).await);
                     }
                 }
                 Err(ControllerError::AccountError(e))
@@ -230,22 +271,62 @@ impl Controller {
             return self.execute_from_outside_v3(calls).await;
         }
 
+        self.execute_with_max_fee(calls, max_fee).await
+    }
+
+    /// Executes `calls` in a V1 transaction paying at most `max_fee` in ETH. Unlike
+    /// [`Controller::execute`] it never falls back to an outside execution, so it only needs a
+    /// shared reference and can be called concurrently, each call getting its own nonce.
+    pub async fn execute_with_max_fee(
+        &self,
+        calls: Vec<Call>,
+        max_fee: Felt,
+    ) -> Result<InvokeTransactionResult, ControllerError> {
+        self.send_with_fee(calls, TransactionFee::V1(max_fee)).await
+    }
+
+    /// Executes `calls` in a V3 transaction paying its fee in STRK, within `resource_bounds`.
+    pub async fn execute_with_resource_bounds(
+        &self,
+        calls: Vec<Call>,
+        resource_bounds: ResourceBounds,
+    ) -> Result<InvokeTransactionResult, ControllerError> {
+        self.send_with_fee(calls, TransactionFee::V3(resource_bounds))
+            .await
+    }
+
+    async fn send_with_fee(
+        &self,
+        calls: Vec<Call>,
+        mut fee: TransactionFee,
+    ) -> Result<InvokeTransactionResult, ControllerError> {
         let mut retry_count = 0;
         let max_retries = 1;
+        let mut deployed = false;
 
         loop {
-            let nonce = self.get_nonce().await?;
-            let result = self
-                .execute_v1(calls.clone())
-                .nonce(nonce)
-                .max_fee(max_fee)
-                .send()
-                .await;
+            let nonce = self.nonce.reserve(&self.provider, self.address).await?;
+            let result = match fee {
+                TransactionFee::V1(max_fee) => {
+                    self.execute_v1(calls.clone())
+                        .nonce(nonce)
+                        .max_fee(max_fee)
+                        .send()
+                        .await
+                }
+                TransactionFee::V3(resource_bounds) => {
+                    self.execute_v3(calls.clone())
+                        .nonce(nonce)
+                        .gas(resource_bounds.l1_gas.max_amount)
+                        .gas_price(resource_bounds.l1_gas.max_price_per_unit)
+                        .send()
+                        .await
+                }
+            };
 
             match result {
                 Ok(tx_result) => {
-                    // Update nonce
-                    self.nonce += Felt::ONE;
+                    self.nonce.submitted(nonce, tx_result.transaction_hash);
 
                     // Update is_registered to true after successful execution with a session
                     if let Some((key, metadata)) =
@@ -254,12 +335,13 @@ impl Controller {
                         if !metadata.is_registered {
                             let mut updated_metadata = metadata;
                             updated_metadata.is_registered = true;
-                            self.storage.set_session(&key, updated_metadata)?;
+                            self.storage.clone().set_session(&key, updated_metadata)?;
                         }
                     }
                     return Ok(tx_result);
                 }
                 Err(e) => {
+                    self.nonce.rejected(nonce);
                     match &e {
                         AccountError::Provider(ProviderError::StarknetError(
                             StarknetError::TransactionExecutionError(data),
@@ -267,18 +349,19 @@ impl Controller {
                             .execution_error
                             .contains(&format!("{:x} is not deployed.", self.address)) =>
                         {
-                            return Err(self.build_not_deployed_err().await);
+                            if self.deploy_on_first_use && !deployed {
+                                fee = self.deploy_for_first_use(&calls, fee).await?;
+                                deployed = true;
+                                continue;
+                            }
+                            return Err(self.build_not_deployed_err(fee.token()).await);
                         }
                         AccountError::Provider(ProviderError::StarknetError(
                             StarknetError::InvalidTransactionNonce,
                         )) => {
                             if retry_count < max_retries {
                                 // Refetch nonce from the provider
-                                let new_nonce = self
-                                    .provider
-                                    .get_nonce(self.block_id(), self.address())
-                                    .await?;
-                                self.nonce = new_nonce;
+                                self.nonce.resync(&self.provider, self.address).await?;
                                 retry_count += 1;
                                 continue;
                             }
@@ -290,16 +373,17 @@ impl Controller {
                                 && retry_count < max_retries
                             {
                                 // Refetch nonce from the provider
-                                let new_nonce = self
-                                    .provider
-                                    .get_nonce(self.block_id(), self.address())
-                                    .await?;
-                                self.nonce = new_nonce;
+                                self.nonce.resync(&self.provider, self.address).await?;
                                 retry_count += 1;
                                 continue;
                             } else if data.contains(&format!("{:x} is not deployed.", self.address))
                             {
-                                return Err(self.build_not_deployed_err().await);
+                                if self.deploy_on_first_use && !deployed {
+                                    fee = self.deploy_for_first_use(&calls, fee).await?;
+                                    deployed = true;
+                                    continue;
+                                }
+                                return Err(self.build_not_deployed_err(fee.token()).await);
                             }
                         }
                         _ => {}
@@ -310,6 +394,36 @@ impl Controller {
         }
     }
 
+    /// Executes `calls` like [`Controller::execute`] and waits for the transaction to be accepted
+    /// on L2, for up to 60 seconds. A reverted transaction is returned as a `TransactionReverted`
+    /// error.
+    pub async fn execute_and_wait(
+        &mut self,
+        calls: Vec<Call>,
+        max_fee: Felt,
+    ) -> Result<TransactionReceiptWithBlockInfo, ControllerError> {
+        self.execute_and_wait_with_options(calls, max_fee, WaitOptions::default())
+            .await
+    }
+
+    /// Executes `calls` like [`Controller::execute_and_wait`], waiting for the finality status
+    /// and for as long as `options` say, e.g. for acceptance on L1 or on a slow appchain.
+    pub async fn execute_and_wait_with_options(
+        &mut self,
+        calls: Vec<Call>,
+        max_fee: Felt,
+        options: WaitOptions,
+    ) -> Result<TransactionReceiptWithBlockInfo, ControllerError> {
+        let result = self.execute(calls, max_fee).await?;
+        let receipt = TransactionWaiter::new(result.transaction_hash, &self.provider)
+            .with_finality(options.finality)
+            .with_timeout(options.timeout)
+            .wait()
+            .await?;
+
+        Ok(receipt)
+    }
+
     pub async fn delegate_account(&self) -> Result<Felt, ControllerError> {
         self.contract()
             .delegate_account()
@@ -324,24 +438,20 @@ impl Controller {
             .set_delegate_account(&delegate_address.into())
     }
 
+    /// ETH balance, saturating at `u128::MAX`. Use [`Controller::token_balance`] for the full
+    /// amount.
     pub async fn eth_balance(&self) -> Result<u128, ControllerError> {
-        let address = self.address;
-        let result = self
-            .provider
-            .call(
-                FunctionCall {
-                    contract_address: ETH_CONTRACT_ADDRESS,
-                    entry_point_selector: selector!("balanceOf"),
-                    calldata: vec![address],
-                },
-                BlockId::Tag(BlockTag::Pending),
-            )
-            .await
-            .map_err(ControllerError::ProviderError)?;
+        self.fee_token_balance(FeeToken::Eth).await
+    }
 
-        U256::cairo_deserialize(&result, 0)
-            .map_err(ControllerError::CairoSerde)
-            .map(|v| v.low)
+    /// Balance of `fee_token`, saturating at `u128::MAX` as fees are compared to it in a `u128`.
+    async fn fee_token_balance(&self, fee_token: FeeToken) -> Result<u128, ControllerError> {
+        let balance = self.token_balance(fee_token.address()).await?;
+        Ok(if balance.high == 0 {
+            balance.low
+        } else {
+            u128::MAX
+        })
     }
 
     pub async fn sign_message(&self, data: TypedData) -> Result<Vec<Felt>, SignError> {
@@ -351,22 +461,49 @@ impl Controller {
     }
 
     async fn get_nonce(&self) -> Result<Felt, ProviderError> {
-        let current_nonce = self.nonce;
-
-        if current_nonce == Felt::ZERO {
-            match self
-                .provider
-                .get_nonce(self.block_id(), self.address())
-                .await
-            {
-                Ok(nonce) => Ok(nonce),
-                Err(ProviderError::StarknetError(StarknetError::ContractNotFound)) => {
-                    Ok(Felt::ZERO)
-                }
-                Err(e) => Err(e),
+        self.nonce.next(&self.provider, self.address).await
+    }
+
+    /// Hands out the nonces of the Controller's transactions and tracks the pending ones.
+    pub fn nonce_manager(&self) -> &NonceManager {
+        &self.nonce
+    }
+}
+
+/// Fee of a transaction sent by the Controller, which decides its version.
+#[derive(Clone, Copy)]
+enum TransactionFee {
+    V1(Felt),
+    V3(ResourceBounds),
+}
+
+impl TransactionFee {
+    fn token(&self) -> FeeToken {
+        match self {
+            TransactionFee::V1(_) => FeeToken::Eth,
+            TransactionFee::V3(_) => FeeToken::Strk,
+        }
+    }
+
+    /// Maximum fee the transaction can be charged, in the unit of its fee token.
+    fn max_fee(&self) -> u128 {
+        match self {
+            TransactionFee::V1(max_fee) => felt_to_u128(*max_fee),
+            TransactionFee::V3(resource_bounds) => resource_bounds.max_fee(),
+        }
+    }
+
+    /// The same fee with `spent` taken out of its maximum.
+    fn without(self, spent: u128) -> Self {
+        let remaining = self.max_fee().saturating_sub(spent);
+        match self {
+            TransactionFee::V1(_) => TransactionFee::V1(Felt::from(remaining)),
+            TransactionFee::V3(mut resource_bounds) => {
+                let price = resource_bounds.l1_gas.max_price_per_unit.max(1);
+                resource_bounds.l1_gas.max_amount =
+                    u64::try_from(remaining / price).unwrap_or(u64::MAX);
+                TransactionFee::V3(resource_bounds)
             }
-        } else {
-            Ok(current_nonce)
         }
     }
 }
@@ -382,7 +519,7 @@ impl_account!(Controller, |account: &Controller, context| {
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
 impl ConnectedAccount for Controller {
-    type Provider = CartridgeJsonRpcProvider;
+    type Provider = SharedProvider;
 
     fn provider(&self) -> &Self::Provider {
         &self.provider
@@ -407,6 +544,9 @@ impl AccountHashAndCallsSigner for Controller {
     ) -> Result<Vec<Felt>, SignError> {
         match self.session_account(calls) {
             Some(session_account) => session_account.sign_hash_and_calls(hash, calls).await,
+            // registering a session is a transaction of the owner account, which signing
+            // never sends, see `Controller::authorize_owner_session`
+            None if self.is_owned_by_connected_account() => Err(SignError::SessionNotAuthorized),
             _ => {
                 let signature = self.owner.sign(&hash).await?;
                 Ok(Vec::<SignerSignature>::cairo_serialize(&vec![signature]))
@@ -0,0 +1,187 @@
+use std::any::Any;
+use std::time::Duration;
+
+use starknet::core::types::Felt;
+use starknet::core::utils::cairo_short_string_to_felt;
+use url::Url;
+
+use crate::abigen;
+use crate::errors::ControllerError;
+use crate::factory::ControllerFactory;
+use crate::provider::shared::SharedProvider;
+use crate::provider::{CartridgeJsonRpcProvider, CartridgeProvider};
+use crate::signers::Owner;
+use crate::storage::{ControllerMetadata, SharedStorage, StorageBackend};
+
+use super::{Controller, NonceManager};
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+#[path = "builder_test.rs"]
+mod builder_test;
+
+/// Builds a [`Controller`] with its dependencies injected.
+///
+/// By default the Controller gets a [`CartridgeJsonRpcProvider`] for its `rpc_url`, the
+/// platform's default storage, and is stored as the active account of its app, which is what
+/// [`Controller::new`] does. Services running many Controllers can instead share a single
+/// provider and storage between them, and skip storing Controllers they only hold in memory.
+pub struct ControllerBuilder {
+    app_id: String,
+    username: String,
+    class_hash: Felt,
+    rpc_url: Url,
+    owner: Owner,
+    address: Felt,
+    chain_id: Felt,
+    provider: Option<SharedProvider>,
+    storage: Option<SharedStorage>,
+    nonce: Option<Felt>,
+    nonce_timeout: Option<Duration>,
+    guardian: Felt,
+    deploy_on_first_use: bool,
+    persist: bool,
+}
+
+impl ControllerBuilder {
+    pub fn new(
+        app_id: String,
+        username: String,
+        class_hash: Felt,
+        rpc_url: Url,
+        owner: Owner,
+        address: Felt,
+        chain_id: Felt,
+    ) -> Self {
+        Self {
+            app_id,
+            username,
+            class_hash,
+            rpc_url,
+            owner,
+            address,
+            chain_id,
+            provider: None,
+            storage: None,
+            nonce: None,
+            nonce_timeout: None,
+            guardian: Felt::ZERO,
+            deploy_on_first_use: false,
+            persist: true,
+        }
+    }
+
+    /// Sends requests through `provider` rather than a new [`CartridgeJsonRpcProvider`]. The
+    /// `rpc_url` is still stored with the Controller's metadata.
+    pub fn with_provider<P>(mut self, provider: P) -> Self
+    where
+        P: CartridgeProvider + Send + Sync + 'static,
+    {
+        self.provider = Some(SharedProvider::new(provider));
+        self
+    }
+
+    /// Stores the Controller and its sessions in `storage`. Passing a [`SharedStorage`] shares
+    /// it with the Controllers it was already given to.
+    pub fn with_storage<S>(mut self, storage: S) -> Self
+    where
+        S: StorageBackend + 'static,
+    {
+        let storage = match (&storage as &dyn Any).downcast_ref::<SharedStorage>() {
+            Some(shared) => shared.clone(),
+            None => SharedStorage::new(storage),
+        };
+        self.storage = Some(storage);
+        self
+    }
+
+    /// Starts from a known account nonce instead of fetching it before the first transaction.
+    pub fn with_nonce(mut self, nonce: Felt) -> Self {
+        self.nonce = Some(nonce);
+        self
+    }
+
+    /// How long a transaction may stay pending before the nonce is fetched from the chain
+    /// again. See [`NonceManager::with_timeout`].
+    pub fn with_nonce_timeout(mut self, timeout: Duration) -> Self {
+        self.nonce_timeout = Some(timeout);
+        self
+    }
+
+    /// Guid of the guardian key set on the sessions created with
+    /// [`Controller::create_session`].
+    pub fn with_guardian(mut self, guardian: Felt) -> Self {
+        self.guardian = guardian;
+        self
+    }
+
+    /// Deploys the Controller on its first execution if it isn't yet, `false` by default. See
+    /// [`Controller::set_deploy_on_first_use`].
+    pub fn with_deploy_on_first_use(mut self, enabled: bool) -> Self {
+        self.deploy_on_first_use = enabled;
+        self
+    }
+
+    /// Whether to store the Controller as the active account of its app, `true` by default.
+    pub fn with_persistence(mut self, persist: bool) -> Self {
+        self.persist = persist;
+        self
+    }
+
+    pub fn build(self) -> Result<Controller, ControllerError> {
+        let provider = self.provider.unwrap_or_else(|| {
+            SharedProvider::new(CartridgeJsonRpcProvider::new(self.rpc_url.clone()))
+        });
+        // the username is the salt of the address, so it must fit a short string
+        let salt = cairo_short_string_to_felt(&self.username)
+            .map_err(|_| ControllerError::InvalidUsername(self.username.clone()))?;
+
+        let factory = ControllerFactory::new(
+            self.class_hash,
+            self.chain_id,
+            self.owner.clone(),
+            provider.clone(),
+        );
+
+        let mut nonce = NonceManager::new(self.nonce);
+        if let Some(timeout) = self.nonce_timeout {
+            nonce = nonce.with_timeout(timeout);
+        }
+
+        let mut controller = Controller {
+            app_id: self.app_id,
+            address: self.address,
+            chain_id: self.chain_id,
+            class_hash: self.class_hash,
+            rpc_url: self.rpc_url,
+            username: self.username,
+            salt,
+            provider,
+            owner: self.owner,
+            guardian: self.guardian,
+            deploy_on_first_use: self.deploy_on_first_use,
+            contract: None,
+            factory,
+            storage: self.storage.unwrap_or_default(),
+            nonce,
+            execute_from_outside_nonce: (
+                starknet::signers::SigningKey::from_random().secret_scalar(),
+                0,
+            ),
+        };
+
+        let contract = Box::new(abigen::controller::Controller::new(
+            controller.address,
+            controller.clone(),
+        ));
+        controller.contract = Some(contract);
+
+        if self.persist {
+            let metadata = ControllerMetadata::from(&controller);
+            controller
+                .storage
+                .set_controller(&controller.app_id, controller.address, metadata)?;
+        }
+
+        Ok(controller)
+    }
+}
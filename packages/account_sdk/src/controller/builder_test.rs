@@ -0,0 +1,130 @@
+use serde_json::json;
+use starknet::core::types::Felt;
+use starknet::macros::felt;
+use url::Url;
+
+use crate::account::session::policy::Policy;
+use crate::artifacts::{Version, CONTROLLERS};
+use crate::errors::ControllerError;
+use crate::provider::CartridgeJsonRpcProvider;
+use crate::signers::{Owner, Signer};
+use crate::storage::inmemory::InMemoryBackend;
+use crate::storage::{SharedStorage, StorageBackend};
+use crate::tests::runners::stub::{result_response, StubServer};
+
+use super::{Controller, ControllerBuilder};
+
+const APP_ID: &str = "app_id";
+
+fn builder(rpc_url: Url) -> ControllerBuilder {
+    ControllerBuilder::new(
+        APP_ID.to_string(),
+        "username".to_string(),
+        CONTROLLERS[&Version::LATEST].hash,
+        rpc_url,
+        Owner::Signer(Signer::new_starknet_random()),
+        felt!("0xdead"),
+        felt!("0x534e5f5345504f4c4941"),
+    )
+}
+
+fn unreachable_url() -> Url {
+    Url::parse("http://localhost:1/").unwrap()
+}
+
+#[tokio::test]
+async fn test_storage_and_persistence() {
+    let storage = SharedStorage::new(InMemoryBackend::new());
+
+    builder(unreachable_url())
+        .with_storage(storage.clone())
+        .with_persistence(false)
+        .build()
+        .unwrap();
+    assert!(storage.keys().unwrap().is_empty());
+
+    let controller = builder(unreachable_url())
+        .with_storage(storage.clone())
+        .build()
+        .unwrap();
+    let metadata = storage.controller(APP_ID).unwrap().unwrap();
+    assert_eq!(metadata.address, controller.address);
+
+    // Clones of the Controller write to the same storage
+    let mut clone = controller.clone();
+    clone.storage.clear().unwrap();
+    assert!(storage.controller(APP_ID).unwrap().is_none());
+}
+
+#[test]
+fn test_invalid_username() {
+    let username = "a".repeat(32);
+    let result = ControllerBuilder::new(
+        APP_ID.to_string(),
+        username.clone(),
+        CONTROLLERS[&Version::LATEST].hash,
+        unreachable_url(),
+        Owner::Signer(Signer::new_starknet_random()),
+        felt!("0xdead"),
+        felt!("0x534e5f5345504f4c4941"),
+    )
+    .with_persistence(false)
+    .build();
+
+    assert!(matches!(result, Err(ControllerError::InvalidUsername(u)) if u == username));
+
+    let result = Controller::new(
+        APP_ID.to_string(),
+        username.clone(),
+        CONTROLLERS[&Version::LATEST].hash,
+        unreachable_url(),
+        Owner::Signer(Signer::new_starknet_random()),
+        felt!("0xdead"),
+        felt!("0x534e5f5345504f4c4941"),
+    );
+    assert!(matches!(result, Err(ControllerError::InvalidUsername(u)) if u == username));
+}
+
+#[tokio::test]
+async fn test_provider_and_nonce() {
+    let server = StubServer::start(|_, request| match request["method"].as_str() {
+        Some("starknet_getNonce") => result_response(request, json!("0x7")),
+        method => panic!("Unexpected method: {:?}", method),
+    });
+
+    let controller = builder(unreachable_url())
+        .with_provider(CartridgeJsonRpcProvider::new(server.url.clone()))
+        .with_persistence(false)
+        .build()
+        .unwrap();
+    assert_eq!(controller.get_nonce().await.unwrap(), felt!("0x7"));
+    assert_eq!(server.requests(), 1);
+
+    let controller = builder(unreachable_url())
+        .with_provider(CartridgeJsonRpcProvider::new(server.url.clone()))
+        .with_nonce(felt!("0x3"))
+        .with_persistence(false)
+        .build()
+        .unwrap();
+    assert_eq!(controller.get_nonce().await.unwrap(), felt!("0x3"));
+    assert_eq!(server.requests(), 1);
+}
+
+#[tokio::test]
+async fn test_guardian() {
+    let guardian = felt!("0x42");
+    let mut controller = builder(unreachable_url())
+        .with_storage(InMemoryBackend::new())
+        .with_guardian(guardian)
+        .build()
+        .unwrap();
+
+    let policies = vec![Policy::new_call(Felt::ONE, felt!("0x2"))];
+    controller
+        .create_session(policies.clone(), u64::MAX)
+        .await
+        .unwrap();
+
+    let (_, metadata) = controller.session_metadata(&policies, None).unwrap();
+    assert_eq!(metadata.session.inner.guardian_key_guid, guardian);
+}
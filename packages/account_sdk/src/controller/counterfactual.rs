@@ -0,0 +1,256 @@
+use std::time::Duration;
+
+use starknet::accounts::{AccountFactory, ExecutionEncoder};
+use starknet::core::types::{
+    BlockId, BlockTag, BroadcastedDeployAccountTransaction, BroadcastedDeployAccountTransactionV1,
+    BroadcastedDeployAccountTransactionV3, BroadcastedInvokeTransaction,
+    BroadcastedInvokeTransactionV1, BroadcastedInvokeTransactionV3, BroadcastedTransaction, Call,
+    DataAvailabilityMode, FeeEstimate, Felt, ResourceBounds as StarknetResourceBounds,
+    ResourceBoundsMapping, SimulationFlagForEstimateFee,
+};
+use starknet::providers::{Provider, ProviderError};
+
+use crate::{
+    constants::WEBAUTHN_GAS,
+    errors::ControllerError,
+    fees::{felt_to_u128, FeeToken, ResourceBounds},
+    transaction_waiter::TransactionWaiter,
+};
+
+use super::{Controller, TransactionFee};
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+#[path = "counterfactual_test.rs"]
+mod counterfactual_test;
+
+impl Controller {
+    const DEPLOYMENT_TIMEOUT: Duration = Duration::from_secs(60);
+
+    /// Whether the Controller deploys itself when asked to execute before it is deployed,
+    /// instead of returning a `NotDeployed` error.
+    pub fn deploys_on_first_use(&self) -> bool {
+        self.deploy_on_first_use
+    }
+
+    /// Makes the Controller deploy itself, paying with its own balance, when asked to execute
+    /// before it is deployed. Fee estimates then cover both the deployment and the execution.
+    ///
+    /// Executions sent through the paymaster aren't affected, it only executes calls on
+    /// accounts that are already deployed.
+    pub fn set_deploy_on_first_use(&mut self, enabled: bool) {
+        self.deploy_on_first_use = enabled;
+    }
+
+    /// Sends the deploy account transaction, and waits for it to be accepted so that the
+    /// execution of `calls` that follows can be validated. A connected owner account deploys
+    /// the Controller through the Universal Deployer instead.
+    ///
+    /// The deployment and the execution are paid out of `fee` together: the deployment is
+    /// bounded by what the execution leaves of it, and the fee left for the execution is
+    /// returned. When both don't fit in `fee`, nothing is sent and a `NotDeployed` error
+    /// carries their estimate.
+    pub(crate) async fn deploy_for_first_use(
+        &self,
+        calls: &[Call],
+        fee: TransactionFee,
+    ) -> Result<TransactionFee, ControllerError> {
+        if self.is_owned_by_connected_account() {
+            // The owner account pays for the deployment and the nonce of the Controller is
+            // left untouched
+            self.deploy_through_owner().await?;
+            return Ok(fee);
+        }
+
+        let fee_token = fee.token();
+        let (deployment, execution) = self
+            .estimate_deployment_and_execution(calls, fee_token)
+            .await?;
+        let fee_estimate = combined_estimate(&deployment, &execution);
+
+        let balance = self.fee_token_balance(fee_token).await?;
+        if fee_estimate.overall_fee > Felt::from(balance) {
+            return Err(ControllerError::InsufficientBalance {
+                fee_estimate: Box::new(fee_estimate),
+                balance,
+            });
+        }
+        let budget = fee.max_fee();
+        if felt_to_u128(fee_estimate.overall_fee) > budget {
+            return Err(ControllerError::NotDeployed {
+                fee_estimate: Box::new(fee_estimate),
+                balance,
+            });
+        }
+
+        let deployment_budget = budget - felt_to_u128(execution.overall_fee);
+        let (result, spent) = match fee_token {
+            FeeToken::Eth => {
+                let max_fee = (felt_to_u128(deployment.overall_fee).saturating_mul(3) / 2)
+                    .min(deployment_budget);
+                let result = self.deploy().max_fee(Felt::from(max_fee)).send().await;
+                (result, max_fee)
+            }
+            FeeToken::Strk => {
+                let mut resource_bounds = ResourceBounds::from_fee_estimate(&deployment);
+                let price = resource_bounds.l1_gas.max_price_per_unit.max(1);
+                resource_bounds.l1_gas.max_amount = resource_bounds
+                    .l1_gas
+                    .max_amount
+                    .min(u64::try_from(deployment_budget / price).unwrap_or(u64::MAX));
+                let result = self
+                    .deploy_v3()
+                    .gas(resource_bounds.l1_gas.max_amount)
+                    .gas_price(resource_bounds.l1_gas.max_price_per_unit)
+                    .send()
+                    .await;
+                (result, resource_bounds.max_fee())
+            }
+        };
+        let result = result?;
+
+        TransactionWaiter::new(result.transaction_hash, &self.provider)
+            .with_timeout(Self::DEPLOYMENT_TIMEOUT)
+            .wait()
+            .await?;
+
+        // The deployment used the first nonce of the account
+        self.nonce.resync(&self.provider, self.address).await?;
+        Ok(fee.without(spent))
+    }
+
+    /// Estimates the fee of deploying the Controller and then executing `calls`, both paid in
+    /// `fee_token`. The returned estimate is the sum of the two.
+    ///
+    /// Validation is skipped, as the execution can't be signed before the deployment exists.
+    /// The gas of validating a WebAuthn signature is added for each transaction instead.
+    pub(crate) async fn estimate_deploy_and_invoke_fee(
+        &self,
+        calls: &[Call],
+        fee_token: FeeToken,
+    ) -> Result<FeeEstimate, ControllerError> {
+        let (deployment, execution) = self
+            .estimate_deployment_and_execution(calls, fee_token)
+            .await?;
+        let fee_estimate = combined_estimate(&deployment, &execution);
+
+        let balance = self.fee_token_balance(fee_token).await?;
+        if fee_estimate.overall_fee > Felt::from(balance) {
+            return Err(ControllerError::InsufficientBalance {
+                fee_estimate: Box::new(fee_estimate),
+                balance,
+            });
+        }
+
+        Ok(fee_estimate)
+    }
+
+    /// Estimates the fees of deploying the Controller and of then executing `calls`, each
+    /// including the gas of validating a WebAuthn signature.
+    async fn estimate_deployment_and_execution(
+        &self,
+        calls: &[Call],
+        fee_token: FeeToken,
+    ) -> Result<(FeeEstimate, FeeEstimate), ControllerError> {
+        let salt = self.salt;
+        let class_hash = self.factory.class_hash();
+        let constructor_calldata = self.factory.calldata();
+        let calldata = self.encode_calls(calls);
+
+        let transactions = match fee_token {
+            FeeToken::Eth => [
+                BroadcastedTransaction::DeployAccount(BroadcastedDeployAccountTransaction::V1(
+                    BroadcastedDeployAccountTransactionV1 {
+                        max_fee: Felt::ZERO,
+                        signature: vec![],
+                        nonce: Felt::ZERO,
+                        contract_address_salt: salt,
+                        constructor_calldata,
+                        class_hash,
+                        is_query: true,
+                    },
+                )),
+                BroadcastedTransaction::Invoke(BroadcastedInvokeTransaction::V1(
+                    BroadcastedInvokeTransactionV1 {
+                        sender_address: self.address,
+                        calldata,
+                        max_fee: Felt::ZERO,
+                        signature: vec![],
+                        nonce: Felt::ONE,
+                        is_query: true,
+                    },
+                )),
+            ],
+            FeeToken::Strk => [
+                BroadcastedTransaction::DeployAccount(BroadcastedDeployAccountTransaction::V3(
+                    BroadcastedDeployAccountTransactionV3 {
+                        signature: vec![],
+                        nonce: Felt::ZERO,
+                        contract_address_salt: salt,
+                        constructor_calldata,
+                        class_hash,
+                        resource_bounds: empty_resource_bounds(),
+                        tip: 0,
+                        paymaster_data: vec![],
+                        nonce_data_availability_mode: DataAvailabilityMode::L1,
+                        fee_data_availability_mode: DataAvailabilityMode::L1,
+                        is_query: true,
+                    },
+                )),
+                BroadcastedTransaction::Invoke(BroadcastedInvokeTransaction::V3(
+                    BroadcastedInvokeTransactionV3 {
+                        sender_address: self.address,
+                        calldata,
+                        signature: vec![],
+                        nonce: Felt::ONE,
+                        resource_bounds: empty_resource_bounds(),
+                        tip: 0,
+                        paymaster_data: vec![],
+                        account_deployment_data: vec![],
+                        nonce_data_availability_mode: DataAvailabilityMode::L1,
+                        fee_data_availability_mode: DataAvailabilityMode::L1,
+                        is_query: true,
+                    },
+                )),
+            ],
+        };
+
+        let estimates = self
+            .provider
+            .estimate_fee(
+                transactions,
+                [SimulationFlagForEstimateFee::SkipValidate],
+                BlockId::Tag(BlockTag::Pending),
+            )
+            .await?;
+        let [deployment, execution] = estimates.as_slice() else {
+            return Err(ProviderError::ArrayLengthMismatch.into());
+        };
+
+        let with_validation = |estimate: &FeeEstimate| {
+            let mut estimate = estimate.clone();
+            estimate.overall_fee += WEBAUTHN_GAS * estimate.gas_price;
+            estimate
+        };
+        Ok((with_validation(deployment), with_validation(execution)))
+    }
+}
+
+/// Estimate of sending the deployment and then the execution, priced as the execution.
+fn combined_estimate(deployment: &FeeEstimate, execution: &FeeEstimate) -> FeeEstimate {
+    let mut fee_estimate = execution.clone();
+    fee_estimate.gas_consumed += deployment.gas_consumed;
+    fee_estimate.data_gas_consumed += deployment.data_gas_consumed;
+    fee_estimate.overall_fee += deployment.overall_fee;
+    fee_estimate
+}
+
+fn empty_resource_bounds() -> ResourceBoundsMapping {
+    let empty = StarknetResourceBounds {
+        max_amount: 0,
+        max_price_per_unit: 0,
+    };
+    ResourceBoundsMapping {
+        l1_gas: empty.clone(),
+        l2_gas: empty,
+    }
+}
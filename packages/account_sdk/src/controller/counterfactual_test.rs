@@ -0,0 +1,93 @@
+use cainome::cairo_serde::{ContractAddress, U256};
+use starknet::core::types::{BlockId, BlockTag, Felt};
+use starknet::core::utils::cairo_short_string_to_felt;
+use starknet::macros::felt;
+use starknet::providers::Provider;
+
+use crate::abigen::erc_20::Erc20;
+use crate::artifacts::{Version, CONTROLLERS};
+use crate::errors::ControllerError;
+use crate::factory::ControllerFactory;
+use crate::signers::{Owner, Signer};
+use crate::tests::account::FEE_TOKEN_ADDRESS;
+use crate::tests::runners::katana::KatanaRunner;
+
+use super::ControllerBuilder;
+
+#[tokio::test]
+async fn test_deploy_on_first_use() {
+    let runner = KatanaRunner::load();
+    let owner = Owner::Signer(Signer::new_starknet_random());
+    let chain_id = runner.client().chain_id().await.unwrap();
+    let class_hash = CONTROLLERS[&Version::LATEST].hash;
+
+    let username = "firstuse".to_string();
+    let salt = cairo_short_string_to_felt(&username).unwrap();
+    let address =
+        ControllerFactory::new(class_hash, chain_id, owner.clone(), runner.client().clone())
+            .address(salt);
+
+    let mut controller = ControllerBuilder::new(
+        "app_id".to_string(),
+        username,
+        class_hash,
+        runner.rpc_url.clone(),
+        owner,
+        address,
+        chain_id,
+    )
+    .with_provider(runner.client().clone())
+    .with_deploy_on_first_use(true)
+    .with_persistence(false)
+    .build()
+    .unwrap();
+
+    let recipient = ContractAddress(felt!("0x18301129"));
+    let erc20 = Erc20::new(*FEE_TOKEN_ADDRESS, &controller);
+    let call = erc20.transfer_getcall(&recipient, &U256 { low: 1, high: 0 });
+
+    // Without funds, the estimate covering the deployment can't be paid
+    let result = controller.estimate_invoke_fee(vec![call.clone()]).await;
+    assert!(
+        matches!(result, Err(ControllerError::InsufficientBalance { .. })),
+        "Expected InsufficientBalance error, got: {:?}",
+        result
+    );
+
+    runner.fund(&address).await;
+    let fee_estimate = controller
+        .estimate_invoke_fee(vec![call.clone()])
+        .await
+        .unwrap();
+
+    // A max fee short of covering the deployment as well leaves the Controller undeployed
+    let result = controller
+        .execute(vec![call.clone()], fee_estimate.overall_fee - Felt::ONE)
+        .await;
+    assert!(
+        matches!(result, Err(ControllerError::NotDeployed { .. })),
+        "Expected NotDeployed error, got: {:?}",
+        result
+    );
+    assert!(runner
+        .client()
+        .get_class_hash_at(BlockId::Tag(BlockTag::Pending), address)
+        .await
+        .is_err());
+
+    controller
+        .execute_and_wait(vec![call], fee_estimate.overall_fee)
+        .await
+        .unwrap();
+
+    let deployed_class_hash = runner
+        .client()
+        .get_class_hash_at(BlockId::Tag(BlockTag::Pending), address)
+        .await
+        .unwrap();
+    assert_eq!(deployed_class_hash, class_hash);
+    assert_eq!(
+        erc20.balanceOf(&recipient).call().await.unwrap(),
+        U256 { low: 1, high: 0 }
+    );
+}
@@ -0,0 +1,201 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+
+use starknet::core::types::{BlockId, BlockTag, Felt, StarknetError};
+use starknet::providers::{Provider, ProviderError};
+
+use crate::utils::time::get_current_timestamp;
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+#[path = "nonce_test.rs"]
+mod nonce_test;
+
+/// A transaction submitted with a nonce handed out by a [`NonceManager`], and not yet known to
+/// be included.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PendingTransaction {
+    pub nonce: Felt,
+    pub transaction_hash: Felt,
+    /// Unix timestamp, in seconds, of the submission.
+    pub submitted_at: u64,
+}
+
+/// Hands out the nonces of an account's transactions, so several can be submitted back to back
+/// without waiting for the previous ones to be included.
+///
+/// Clones share their state, nonces are therefore sequential across every clone of a
+/// [`Controller`](super::Controller). The next nonce is fetched from the chain when first needed,
+/// and again after a transaction is rejected or a pending one outlives the timeout.
+#[derive(Clone, Debug)]
+pub struct NonceManager {
+    state: Arc<Mutex<NonceState>>,
+    timeout: Duration,
+}
+
+#[derive(Debug, Default)]
+struct NonceState {
+    /// `None` when it has to be fetched from the chain.
+    next: Option<Felt>,
+    pending: BTreeMap<Felt, PendingTransaction>,
+}
+
+impl Default for NonceManager {
+    fn default() -> Self {
+        Self::new(None)
+    }
+}
+
+impl NonceManager {
+    const DEFAULT_TIMEOUT: Duration = Duration::from_secs(300);
+
+    /// A manager starting at `next`, or at the account's nonce on chain when `None`.
+    pub fn new(next: Option<Felt>) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(NonceState {
+                next,
+                pending: BTreeMap::new(),
+            })),
+            timeout: Self::DEFAULT_TIMEOUT,
+        }
+    }
+
+    /// Sets how long a transaction may stay pending before the nonce is checked against the
+    /// chain again.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// The nonce the next transaction will get, without reserving it.
+    pub async fn next<P>(&self, provider: &P, address: Felt) -> Result<Felt, ProviderError>
+    where
+        P: Provider + Sync,
+    {
+        self.refresh(provider, address).await?;
+        if let Some(next) = self.lock().next {
+            return Ok(next);
+        }
+
+        let nonce = fetch_nonce(provider, address).await?;
+        Ok(*self.lock().next.get_or_insert(nonce))
+    }
+
+    /// Reserves the next nonce. Every call gets a different one, even when made concurrently.
+    pub async fn reserve<P>(&self, provider: &P, address: Felt) -> Result<Felt, ProviderError>
+    where
+        P: Provider + Sync,
+    {
+        self.refresh(provider, address).await?;
+        if let Some(nonce) = self.take_next() {
+            return Ok(nonce);
+        }
+
+        let fetched = fetch_nonce(provider, address).await?;
+        // Another reservation may have synced while fetching, its nonce wins
+        let mut state = self.lock();
+        let nonce = *state.next.get_or_insert(fetched);
+        state.next = Some(nonce + Felt::ONE);
+        Ok(nonce)
+    }
+
+    /// Records that the transaction using the reserved `nonce` was accepted by the node.
+    pub fn submitted(&self, nonce: Felt, transaction_hash: Felt) {
+        self.lock().pending.insert(
+            nonce,
+            PendingTransaction {
+                nonce,
+                transaction_hash,
+                submitted_at: get_current_timestamp(),
+            },
+        );
+    }
+
+    /// Records that the transaction using the reserved `nonce` was rejected. Transactions
+    /// submitted after it can't be included until the gap is filled, so they are dropped and the
+    /// next nonce is fetched from the chain again.
+    pub fn rejected(&self, nonce: Felt) {
+        let mut state = self.lock();
+        state.pending.retain(|pending, _| *pending < nonce);
+        state.next = None;
+    }
+
+    /// Discards the local state and starts again from the account's nonce on chain.
+    pub async fn resync<P>(&self, provider: &P, address: Felt) -> Result<Felt, ProviderError>
+    where
+        P: Provider + Sync,
+    {
+        let nonce = fetch_nonce(provider, address).await?;
+        let mut state = self.lock();
+        state.pending.clear();
+        state.next = Some(nonce);
+        Ok(nonce)
+    }
+
+    /// Transactions submitted through this manager that are not yet known to be included, by
+    /// increasing nonce.
+    pub fn pending(&self) -> Vec<PendingTransaction> {
+        self.lock().pending.values().cloned().collect()
+    }
+
+    /// Checks the oldest pending transaction once it outlives the timeout. Transactions below
+    /// the nonce on chain were included and are dropped. If the oldest one wasn't, it is
+    /// considered lost and the manager resyncs.
+    async fn refresh<P>(&self, provider: &P, address: Felt) -> Result<(), ProviderError>
+    where
+        P: Provider + Sync,
+    {
+        let oldest = {
+            let state = self.lock();
+            let now = get_current_timestamp();
+            match state.pending.values().next() {
+                Some(oldest)
+                    if now.saturating_sub(oldest.submitted_at) >= self.timeout.as_secs() =>
+                {
+                    oldest.nonce
+                }
+                _ => return Ok(()),
+            }
+        };
+
+        let on_chain = fetch_nonce(provider, address).await?;
+        let mut state = self.lock();
+        if on_chain > oldest {
+            state.pending.retain(|nonce, _| *nonce >= on_chain);
+            if state.next.map_or(true, |next| next < on_chain) {
+                state.next = Some(on_chain);
+            }
+        } else {
+            state.pending.clear();
+            state.next = Some(on_chain);
+        }
+        Ok(())
+    }
+
+    fn take_next(&self) -> Option<Felt> {
+        let mut state = self.lock();
+        let nonce = state.next?;
+        state.next = Some(nonce + Felt::ONE);
+        Some(nonce)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, NonceState> {
+        // The state is only ever updated in place, it stays consistent across a panic
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// The account's nonce in the pending block, zero while it isn't deployed.
+async fn fetch_nonce<P>(provider: &P, address: Felt) -> Result<Felt, ProviderError>
+where
+    P: Provider + Sync,
+{
+    match provider
+        .get_nonce(BlockId::Tag(BlockTag::Pending), address)
+        .await
+    {
+        Ok(nonce) => Ok(nonce),
+        Err(ProviderError::StarknetError(StarknetError::ContractNotFound)) => Ok(Felt::ZERO),
+        Err(e) => Err(e),
+    }
+}
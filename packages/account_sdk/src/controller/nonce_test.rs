@@ -0,0 +1,169 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use cainome::cairo_serde::{ContractAddress, U256};
+use futures::future::join_all;
+use serde_json::json;
+use starknet::core::types::Felt;
+use starknet::macros::felt;
+
+use crate::abigen::erc_20::Erc20;
+use crate::artifacts::Version;
+use crate::provider::CartridgeJsonRpcProvider;
+use crate::signers::{Owner, Signer};
+use crate::tests::account::FEE_TOKEN_ADDRESS;
+use crate::tests::runners::katana::KatanaRunner;
+use crate::tests::runners::stub::{result_response, StubServer};
+use crate::transaction_waiter::TransactionWaiter;
+
+use super::NonceManager;
+
+const ADDRESS: Felt = felt!("0xdead");
+
+/// A node whose account nonce is the value of the returned counter.
+fn node() -> (StubServer, CartridgeJsonRpcProvider, Arc<AtomicU64>) {
+    let nonce = Arc::new(AtomicU64::new(5));
+    let on_chain = nonce.clone();
+    let server = StubServer::start(move |_, request| match request["method"].as_str() {
+        Some("starknet_getNonce") => result_response(
+            request,
+            json!(format!("{:#x}", on_chain.load(Ordering::SeqCst))),
+        ),
+        method => panic!("Unexpected method: {:?}", method),
+    });
+    let provider = CartridgeJsonRpcProvider::new(server.url.clone());
+    (server, provider, nonce)
+}
+
+#[tokio::test]
+async fn test_concurrent_reservations() {
+    let (server, provider, _) = node();
+    let manager = NonceManager::default();
+
+    let mut nonces = join_all((0..3).map(|_| manager.reserve(&provider, ADDRESS)))
+        .await
+        .into_iter()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    nonces.sort();
+    assert_eq!(nonces, vec![felt!("0x5"), felt!("0x6"), felt!("0x7")]);
+
+    // Clones share their nonces, and only the first reservations hit the node
+    let requests = server.requests();
+    let clone = manager.clone();
+    assert_eq!(clone.next(&provider, ADDRESS).await.unwrap(), felt!("0x8"));
+    assert_eq!(
+        manager.reserve(&provider, ADDRESS).await.unwrap(),
+        felt!("0x8")
+    );
+    assert_eq!(server.requests(), requests);
+}
+
+#[tokio::test]
+async fn test_rejection_resyncs() {
+    let (_server, provider, on_chain) = node();
+    let manager = NonceManager::new(Some(felt!("0x5")));
+
+    for nonce in 5..8u64 {
+        let reserved = manager.reserve(&provider, ADDRESS).await.unwrap();
+        assert_eq!(reserved, Felt::from(nonce));
+        manager.submitted(reserved, Felt::from(nonce * 100));
+    }
+    assert_eq!(manager.pending().len(), 3);
+
+    // Transactions after the rejected one can't be included anymore
+    manager.rejected(felt!("0x6"));
+    assert_eq!(
+        manager
+            .pending()
+            .iter()
+            .map(|pending| pending.nonce)
+            .collect::<Vec<_>>(),
+        vec![felt!("0x5")]
+    );
+
+    on_chain.store(6, Ordering::SeqCst);
+    assert_eq!(
+        manager.reserve(&provider, ADDRESS).await.unwrap(),
+        felt!("0x6")
+    );
+}
+
+#[tokio::test]
+async fn test_timeout_resyncs() {
+    let (_server, provider, on_chain) = node();
+    let manager = NonceManager::new(Some(felt!("0x5"))).with_timeout(Duration::ZERO);
+
+    let mut nonces = Vec::new();
+    for _ in 0..3 {
+        nonces.push(manager.reserve(&provider, ADDRESS).await.unwrap());
+    }
+    for nonce in nonces {
+        manager.submitted(nonce, nonce);
+    }
+
+    // The first two were included, the last one is still pending
+    on_chain.store(7, Ordering::SeqCst);
+    assert_eq!(
+        manager.next(&provider, ADDRESS).await.unwrap(),
+        felt!("0x8")
+    );
+    assert_eq!(manager.pending().len(), 1);
+
+    // It outlived the timeout without being included, it is considered lost
+    assert_eq!(
+        manager.next(&provider, ADDRESS).await.unwrap(),
+        felt!("0x7")
+    );
+    assert!(manager.pending().is_empty());
+}
+
+#[tokio::test]
+async fn test_pipelined_execution() {
+    let runner = KatanaRunner::load();
+    let controller = runner
+        .deploy_controller(
+            "testuser".to_owned(),
+            Owner::Signer(Signer::new_starknet_random()),
+            Version::LATEST,
+        )
+        .await;
+
+    let erc20 = Erc20::new(*FEE_TOKEN_ADDRESS, &controller);
+    let call = erc20.transfer_getcall(
+        &ContractAddress(felt!("0x18301129")),
+        &U256 { low: 1, high: 0 },
+    );
+    let max_fee = controller
+        .estimate_invoke_fee(vec![call.clone()])
+        .await
+        .unwrap()
+        .overall_fee
+        * Felt::TWO;
+
+    // Submitted back to back, without waiting for the previous one to be included
+    let mut transaction_hashes = Vec::new();
+    for _ in 0..3 {
+        let result = controller
+            .execute_with_max_fee(vec![call.clone()], max_fee)
+            .await
+            .unwrap();
+        transaction_hashes.push(result.transaction_hash);
+    }
+    for transaction_hash in transaction_hashes {
+        TransactionWaiter::new(transaction_hash, runner.client())
+            .with_timeout(Duration::from_secs(5))
+            .wait()
+            .await
+            .unwrap();
+    }
+
+    assert_eq!(controller.nonce_manager().pending().len(), 3);
+    let balance = erc20
+        .balanceOf(&ContractAddress(felt!("0x18301129")))
+        .call()
+        .await
+        .unwrap();
+    assert_eq!(balance, U256 { low: 3, high: 0 });
+}
@@ -3,19 +3,25 @@ use std::time::Duration;
 use crate::{
     abigen::erc_20::Erc20,
     artifacts::{Version, CONTROLLERS},
+    constants::STRK_CONTRACT_ADDRESS,
     controller::Controller,
     errors::ControllerError,
     factory::ControllerFactory,
+    fees::ResourceBounds,
     signers::{Owner, Signer},
-    tests::{
-        account::FEE_TOKEN_ADDRESS, runners::katana::KatanaRunner,
-        transaction_waiter::TransactionWaiter,
-    },
+    tests::{account::FEE_TOKEN_ADDRESS, runners::katana::KatanaRunner},
+    transaction_waiter::{TransactionWaiter, TransactionWaitingError, WaitOptions},
 };
 use cainome::cairo_serde::{ContractAddress, U256};
 use starknet::{
-    accounts::AccountFactory, core::utils::cairo_short_string_to_felt, macros::felt,
-    providers::Provider, signers::SigningKey,
+    accounts::AccountFactory,
+    core::{
+        types::{Felt, PriceUnit, TransactionFinalityStatus},
+        utils::cairo_short_string_to_felt,
+    },
+    macros::felt,
+    providers::Provider,
+    signers::SigningKey,
 };
 
 #[tokio::test]
@@ -51,7 +57,8 @@ async fn test_deploy_controller() {
         owner.clone(),
         address,
         chain_id,
-    );
+    )
+    .unwrap();
 
     runner.fund(&address).await;
 
@@ -100,7 +107,8 @@ async fn test_controller_not_deployed() {
         Owner::Signer(signer.clone()),
         felt!("0xdeadbeef"),
         chain_id,
-    );
+    )
+    .unwrap();
 
     let recipient = ContractAddress(felt!("0x18301129"));
     let amount = U256 { low: 0, high: 0 };
@@ -142,7 +150,8 @@ async fn test_controller_nonce_mismatch_recovery() {
         Owner::Signer(signer.clone()),
         controller1.address,
         chain_id,
-    );
+    )
+    .unwrap();
 
     // Send a transaction using controller1
     let recipient = ContractAddress(felt!("0x18301129"));
@@ -285,3 +294,124 @@ async fn test_multiple_transactions() {
     let result2 = ensure_txn(transfer2, runner.client()).await;
     assert!(result2.is_ok(), "Second transaction failed");
 }
+
+#[tokio::test]
+async fn test_execute_and_wait() {
+    let runner = KatanaRunner::load();
+    let mut controller = runner
+        .deploy_controller(
+            "testuser".to_owned(),
+            Owner::Signer(Signer::new_starknet_random()),
+            Version::LATEST,
+        )
+        .await;
+
+    let recipient = ContractAddress(felt!("0x18301129"));
+    let erc20 = Erc20::new(*FEE_TOKEN_ADDRESS, &controller);
+    let transfer = erc20.transfer_getcall(&recipient, &U256 { low: 1, high: 0 });
+    let overdraft = erc20.transfer_getcall(
+        &recipient,
+        &U256 {
+            low: u128::MAX,
+            high: u128::MAX,
+        },
+    );
+
+    let max_fee = controller
+        .estimate_invoke_fee(vec![transfer.clone()])
+        .await
+        .unwrap()
+        .overall_fee
+        * Felt::TWO;
+
+    let receipt = controller
+        .execute_and_wait(vec![transfer.clone()], max_fee)
+        .await
+        .unwrap();
+    assert!(matches!(
+        receipt.receipt.finality_status(),
+        TransactionFinalityStatus::AcceptedOnL2
+    ));
+
+    // Katana never settles on L1, so waiting for it runs out of time
+    let result = controller
+        .execute_and_wait_with_options(
+            vec![transfer],
+            max_fee,
+            WaitOptions {
+                finality: TransactionFinalityStatus::AcceptedOnL1,
+                timeout: Duration::from_secs(3),
+            },
+        )
+        .await;
+    assert!(
+        matches!(
+            result,
+            Err(ControllerError::TransactionWaitingError(
+                TransactionWaitingError::Timeout
+            ))
+        ),
+        "Expected the wait to time out, got: {:?}",
+        result
+    );
+
+    let result = controller.execute_and_wait(vec![overdraft], max_fee).await;
+    assert!(
+        matches!(
+            result,
+            Err(ControllerError::TransactionWaitingError(
+                TransactionWaitingError::TransactionReverted(_)
+            ))
+        ),
+        "Expected the transaction to revert, got: {:?}",
+        result
+    );
+}
+
+#[tokio::test]
+async fn test_execute_v3() {
+    let runner = KatanaRunner::load();
+    let mut controller = runner
+        .deploy_controller(
+            "testuser".to_owned(),
+            Owner::Signer(Signer::new_starknet_random()),
+            Version::LATEST,
+        )
+        .await;
+
+    let erc20 = Erc20::new(STRK_CONTRACT_ADDRESS, &controller);
+    let transfer = erc20.transfer_getcall(
+        &ContractAddress(felt!("0x18301129")),
+        &U256 { low: 1, high: 0 },
+    );
+
+    // The Controller was only funded in ETH
+    let result = controller
+        .estimate_invoke_fee_v3(vec![transfer.clone()])
+        .await;
+    assert!(
+        matches!(result, Err(ControllerError::InsufficientBalance { .. })),
+        "Expected an insufficient STRK balance, got: {:?}",
+        result
+    );
+
+    runner
+        .fund_token(STRK_CONTRACT_ADDRESS, &controller.address)
+        .await;
+
+    let estimate = controller
+        .estimate_invoke_fee_v3(vec![transfer.clone()])
+        .await
+        .unwrap();
+    assert_eq!(estimate.unit, PriceUnit::Fri);
+
+    let result = controller
+        .execute_with_resource_bounds(vec![transfer], ResourceBounds::from_fee_estimate(&estimate))
+        .await
+        .unwrap();
+
+    TransactionWaiter::new(result.transaction_hash, runner.client())
+        .wait()
+        .await
+        .unwrap();
+}
@@ -0,0 +1,100 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use starknet::accounts::{AccountError, ConnectedAccount};
+use starknet::core::types::contract::SierraClass;
+use starknet::core::types::{BlockId, BlockTag, Felt, StarknetError};
+use starknet::providers::{Provider, ProviderError};
+
+use crate::{
+    artifacts::{Version, CONTROLLERS},
+    transaction_waiter::{TransactionWaiter, TransactionWaitingError},
+};
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+#[path = "declaration_test.rs"]
+mod declaration_test;
+
+#[derive(Debug, thiserror::Error)]
+pub enum DeclarationError<S> {
+    #[error(transparent)]
+    Provider(#[from] ProviderError),
+    #[error(transparent)]
+    Account(#[from] AccountError<S>),
+    #[error(transparent)]
+    TransactionWaiting(#[from] TransactionWaitingError),
+}
+
+/// Class of a Controller version on a network, after [`declare_controller_classes`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DeclaredClass {
+    pub version: Version,
+    pub class_hash: Felt,
+    /// `None` when the class was already declared.
+    pub transaction_hash: Option<Felt>,
+}
+
+const DECLARATION_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Declares the class of every version in `versions` with `account`, from the Sierra classes
+/// embedded in [`CONTROLLERS`], so Controllers of these versions can be deployed on a new
+/// network. Classes the network already knows are skipped.
+///
+/// Declarations are sent one after the other, each one waiting for the previous to be accepted.
+pub async fn declare_controller_classes<A>(
+    account: &A,
+    versions: &[Version],
+) -> Result<Vec<DeclaredClass>, DeclarationError<A::SignError>>
+where
+    A: ConnectedAccount + Sync,
+    A::Provider: Send,
+{
+    let mut declared = Vec::with_capacity(versions.len());
+
+    for version in versions {
+        let class = CONTROLLERS[version];
+        let transaction_hash = if is_declared(account.provider(), class.hash).await? {
+            None
+        } else {
+            let sierra: SierraClass =
+                serde_json::from_str(class.content).expect("Embedded classes should be valid");
+            let flattened = sierra
+                .flatten()
+                .expect("Embedded classes should be flattenable");
+
+            let result = account
+                .declare_v2(Arc::new(flattened), class.casm_hash)
+                .fee_estimate_multiplier(1.5)
+                .send()
+                .await?;
+            TransactionWaiter::new(result.transaction_hash, account.provider())
+                .with_timeout(DECLARATION_TIMEOUT)
+                .wait()
+                .await?;
+
+            Some(result.transaction_hash)
+        };
+
+        declared.push(DeclaredClass {
+            version: *version,
+            class_hash: class.hash,
+            transaction_hash,
+        });
+    }
+
+    Ok(declared)
+}
+
+async fn is_declared<P>(provider: &P, class_hash: Felt) -> Result<bool, ProviderError>
+where
+    P: Provider + Sync,
+{
+    match provider
+        .get_class(BlockId::Tag(BlockTag::Pending), class_hash)
+        .await
+    {
+        Ok(_) => Ok(true),
+        Err(ProviderError::StarknetError(StarknetError::ClassHashNotFound)) => Ok(false),
+        Err(e) => Err(e),
+    }
+}
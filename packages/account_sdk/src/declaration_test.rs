@@ -0,0 +1,36 @@
+use starknet::core::types::{BlockId, BlockTag};
+use starknet::providers::Provider;
+
+use crate::artifacts::{Version, CONTROLLERS};
+use crate::tests::runners::katana::KatanaRunner;
+
+use super::declare_controller_classes;
+
+#[tokio::test]
+async fn test_declare_controller_classes() {
+    let runner = KatanaRunner::load();
+    let account = runner.executor().await;
+    let versions = [Version::V1_0_5, Version::V1_0_6];
+
+    let declared = declare_controller_classes(&account, &versions)
+        .await
+        .unwrap();
+    assert_eq!(declared.len(), versions.len());
+    for (class, version) in declared.iter().zip(versions) {
+        assert_eq!(class.version, version);
+        assert_eq!(class.class_hash, CONTROLLERS[&version].hash);
+        runner
+            .client()
+            .get_class(BlockId::Tag(BlockTag::Pending), class.class_hash)
+            .await
+            .unwrap();
+    }
+
+    // Declaring again is a no-op
+    let declared = declare_controller_classes(&account, &versions)
+        .await
+        .unwrap();
+    assert!(declared
+        .iter()
+        .all(|class| class.transaction_hash.is_none()));
+}
@@ -0,0 +1,79 @@
+use futures::future::join_all;
+use starknet::core::types::{BlockId, BlockTag, Felt, StarknetError};
+use starknet::core::utils::{cairo_short_string_to_felt, get_contract_address};
+use starknet::providers::{Provider, ProviderError};
+
+use crate::{
+    artifacts::{Version, CONTROLLERS, VERSIONS},
+    errors::ControllerError,
+    factory::constructor_calldata,
+    signers::Owner,
+};
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+#[path = "discovery_test.rs"]
+mod discovery_test;
+
+/// A deployed Controller found from its username and owner.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DiscoveredController {
+    /// Version the Controller was deployed with, from which its address derives.
+    pub version: Version,
+    pub address: Felt,
+    /// Class hash on chain, which differs from the one of `version` once upgraded.
+    pub class_hash: Felt,
+}
+
+/// Addresses a Controller of `username` owned by `owner` would have, for every version in
+/// [`VERSIONS`].
+pub fn candidate_addresses(
+    username: &str,
+    owner: &Owner,
+) -> Result<Vec<(Version, Felt)>, ControllerError> {
+    let salt = cairo_short_string_to_felt(username)
+        .map_err(|_| ControllerError::InvalidUsername(username.to_string()))?;
+    let calldata = constructor_calldata(owner);
+
+    Ok(VERSIONS
+        .iter()
+        .map(|version| {
+            let class_hash = CONTROLLERS[version].hash;
+            let address = get_contract_address(salt, class_hash, &calldata, Felt::ZERO);
+            (*version, address)
+        })
+        .collect())
+}
+
+/// Finds the Controllers of `username` owned by `owner` that are deployed, by checking every
+/// candidate address in [`VERSIONS`] order. This recovers an account whose local storage was
+/// lost.
+pub async fn discover_controllers<P>(
+    provider: &P,
+    username: &str,
+    owner: &Owner,
+) -> Result<Vec<DiscoveredController>, ControllerError>
+where
+    P: Provider + Sync,
+{
+    let candidates = candidate_addresses(username, owner)?;
+    let class_hashes =
+        join_all(candidates.iter().map(|(_, address)| {
+            provider.get_class_hash_at(BlockId::Tag(BlockTag::Pending), *address)
+        }))
+        .await;
+
+    let mut controllers = Vec::new();
+    for ((version, address), class_hash) in candidates.into_iter().zip(class_hashes) {
+        match class_hash {
+            Ok(class_hash) => controllers.push(DiscoveredController {
+                version,
+                address,
+                class_hash,
+            }),
+            Err(ProviderError::StarknetError(StarknetError::ContractNotFound)) => {}
+            Err(e) => return Err(e.into()),
+        }
+    }
+
+    Ok(controllers)
+}
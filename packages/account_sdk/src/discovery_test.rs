@@ -0,0 +1,66 @@
+use crate::artifacts::{Version, CONTROLLERS, VERSIONS};
+use crate::errors::ControllerError;
+use crate::signers::{Owner, Signer};
+use crate::tests::runners::katana::KatanaRunner;
+
+use super::{candidate_addresses, discover_controllers, DiscoveredController};
+
+#[test]
+fn test_candidate_addresses() {
+    let owner = Owner::Signer(Signer::new_starknet_random());
+    let candidates = candidate_addresses("username", &owner).unwrap();
+
+    assert_eq!(
+        candidates
+            .iter()
+            .map(|(version, _)| *version)
+            .collect::<Vec<_>>(),
+        *VERSIONS
+    );
+
+    let other = candidate_addresses("other", &owner).unwrap();
+    assert!(candidates
+        .iter()
+        .zip(other.iter())
+        .all(|((_, address), (_, other))| address != other));
+
+    let username = "a".repeat(32);
+    assert!(matches!(
+        candidate_addresses(&username, &owner),
+        Err(ControllerError::InvalidUsername(u)) if u == username
+    ));
+}
+
+#[tokio::test]
+async fn test_discover_controllers() {
+    let runner = KatanaRunner::load();
+    let owner = Owner::Signer(Signer::new_starknet_random());
+    let controller = runner
+        .deploy_controller("testuser".to_owned(), owner.clone(), Version::V1_0_4)
+        .await;
+
+    let discovered = discover_controllers(runner.client(), "testuser", &owner)
+        .await
+        .unwrap();
+    assert_eq!(
+        discovered,
+        vec![DiscoveredController {
+            version: Version::V1_0_4,
+            address: controller.address,
+            class_hash: CONTROLLERS[&Version::V1_0_4].hash,
+        }]
+    );
+
+    // Another owner or username leads to other addresses
+    let other_owner = Owner::Signer(Signer::new_starknet_random());
+    assert!(
+        discover_controllers(runner.client(), "testuser", &other_owner)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+    assert!(discover_controllers(runner.client(), "otheruser", &owner)
+        .await
+        .unwrap()
+        .is_empty());
+}
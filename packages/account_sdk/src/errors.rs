@@ -1,11 +1,14 @@
 use cainome::cairo_serde;
 use starknet::{
     accounts::{AccountError, AccountFactoryError},
-    core::types::FeeEstimate,
+    core::types::{FeeEstimate, Felt},
     providers::ProviderError,
 };
 
-use crate::{provider::ExecuteFromOutsideError, signers::SignError};
+use crate::{
+    provider::ExecuteFromOutsideError, signers::SignError,
+    transaction_waiter::TransactionWaitingError,
+};
 
 #[derive(Debug, thiserror::Error)]
 pub enum ControllerError {
@@ -18,6 +21,7 @@ pub enum ControllerError {
     #[error(transparent)]
     AccountError(#[from] AccountError<SignError>),
 
+    /// `balance` is in the token the fee is paid in, given by the unit of `fee_estimate`.
     #[error("Controller is not deployed. Required fee: {fee_estimate:?}")]
     NotDeployed {
         fee_estimate: Box<FeeEstimate>,
@@ -36,6 +40,7 @@ pub enum ControllerError {
     #[error(transparent)]
     ProviderError(#[from] ProviderError),
 
+    /// `balance` is in the token the fee is paid in, given by the unit of `fee_estimate`.
     #[error("Insufficient balance for transaction. Required fee: {fee_estimate:?}")]
     InsufficientBalance {
         fee_estimate: Box<FeeEstimate>,
@@ -48,6 +53,18 @@ pub enum ControllerError {
     #[error(transparent)]
     UrlParseError(#[from] url::ParseError),
 
+    #[error(transparent)]
+    TransactionWaitingError(#[from] TransactionWaitingError),
+
+    #[error("Owner {0:#x} wasn't updated on chain")]
+    OwnerNotUpdated(Felt),
+
+    #[error("{0} signatures can't be verified offline")]
+    OfflineVerificationUnsupported(String),
+
+    #[error("Username {0} isn't a short string of at most 31 ASCII characters")]
+    InvalidUsername(String),
+
     #[cfg(feature = "webauthn")]
     #[error(transparent)]
     Base64DecodeError(#[from] base64::DecodeError),
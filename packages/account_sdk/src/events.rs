@@ -0,0 +1,133 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use futures::stream::{self, Stream};
+use starknet::core::types::{BlockId, EmittedEvent, EventFilter, Felt};
+use starknet::providers::Provider;
+
+use crate::{
+    abigen::controller::ControllerEvent, controller::Controller, errors::ControllerError,
+    utils::time::sleep,
+};
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+#[path = "events_test.rs"]
+mod events_test;
+
+/// A decoded event emitted by a Controller, along with where it was emitted.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EmittedControllerEvent {
+    pub event: ControllerEvent,
+    /// `None` while the block is pending.
+    pub block_hash: Option<Felt>,
+    /// `None` while the block is pending.
+    pub block_number: Option<u64>,
+    pub transaction_hash: Felt,
+}
+
+impl TryFrom<EmittedEvent> for EmittedControllerEvent {
+    type Error = String;
+
+    fn try_from(event: EmittedEvent) -> Result<Self, Self::Error> {
+        let block_hash = event.block_hash;
+        let block_number = event.block_number;
+        let transaction_hash = event.transaction_hash;
+
+        Ok(Self {
+            event: event.try_into()?,
+            block_hash,
+            block_number,
+            transaction_hash,
+        })
+    }
+}
+
+impl Controller {
+    const EVENTS_CHUNK_SIZE: u64 = 100;
+
+    /// Fetches every event the Controller emitted between `from_block` and `to_block`, both
+    /// included, in the order they were emitted.
+    ///
+    /// Events that don't decode to a [`ControllerEvent`], e.g. ones emitted by a component
+    /// missing from the bindings, are skipped.
+    pub async fn events(
+        &self,
+        from_block: BlockId,
+        to_block: BlockId,
+    ) -> Result<Vec<EmittedControllerEvent>, ControllerError> {
+        let filter = EventFilter {
+            from_block: Some(from_block),
+            to_block: Some(to_block),
+            address: Some(self.address),
+            keys: None,
+        };
+
+        let mut events = Vec::new();
+        let mut continuation_token = None;
+        loop {
+            let page = self
+                .provider
+                .get_events(filter.clone(), continuation_token, Self::EVENTS_CHUNK_SIZE)
+                .await?;
+
+            events.extend(
+                page.events
+                    .into_iter()
+                    .filter_map(|event| EmittedControllerEvent::try_from(event).ok()),
+            );
+
+            match page.continuation_token {
+                Some(token) => continuation_token = Some(token),
+                None => return Ok(events),
+            }
+        }
+    }
+
+    /// Returns a stream of the events emitted from `from_block` onwards.
+    ///
+    /// The stream checks for new blocks every `poll_interval` and never ends. Only accepted
+    /// blocks are read, so an event is yielded once its block is no longer pending. A failed
+    /// poll yields the error and is retried on the next interval.
+    pub fn event_stream(
+        &self,
+        from_block: u64,
+        poll_interval: Duration,
+    ) -> impl Stream<Item = Result<EmittedControllerEvent, ControllerError>> + '_ {
+        let state = (from_block, VecDeque::new());
+
+        stream::unfold(state, move |(mut next_block, mut buffer)| async move {
+            loop {
+                if let Some(event) = buffer.pop_front() {
+                    return Some((Ok(event), (next_block, buffer)));
+                }
+
+                let latest_block = match self.provider.block_number().await {
+                    Ok(block_number) => block_number,
+                    Err(e) => {
+                        sleep(poll_interval).await;
+                        return Some((Err(e.into()), (next_block, buffer)));
+                    }
+                };
+
+                if latest_block < next_block {
+                    sleep(poll_interval).await;
+                    continue;
+                }
+
+                match self
+                    .events(BlockId::Number(next_block), BlockId::Number(latest_block))
+                    .await
+                {
+                    Ok(events) => {
+                        buffer.extend(events);
+                        next_block = latest_block + 1;
+                    }
+                    Err(e) => {
+                        sleep(poll_interval).await;
+                        return Some((Err(e), (next_block, buffer)));
+                    }
+                }
+            }
+        })
+    }
+}
@@ -0,0 +1,76 @@
+use std::time::Duration;
+
+use cainome::cairo_serde::{ContractAddress, U256};
+use futures::StreamExt;
+use starknet::{
+    core::types::{BlockId, BlockTag},
+    macros::felt,
+    providers::Provider,
+};
+
+use crate::{
+    abigen::{controller::ControllerEvent, erc_20::Erc20},
+    artifacts::Version,
+    signers::{Owner, Signer},
+    tests::{account::FEE_TOKEN_ADDRESS, runners::katana::KatanaRunner},
+    transaction_waiter::TransactionWaiter,
+};
+
+#[tokio::test]
+async fn test_events_and_stream() {
+    let runner = KatanaRunner::load();
+    let mut controller = runner
+        .deploy_controller(
+            "testuser".to_owned(),
+            Owner::Signer(Signer::new_starknet_random()),
+            Version::LATEST,
+        )
+        .await;
+
+    let from_block = runner.client().block_number().await.unwrap();
+
+    let erc20 = Erc20::new(*FEE_TOKEN_ADDRESS, &controller);
+    let call = erc20.transfer_getcall(
+        &ContractAddress(felt!("0x18301129")),
+        &U256 { low: 1, high: 0 },
+    );
+    let max_fee = controller
+        .estimate_invoke_fee(vec![call.clone()])
+        .await
+        .unwrap();
+
+    let mut transaction_hashes = Vec::new();
+    for _ in 0..2 {
+        let result = controller
+            .execute(vec![call.clone()], max_fee.overall_fee)
+            .await
+            .unwrap();
+        TransactionWaiter::new(result.transaction_hash, runner.client())
+            .with_timeout(Duration::from_secs(5))
+            .wait()
+            .await
+            .unwrap();
+        transaction_hashes.push(result.transaction_hash);
+    }
+
+    let events = controller
+        .events(BlockId::Number(from_block), BlockId::Tag(BlockTag::Latest))
+        .await
+        .unwrap();
+
+    let executed = events
+        .iter()
+        .filter(|event| matches!(event.event, ControllerEvent::TransactionExecuted(_)))
+        .map(|event| event.transaction_hash)
+        .collect::<Vec<_>>();
+    assert_eq!(executed, transaction_hashes);
+    assert!(events.iter().all(|event| event.block_number.is_some()));
+
+    let streamed = controller
+        .event_stream(from_block, Duration::from_millis(100))
+        .take(events.len())
+        .map(|event| event.unwrap())
+        .collect::<Vec<_>>()
+        .await;
+    assert_eq!(streamed, events);
+}
@@ -0,0 +1,197 @@
+use cainome::cairo_serde::ContractAddress;
+use starknet::core::types::{BlockId, BlockTag, Call, Felt, TransactionReceiptWithBlockInfo};
+use starknet::macros::short_string;
+use starknet::signers::SigningKey;
+
+use crate::{
+    abigen::controller::{ControllerEvent, ExternalOwnersEvent},
+    account::session::{account::SessionAccount, hash::Session, policy::Policy},
+    controller::Controller,
+    errors::ControllerError,
+    signers::{Owner, SignError, Signer},
+    storage::{selectors::Selectors, Credentials, SessionMetadata, StorageBackend},
+};
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+#[path = "external_owners_test.rs"]
+mod external_owners_test;
+
+impl Controller {
+    /// Whether the account at `address` is an external owner of the Controller.
+    pub async fn is_external_owner(&self, address: Felt) -> Result<bool, ControllerError> {
+        self.contract()
+            .is_external_owner(&ContractAddress(address))
+            .call()
+            .await
+            .map_err(ControllerError::CairoSerde)
+    }
+
+    /// Call registering the account at `address` as an external owner. An external owner can
+    /// execute it for a Controller whose owner is itself an account.
+    pub fn register_external_owner_call(&self, address: Felt) -> Call {
+        self.contract()
+            .register_external_owner_getcall(&ContractAddress(address))
+    }
+
+    /// Call removing the external owner at `address`.
+    pub fn remove_external_owner_call(&self, address: Felt) -> Call {
+        self.contract()
+            .remove_external_owner_getcall(&ContractAddress(address))
+    }
+
+    /// Registers the account at `address` as an external owner, paying at most `max_fee` as in
+    /// [`Controller::execute`], and waits for it to be accepted.
+    pub async fn register_external_owner(
+        &mut self,
+        address: Felt,
+        max_fee: Felt,
+    ) -> Result<TransactionReceiptWithBlockInfo, ControllerError> {
+        let call = self.register_external_owner_call(address);
+        let receipt = self.execute_and_wait(vec![call], max_fee).await?;
+
+        self.ensure_external_owner(address, true).await?;
+        Ok(receipt)
+    }
+
+    /// Removes the external owner at `address`, like [`Controller::register_external_owner`].
+    pub async fn remove_external_owner(
+        &mut self,
+        address: Felt,
+        max_fee: Felt,
+    ) -> Result<TransactionReceiptWithBlockInfo, ControllerError> {
+        let call = self.remove_external_owner_call(address);
+        let receipt = self.execute_and_wait(vec![call], max_fee).await?;
+
+        self.ensure_external_owner(address, false).await?;
+        Ok(receipt)
+    }
+
+    /// Current external owners, in the order they were registered, replayed from the
+    /// `ExternalOwnerRegistered` and `ExternalOwnerRemoved` events the Controller emitted since
+    /// `from_block`.
+    ///
+    /// Every event from `from_block` to the pending block is fetched, page by page, so pass the
+    /// block the Controller was deployed in, or any block before its first external owner was
+    /// registered. `BlockId::Number(0)` scans the whole history of the chain.
+    pub async fn external_owners(&self, from_block: BlockId) -> Result<Vec<Felt>, ControllerError> {
+        let events = self
+            .events(from_block, BlockId::Tag(BlockTag::Pending))
+            .await?;
+
+        let mut owners = Vec::new();
+        for emitted in events {
+            match emitted.event {
+                ControllerEvent::ExternalOwnersEvent(
+                    ExternalOwnersEvent::ExternalOwnerRegistered(event),
+                ) => {
+                    if !owners.contains(&event.address.0) {
+                        owners.push(event.address.0);
+                    }
+                }
+                ControllerEvent::ExternalOwnersEvent(
+                    ExternalOwnersEvent::ExternalOwnerRemoved(event),
+                ) => owners.retain(|owner| *owner != event.address.0),
+                _ => {}
+            }
+        }
+
+        Ok(owners)
+    }
+
+    /// Creates a session the external owner at `external_owner` registers, for Controllers
+    /// whose owner can't sign, e.g. an [`Owner::Account`](crate::signers::Owner::Account).
+    ///
+    /// Returns the call the external owner has to execute from its own account, and the
+    /// session account. The session is stored, so once registered the Controller executes the
+    /// calls it allows through it.
+    pub fn create_external_owner_session(
+        &self,
+        external_owner: Felt,
+        policies: Vec<Policy>,
+        expires_at: u64,
+    ) -> Result<(Call, SessionAccount), ControllerError> {
+        let signer = SigningKey::from_random();
+        let session_signer = Signer::Starknet(signer.clone());
+        let session = Session::new(
+            policies,
+            expires_at,
+            &session_signer.clone().into(),
+            self.guardian,
+        )?;
+
+        let call = self
+            .contract()
+            .register_session_getcall(&session.clone().into(), &external_owner);
+
+        let authorization = vec![short_string!("authorization-by-registered"), external_owner];
+        self.storage.clone().set_session(
+            &Selectors::session(&self.address, &self.app_id, &self.chain_id),
+            SessionMetadata {
+                session: session.clone(),
+                max_fee: None,
+                credentials: Some(Credentials {
+                    authorization,
+                    private_key: signer.secret_scalar(),
+                }),
+                is_registered: false,
+            },
+        )?;
+
+        let session_account = SessionAccount::new_as_registered(
+            self.provider.clone(),
+            session_signer,
+            self.address,
+            self.chain_id,
+            external_owner,
+            session,
+        );
+
+        Ok((call, session_account))
+    }
+
+    /// Whether the Controller is owned by an account it can act through.
+    pub(crate) fn is_owned_by_connected_account(&self) -> bool {
+        matches!(&self.owner, Owner::Account(owner) if owner.account().is_some())
+    }
+
+    /// Has the connected owner account register a session allowing `policies` until
+    /// `expires_at`, and waits for it to be accepted. This is how a Controller owned by an
+    /// account signs, as the contract never validates a signature of its external owners.
+    ///
+    /// The Controller then signs the calls the session allows with it. Signing never
+    /// registers a session by itself, it fails with [`SignError::SessionNotAuthorized`] when
+    /// no registered session allows the calls.
+    pub async fn authorize_owner_session(
+        &mut self,
+        policies: Vec<Policy>,
+        expires_at: u64,
+    ) -> Result<SessionAccount, ControllerError> {
+        let Owner::Account(owner) = &self.owner else {
+            return Err(SignError::AccountOwnerCannotSign.into());
+        };
+        let owner = owner.clone();
+
+        let (call, session_account) =
+            self.create_external_owner_session(owner.address(), policies, expires_at)?;
+        owner.execute(vec![call]).await?;
+
+        let key = Selectors::session(&self.address, &self.app_id, &self.chain_id);
+        if let Some(mut metadata) = self.storage.session(&key)? {
+            metadata.is_registered = true;
+            self.storage.set_session(&key, metadata)?;
+        }
+
+        Ok(session_account)
+    }
+
+    async fn ensure_external_owner(
+        &self,
+        address: Felt,
+        expected: bool,
+    ) -> Result<(), ControllerError> {
+        if self.is_external_owner(address).await? != expected {
+            return Err(ControllerError::OwnerNotUpdated(address));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,210 @@
+use cainome::cairo_serde::{ContractAddress, U256};
+use starknet::{
+    accounts::Account,
+    core::{
+        types::{BlockId, Felt},
+        utils::cairo_short_string_to_felt,
+    },
+    macros::{felt, selector},
+    providers::Provider,
+};
+
+use crate::{
+    abigen::erc_20::Erc20,
+    account::{session::policy::Policy, AccountHashAndCallsSigner},
+    artifacts::{Version, CONTROLLERS},
+    constants::UDC_ADDRESS,
+    controller::ControllerBuilder,
+    factory::{constructor_calldata, ControllerFactory},
+    provider::CartridgeJsonRpcProvider,
+    signers::{AccountOwner, Owner, SignError, Signer},
+    tests::{account::FEE_TOKEN_ADDRESS, ensure_txn, runners::katana::KatanaRunner},
+    transaction_waiter::TransactionWaiter,
+    utils::time::get_current_timestamp,
+};
+
+#[tokio::test]
+async fn test_register_and_remove_external_owner() {
+    let runner = KatanaRunner::load();
+    let external_account = runner.executor().await;
+    let mut controller = runner
+        .deploy_controller(
+            "username".to_owned(),
+            Owner::Signer(Signer::new_starknet_random()),
+            Version::LATEST,
+        )
+        .await;
+    let max_fee = felt!("0x10000000000000");
+    let deployed_at = BlockId::Number(runner.client().block_number().await.unwrap());
+
+    assert!(controller
+        .external_owners(deployed_at)
+        .await
+        .unwrap()
+        .is_empty());
+
+    controller
+        .register_external_owner(external_account.address(), max_fee)
+        .await
+        .unwrap();
+    assert!(controller
+        .is_external_owner(external_account.address())
+        .await
+        .unwrap());
+    assert_eq!(
+        controller.external_owners(deployed_at).await.unwrap(),
+        vec![external_account.address()]
+    );
+
+    controller
+        .remove_external_owner(external_account.address(), max_fee)
+        .await
+        .unwrap();
+    assert!(!controller
+        .is_external_owner(external_account.address())
+        .await
+        .unwrap());
+    assert!(controller
+        .external_owners(deployed_at)
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn test_external_owner_session() {
+    let runner = KatanaRunner::load();
+    let external_account = runner.executor().await;
+    let mut controller = runner
+        .deploy_controller(
+            "external_owner".to_owned(),
+            Owner::Account(external_account.address().into()),
+            Version::LATEST,
+        )
+        .await;
+
+    let transfer = Policy::new_call(*FEE_TOKEN_ADDRESS, starknet::macros::selector!("transfer"));
+    let (register_session, _) = controller
+        .create_external_owner_session(external_account.address(), vec![transfer], u64::MAX)
+        .unwrap();
+    ensure_txn(
+        external_account.execute_v1(vec![register_session]),
+        runner.client(),
+    )
+    .await
+    .unwrap();
+
+    // The Controller can't sign, it executes through the session
+    let recipient = ContractAddress(felt!("0x18301129"));
+    let call = Erc20::new(*FEE_TOKEN_ADDRESS, &controller)
+        .transfer_getcall(&recipient, &U256 { low: 0x10, high: 0 });
+    let result = controller
+        .execute(vec![call], felt!("0x10000000000000"))
+        .await
+        .unwrap();
+    TransactionWaiter::new(result.transaction_hash, runner.client())
+        .wait()
+        .await
+        .unwrap();
+}
+
+#[test]
+fn test_deploy_call_goes_through_the_udc() {
+    let owner = Owner::Account(felt!("0xabc").into());
+    let factory = ControllerFactory::new(
+        felt!("0x1234"),
+        felt!("0x534e5f5345504f4c4941"),
+        owner.clone(),
+        CartridgeJsonRpcProvider::new(url::Url::parse("http://localhost:1/").unwrap()),
+    );
+
+    let call = factory.deploy_call(felt!("0x5"));
+    let calldata = constructor_calldata(&owner);
+
+    assert_eq!(call.to, UDC_ADDRESS);
+    assert_eq!(call.selector, selector!("deployContract"));
+    assert_eq!(
+        call.calldata[..4],
+        [
+            felt!("0x1234"),
+            felt!("0x5"),
+            Felt::ZERO,
+            calldata.len().into()
+        ]
+    );
+    assert_eq!(call.calldata[4..], calldata[..]);
+}
+
+#[tokio::test]
+async fn test_controller_owned_by_controller() {
+    let runner = KatanaRunner::load();
+    let owner_controller = runner
+        .deploy_controller(
+            "owner".to_owned(),
+            Owner::Signer(Signer::new_starknet_random()),
+            Version::LATEST,
+        )
+        .await;
+    let chain_id = runner.client().chain_id().await.unwrap();
+
+    let username = "owned".to_owned();
+    let salt = cairo_short_string_to_felt(&username).unwrap();
+    let owner = Owner::Account(AccountOwner::connected(owner_controller.clone()));
+    let factory = ControllerFactory::new(
+        CONTROLLERS[&Version::LATEST].hash,
+        chain_id,
+        owner.clone(),
+        runner.client().clone(),
+    );
+
+    // The owner deploys the Controller, as it can't sign a deploy account transaction
+    factory.deploy_through_owner(salt).await.unwrap();
+    let address = factory.address(salt);
+    runner.fund(&address).await;
+
+    let mut controller = ControllerBuilder::new(
+        "owned_app".to_owned(),
+        username,
+        CONTROLLERS[&Version::LATEST].hash,
+        runner.rpc_url.clone(),
+        owner,
+        address,
+        chain_id,
+    )
+    .with_provider(runner.client().clone())
+    .with_persistence(false)
+    .build()
+    .unwrap();
+    assert!(controller
+        .is_external_owner(owner_controller.address())
+        .await
+        .unwrap());
+
+    // Signing never sends the transaction registering a session
+    let recipient = ContractAddress(felt!("0x18301129"));
+    let call = Erc20::new(*FEE_TOKEN_ADDRESS, &controller)
+        .transfer_getcall(&recipient, &U256 { low: 0x10, high: 0 });
+    assert!(matches!(
+        controller
+            .sign_hash_and_calls(Felt::ONE, &[call.clone()])
+            .await,
+        Err(SignError::SessionNotAuthorized)
+    ));
+
+    // The owner registers a session allowing the transfer, which then signs it
+    controller
+        .authorize_owner_session(
+            Policy::from_calls(&[call.clone()]),
+            get_current_timestamp() + 3600,
+        )
+        .await
+        .unwrap();
+    let result = controller
+        .execute(vec![call], felt!("0x10000000000000"))
+        .await
+        .unwrap();
+    TransactionWaiter::new(result.transaction_hash, runner.client())
+        .wait()
+        .await
+        .unwrap();
+}
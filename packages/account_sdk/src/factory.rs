@@ -2,15 +2,17 @@ use async_trait::async_trait;
 use cainome::cairo_serde::CairoSerde;
 use starknet::{
     accounts::{
-        AccountDeploymentV1, AccountFactory, PreparedAccountDeploymentV1,
+        AccountDeploymentV1, AccountDeploymentV3, AccountFactory, PreparedAccountDeploymentV1,
         PreparedAccountDeploymentV3, RawAccountDeploymentV1, RawAccountDeploymentV3,
     },
-    core::types::{BlockId, BlockTag, Felt},
+    core::types::{BlockId, BlockTag, Call, Felt},
+    macros::selector,
 };
 
 use crate::{
     abigen::controller::SignerSignature,
-    provider::CartridgeJsonRpcProvider,
+    constants::UDC_ADDRESS,
+    provider::{shared::SharedProvider, CartridgeProvider},
     signers::{HashSigner, Owner, SignError},
 };
 
@@ -19,22 +21,20 @@ pub struct ControllerFactory {
     class_hash: Felt,
     chain_id: Felt,
     owner: Owner,
-    provider: CartridgeJsonRpcProvider,
+    provider: SharedProvider,
     block_id: BlockId,
 }
 
 impl ControllerFactory {
-    pub fn new(
-        class_hash: Felt,
-        chain_id: Felt,
-        owner: Owner,
-        provider: CartridgeJsonRpcProvider,
-    ) -> Self {
+    pub fn new<P>(class_hash: Felt, chain_id: Felt, owner: Owner, provider: P) -> Self
+    where
+        P: CartridgeProvider + Send + Sync + 'static,
+    {
         Self {
             class_hash,
             chain_id,
             owner,
-            provider,
+            provider: SharedProvider::new(provider),
             block_id: BlockId::Tag(BlockTag::Pending),
         }
     }
@@ -42,12 +42,46 @@ impl ControllerFactory {
     pub fn address(&self, salt: Felt) -> Felt {
         self.deploy_v1(salt).address()
     }
+
+    /// Call deploying the Controller through the Universal Deployer, to the same address as
+    /// the deploy account transaction would.
+    pub fn deploy_call(&self, salt: Felt) -> Call {
+        let calldata = constructor_calldata(&self.owner);
+
+        let mut udc_calldata = vec![self.class_hash, salt, Felt::ZERO];
+        udc_calldata.push(Felt::from(calldata.len()));
+        udc_calldata.extend(calldata);
+
+        Call {
+            to: UDC_ADDRESS,
+            selector: selector!("deployContract"),
+            calldata: udc_calldata,
+        }
+    }
+
+    /// Deploys a Controller owned by an account through that account, as it can't sign the
+    /// deploy account transaction. Returns the hash of the transaction once accepted.
+    pub async fn deploy_through_owner(&self, salt: Felt) -> Result<Felt, SignError> {
+        match &self.owner {
+            Owner::Account(owner) => owner.execute(vec![self.deploy_call(salt)]).await,
+            Owner::Signer(_) => Err(SignError::OwnerAccount(
+                "the Controller isn't owned by an account".to_string(),
+            )),
+        }
+    }
+}
+
+/// Constructor calldata of a Controller owned by `owner`, without a guardian.
+pub(crate) fn constructor_calldata(owner: &Owner) -> Vec<Felt> {
+    let mut calldata = crate::abigen::controller::Owner::cairo_serialize(&owner.clone().into());
+    calldata.push(Felt::ONE); // no guardian
+    calldata
 }
 
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
 impl AccountFactory for ControllerFactory {
-    type Provider = CartridgeJsonRpcProvider;
+    type Provider = SharedProvider;
     type SignError = SignError;
 
     fn class_hash(&self) -> Felt {
@@ -55,10 +89,7 @@ impl AccountFactory for ControllerFactory {
     }
 
     fn calldata(&self) -> Vec<Felt> {
-        let mut calldata =
-            crate::abigen::controller::Owner::cairo_serialize(&self.owner.clone().into());
-        calldata.push(Felt::ONE); // no guardian
-        calldata
+        constructor_calldata(&self.owner)
     }
 
     fn chain_id(&self) -> Felt {
@@ -102,4 +133,8 @@ impl AccountFactory for ControllerFactory {
     fn deploy_v1(&self, salt: Felt) -> AccountDeploymentV1<'_, Self> {
         AccountDeploymentV1::new(salt, self)
     }
+
+    fn deploy_v3(&self, salt: Felt) -> AccountDeploymentV3<'_, Self> {
+        AccountDeploymentV3::new(salt, self)
+    }
 }
@@ -0,0 +1,87 @@
+use serde::{Deserialize, Serialize};
+use starknet::core::types::{FeeEstimate, Felt, PriceUnit};
+
+use crate::constants::{ETH_CONTRACT_ADDRESS, STRK_CONTRACT_ADDRESS};
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+#[path = "fees_test.rs"]
+mod fees_test;
+
+/// Token a transaction fee is paid in. V1 transactions pay in ETH and V3 transactions in STRK.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum FeeToken {
+    Eth,
+    Strk,
+}
+
+impl FeeToken {
+    pub fn address(&self) -> Felt {
+        match self {
+            FeeToken::Eth => ETH_CONTRACT_ADDRESS,
+            FeeToken::Strk => STRK_CONTRACT_ADDRESS,
+        }
+    }
+}
+
+impl From<PriceUnit> for FeeToken {
+    fn from(unit: PriceUnit) -> Self {
+        match unit {
+            PriceUnit::Wei => FeeToken::Eth,
+            PriceUnit::Fri => FeeToken::Strk,
+        }
+    }
+}
+
+/// Maximum amount of a resource a V3 transaction may consume, and the maximum price it pays
+/// per unit.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResourceBound {
+    pub max_amount: u64,
+    pub max_price_per_unit: u128,
+}
+
+/// Resource bounds of a V3 transaction, whose fee is paid in STRK.
+///
+/// The `starknet` version in use only lets transactions bound their L1 gas. The L1 data gas and
+/// L2 gas bounds, and the tip, are sent as zero until it is upgraded, at which point they will be
+/// added here.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResourceBounds {
+    pub l1_gas: ResourceBound,
+}
+
+impl ResourceBounds {
+    /// Margin applied to estimates, as a fraction, so that the transaction still goes through
+    /// when gas usage or prices move slightly between estimation and inclusion.
+    const ESTIMATE_MARGIN: (u128, u128) = (3, 2);
+
+    pub fn new(l1_gas: ResourceBound) -> Self {
+        Self { l1_gas }
+    }
+
+    /// Bounds covering `estimate` with a 50% margin on both the amount of gas and its price.
+    pub fn from_fee_estimate(estimate: &FeeEstimate) -> Self {
+        let (numerator, denominator) = Self::ESTIMATE_MARGIN;
+        let overall_fee = felt_to_u128(estimate.overall_fee);
+        let gas_price = felt_to_u128(estimate.gas_price).max(1);
+
+        // The overall fee includes data gas, it is folded into the L1 gas amount
+        let amount = overall_fee.div_ceil(gas_price).saturating_mul(numerator) / denominator;
+        let price = gas_price.saturating_mul(numerator) / denominator;
+
+        Self::new(ResourceBound {
+            max_amount: u64::try_from(amount).unwrap_or(u64::MAX),
+            max_price_per_unit: price,
+        })
+    }
+
+    /// Maximum fee the transaction can be charged, in FRI.
+    pub fn max_fee(&self) -> u128 {
+        u128::from(self.l1_gas.max_amount).saturating_mul(self.l1_gas.max_price_per_unit)
+    }
+}
+
+pub(crate) fn felt_to_u128(value: Felt) -> u128 {
+    u128::try_from(value).unwrap_or(u128::MAX)
+}
@@ -0,0 +1,36 @@
+use starknet::core::types::{FeeEstimate, Felt, PriceUnit};
+
+use crate::constants::STRK_CONTRACT_ADDRESS;
+
+use super::{FeeToken, ResourceBound, ResourceBounds};
+
+#[test]
+fn test_fee_token_from_unit() {
+    assert_eq!(FeeToken::from(PriceUnit::Wei), FeeToken::Eth);
+    assert_eq!(FeeToken::from(PriceUnit::Fri), FeeToken::Strk);
+    assert_eq!(FeeToken::Strk.address(), STRK_CONTRACT_ADDRESS);
+}
+
+#[test]
+fn test_resource_bounds_from_fee_estimate() {
+    let estimate = FeeEstimate {
+        gas_consumed: Felt::from(90_u64),
+        gas_price: Felt::from(100_u64),
+        data_gas_consumed: Felt::from(1_u64),
+        data_gas_price: Felt::from(1_001_u64),
+        overall_fee: Felt::from(10_001_u64),
+        unit: PriceUnit::Fri,
+    };
+
+    let bounds = ResourceBounds::from_fee_estimate(&estimate);
+
+    // 10_001 / 100 rounds up to 101 units of gas
+    assert_eq!(
+        bounds,
+        ResourceBounds::new(ResourceBound {
+            max_amount: 151,
+            max_price_per_unit: 150,
+        })
+    );
+    assert_eq!(bounds.max_fee(), 151 * 150);
+}
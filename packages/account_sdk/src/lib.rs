@@ -1,19 +1,31 @@
+pub mod abi;
 pub mod abigen;
 pub mod account;
 pub mod artifacts;
 pub mod constants;
 pub mod controller;
+pub mod declaration;
+pub mod discovery;
 pub mod errors;
+pub mod events;
 pub mod execute_from_outside;
+pub mod external_owners;
 pub mod factory;
+pub mod fees;
 pub mod hash;
+pub mod owners;
 pub mod provider;
+pub mod scheduled;
 pub mod session;
 pub mod signers;
+pub mod simulation;
 pub mod storage;
+pub mod tokens;
+pub mod transaction_waiter;
 pub mod typed_data;
 pub mod upgrade;
 pub mod utils;
+pub mod verification;
 
 #[cfg(not(target_arch = "wasm32"))]
 #[cfg(test)]
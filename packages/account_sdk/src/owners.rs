@@ -0,0 +1,107 @@
+use starknet::accounts::Account;
+use starknet::core::types::{Call, Felt, TransactionReceiptWithBlockInfo};
+
+use crate::{
+    controller::Controller,
+    errors::ControllerError,
+    signers::{NewOwnerSigner, Owner, Signer},
+};
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+#[path = "owners_test.rs"]
+mod owners_test;
+
+impl Controller {
+    /// Whether `signer` is an owner of the Controller on chain.
+    pub async fn is_owner(&self, signer: &Signer) -> Result<bool, ControllerError> {
+        self.contract()
+            .is_owner(&Felt::from(signer.clone()))
+            .call()
+            .await
+            .map_err(ControllerError::CairoSerde)
+    }
+
+    /// Adds `signer` as an owner, paying at most `max_fee` as in [`Controller::execute`], and
+    /// waits for it to be accepted. `signer` signs its own registration, so it has to be able
+    /// to sign, e.g. a WebAuthn signer prompts its device.
+    pub async fn add_owner(
+        &mut self,
+        signer: Signer,
+        max_fee: Felt,
+    ) -> Result<TransactionReceiptWithBlockInfo, ControllerError> {
+        let call = self.add_owner_call(&signer).await?;
+        let receipt = self.execute_and_wait(vec![call], max_fee).await?;
+
+        self.ensure_owner(&signer, true).await?;
+        let guid = Felt::from(signer);
+        self.update_stored_owners(|owners| {
+            if !owners.contains(&guid) {
+                owners.push(guid);
+            }
+        })?;
+        Ok(receipt)
+    }
+
+    /// Removes `signer` from the owners. When it is the signer of the Controller, the
+    /// Controller can't sign anything afterwards until [`Controller::set_owner`] is called.
+    pub async fn remove_owner(
+        &mut self,
+        signer: Signer,
+        max_fee: Felt,
+    ) -> Result<TransactionReceiptWithBlockInfo, ControllerError> {
+        let call = self.contract().remove_owner_getcall(&signer.clone().into());
+        let receipt = self.execute_and_wait(vec![call], max_fee).await?;
+
+        self.ensure_owner(&signer, false).await?;
+        let guid = Felt::from(signer);
+        self.update_stored_owners(|owners| owners.retain(|owner| *owner != guid))?;
+        Ok(receipt)
+    }
+
+    /// Replaces the owner `old` by `new` in a single transaction, so the Controller is never
+    /// left without either of them. When `old` is the signer of the Controller, `new` becomes
+    /// its signer, in storage as well.
+    pub async fn rotate_owner(
+        &mut self,
+        old: Signer,
+        new: Signer,
+        max_fee: Felt,
+    ) -> Result<TransactionReceiptWithBlockInfo, ControllerError> {
+        let calls = vec![
+            self.add_owner_call(&new).await?,
+            self.contract().remove_owner_getcall(&old.clone().into()),
+        ];
+        let receipt = self.execute_and_wait(calls, max_fee).await?;
+
+        self.ensure_owner(&new, true).await?;
+        self.ensure_owner(&old, false).await?;
+
+        let (old, new_guid) = (Felt::from(old), Felt::from(new.clone()));
+        if self.owner_guid() == old {
+            self.set_owner(Owner::Signer(new));
+        }
+        self.update_stored_owners(|owners| {
+            owners.retain(|owner| *owner != old);
+            if !owners.contains(&new_guid) {
+                owners.push(new_guid);
+            }
+        })?;
+        Ok(receipt)
+    }
+
+    async fn add_owner_call(&self, signer: &Signer) -> Result<Call, ControllerError> {
+        let signature = signer
+            .sign_new_owner(&self.chain_id(), &self.address())
+            .await?;
+        Ok(self
+            .contract()
+            .add_owner_getcall(&signer.clone().into(), &signature))
+    }
+
+    async fn ensure_owner(&self, signer: &Signer, expected: bool) -> Result<(), ControllerError> {
+        if self.is_owner(signer).await? != expected {
+            return Err(ControllerError::OwnerNotUpdated(signer.clone().into()));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,85 @@
+use starknet::core::types::Felt;
+use starknet::macros::felt;
+
+use crate::{
+    artifacts::Version,
+    signers::{Owner, Signer},
+    storage::StorageBackend,
+    tests::runners::katana::KatanaRunner,
+};
+
+// Covers the fees of a few owner changes, which can't be estimated before the new owner signs
+const MAX_FEE: Felt = felt!("0x10000000000000");
+
+#[tokio::test]
+async fn test_add_and_remove_owner() {
+    let signer = Signer::new_starknet_random();
+    let runner = KatanaRunner::load();
+    let mut controller = runner
+        .deploy_controller(
+            "username".to_owned(),
+            Owner::Signer(signer.clone()),
+            Version::LATEST,
+        )
+        .await;
+
+    let new_signer = Signer::new_starknet_random();
+    assert!(!controller.is_owner(&new_signer).await.unwrap());
+
+    controller
+        .add_owner(new_signer.clone(), MAX_FEE)
+        .await
+        .unwrap();
+    assert!(controller.is_owner(&signer).await.unwrap());
+    assert!(controller.is_owner(&new_signer).await.unwrap());
+    let metadata = controller.storage.controller("app_id").unwrap().unwrap();
+    assert_eq!(
+        metadata.owners,
+        vec![signer.clone().into(), new_signer.clone().into()]
+    );
+
+    controller
+        .remove_owner(new_signer.clone(), MAX_FEE)
+        .await
+        .unwrap();
+    assert!(controller.is_owner(&signer).await.unwrap());
+    assert!(!controller.is_owner(&new_signer).await.unwrap());
+    let metadata = controller.storage.controller("app_id").unwrap().unwrap();
+    assert_eq!(metadata.owners, vec![Felt::from(signer)]);
+}
+
+#[tokio::test]
+async fn test_rotate_owner() {
+    let signer = Signer::new_starknet_random();
+    let runner = KatanaRunner::load();
+    let mut controller = runner
+        .deploy_controller(
+            "username".to_owned(),
+            Owner::Signer(signer.clone()),
+            Version::LATEST,
+        )
+        .await;
+
+    let new_signer = Signer::new_starknet_random();
+    controller
+        .rotate_owner(signer.clone(), new_signer.clone(), MAX_FEE)
+        .await
+        .unwrap();
+
+    assert!(!controller.is_owner(&signer).await.unwrap());
+    assert!(controller.is_owner(&new_signer).await.unwrap());
+
+    // The new owner signs from now on, and is the one stored
+    let guid = controller.owner_guid();
+    assert_eq!(guid, new_signer.clone().into());
+    let metadata = controller.storage.controller("app_id").unwrap().unwrap();
+    assert_eq!(Felt::from(Owner::try_from(metadata.owner).unwrap()), guid);
+    assert_eq!(metadata.owners, vec![guid]);
+
+    let other_signer = Signer::new_starknet_random();
+    controller
+        .add_owner(other_signer.clone(), MAX_FEE)
+        .await
+        .unwrap();
+    assert!(controller.is_owner(&other_signer).await.unwrap());
+}
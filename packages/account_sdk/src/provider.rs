@@ -18,7 +18,18 @@ use url::Url;
 
 use crate::account::outside_execution::OutsideExecution;
 
-#[cfg(test)]
+use self::config::ProviderConfig;
+
+pub mod config;
+pub mod failover;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod recording;
+pub mod retry;
+pub mod shared;
+
+pub(crate) const OUTSIDE_EXECUTION_METHOD: &str = "cartridge_addExecuteOutsideTransaction";
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
 #[path = "provider_test.rs"]
 mod provider_test;
 
@@ -38,24 +49,43 @@ pub trait CartridgeProvider: Provider + Clone {
 pub struct CartridgeJsonRpcProvider {
     inner: JsonRpcClient<HttpTransport>,
     rpc_url: Url,
+    client: Client,
 }
 
 impl Clone for CartridgeJsonRpcProvider {
     fn clone(&self) -> Self {
-        Self {
-            inner: JsonRpcClient::new(HttpTransport::new(self.rpc_url.clone())),
-            rpc_url: self.rpc_url.clone(),
-        }
+        Self::with_client(self.rpc_url.clone(), self.client.clone())
     }
 }
 
 impl CartridgeJsonRpcProvider {
     pub fn new(rpc_url: Url) -> Self {
+        Self::with_config(rpc_url, &ProviderConfig::default())
+    }
+
+    pub fn with_config(rpc_url: Url, config: &ProviderConfig) -> Self {
+        Self::with_client(rpc_url, config.build_client())
+    }
+
+    /// Creates a provider sending every request, including the paymaster ones, through `client`.
+    pub fn with_client(rpc_url: Url, client: Client) -> Self {
         Self {
-            inner: JsonRpcClient::new(HttpTransport::new(rpc_url.clone())),
+            inner: JsonRpcClient::new(HttpTransport::new_with_client(
+                rpc_url.clone(),
+                client.clone(),
+            )),
             rpc_url,
+            client,
         }
     }
+
+    pub fn rpc_url(&self) -> &Url {
+        &self.rpc_url
+    }
+
+    pub fn client(&self) -> &Client {
+        &self.client
+    }
 }
 
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
@@ -67,33 +97,53 @@ impl CartridgeProvider for CartridgeJsonRpcProvider {
         address: Felt,
         signature: Vec<Felt>,
     ) -> Result<ExecuteFromOutsideResponse, ExecuteFromOutsideError> {
-        let request = JsonRpcRequest {
-            id: 1,
-            jsonrpc: "2.0",
-            method: "cartridge_addExecuteOutsideTransaction",
-            params: OutsideExecutionParams {
-                address,
-                outside_execution,
-                signature,
-            },
+        let params = OutsideExecutionParams {
+            address,
+            outside_execution,
+            signature,
         };
 
-        let client = Client::new();
-        let response = client
-            .post(self.rpc_url.as_str())
-            .header("Content-Type", "application/json")
-            .json(&request)
-            .send()
-            .await?;
-
-        let json_response: Value = response.json().await?;
-        let json_rpc_response: JsonRpcResponse<ExecuteFromOutsideResponse> =
-            serde_json::from_value(json_response)?;
-
-        match json_rpc_response {
-            JsonRpcResponse::Success { result, .. } => Ok(result),
-            JsonRpcResponse::Error { error, .. } => Err(error.into()),
-        }
+        let response = post_outside_execution(&self.client, &self.rpc_url, &params).await?;
+        parse_outside_execution_response(response)
+    }
+}
+
+/// Sends `cartridge_addExecuteOutsideTransaction` and returns the raw JSON-RPC response.
+pub(crate) async fn post_outside_execution(
+    client: &Client,
+    rpc_url: &Url,
+    params: &OutsideExecutionParams,
+) -> Result<Value, ExecuteFromOutsideError> {
+    let request = JsonRpcRequest {
+        id: 1,
+        jsonrpc: "2.0",
+        method: OUTSIDE_EXECUTION_METHOD,
+        params,
+    };
+
+    let response = client
+        .post(rpc_url.as_str())
+        .header("Content-Type", "application/json")
+        .json(&request)
+        .send()
+        .await?;
+
+    if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+        return Err(ExecuteFromOutsideError::RateLimitExceeded);
+    }
+
+    Ok(response.json().await?)
+}
+
+pub(crate) fn parse_outside_execution_response(
+    response: Value,
+) -> Result<ExecuteFromOutsideResponse, ExecuteFromOutsideError> {
+    let json_rpc_response: JsonRpcResponse<ExecuteFromOutsideResponse> =
+        serde_json::from_value(response)?;
+
+    match json_rpc_response {
+        JsonRpcResponse::Success { result, .. } => Ok(result),
+        JsonRpcResponse::Error { error, .. } => Err(error.into()),
     }
 }
 
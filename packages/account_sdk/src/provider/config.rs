@@ -0,0 +1,43 @@
+use std::time::Duration;
+
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::Client;
+
+/// HTTP settings applied to every request of a
+/// [`CartridgeJsonRpcProvider`](super::CartridgeJsonRpcProvider), including the paymaster ones.
+#[derive(Debug, Clone, Default)]
+pub struct ProviderConfig {
+    /// Headers sent with every request, e.g. an API key.
+    pub headers: HeaderMap,
+    /// Maximum duration of a request, from connecting until the whole response is read. Ignored
+    /// on wasm, where the browser handles timeouts.
+    pub timeout: Option<Duration>,
+}
+
+impl ProviderConfig {
+    pub fn with_header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.headers.insert(name, value);
+        self
+    }
+
+    pub fn with_timeout(self, timeout: Duration) -> Self {
+        Self {
+            timeout: Some(timeout),
+            ..self
+        }
+    }
+
+    /// Builds a client with these settings. The client holds the connection pool, clones of it
+    /// share the same pool.
+    pub fn build_client(&self) -> Client {
+        let builder = Client::builder().default_headers(self.headers.clone());
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let builder = match self.timeout {
+            Some(timeout) => builder.timeout(timeout),
+            None => builder,
+        };
+
+        builder.build().expect("Failed to build HTTP client")
+    }
+}
@@ -0,0 +1,622 @@
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use futures::future::join_all;
+use starknet::core::types::{
+    BlockHashAndNumber, BlockId, BroadcastedDeclareTransaction,
+    BroadcastedDeployAccountTransaction, BroadcastedInvokeTransaction, BroadcastedTransaction,
+    ContractClass, DeclareTransactionResult, DeployAccountTransactionResult, EventFilter,
+    EventsPage, FeeEstimate, Felt, FunctionCall, InvokeTransactionResult,
+    MaybePendingBlockWithReceipts, MaybePendingBlockWithTxHashes, MaybePendingBlockWithTxs,
+    MaybePendingStateUpdate, MsgFromL1, SimulatedTransaction, SimulationFlag,
+    SimulationFlagForEstimateFee, SyncStatusType, Transaction, TransactionReceiptWithBlockInfo,
+    TransactionStatus, TransactionTrace, TransactionTraceWithHash,
+};
+use starknet::providers::{Provider, ProviderError, ProviderRequestData, ProviderResponseData};
+use url::Url;
+
+use crate::account::outside_execution::OutsideExecution;
+
+use super::retry::{batch_idempotency, is_outside_execution_retryable, is_retryable, Idempotency};
+use super::{
+    CartridgeJsonRpcProvider, CartridgeProvider, ExecuteFromOutsideError,
+    ExecuteFromOutsideResponse,
+};
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+#[path = "failover_test.rs"]
+mod failover_test;
+
+/// An endpoint of a [`FailoverProvider`].
+#[derive(Debug, Clone)]
+pub struct Endpoint<P> {
+    pub provider: P,
+    /// Whether the endpoint serves `cartridge_addExecuteOutsideTransaction`.
+    pub paymaster: bool,
+}
+
+impl<P> Endpoint<P> {
+    pub fn new(provider: P, paymaster: bool) -> Self {
+        Self {
+            provider,
+            paymaster,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FailoverConfig {
+    /// Chain the endpoints must serve. Defaults to the chain reported by the first endpoint that
+    /// answers the health check.
+    pub chain_id: Option<Felt>,
+    /// Number of blocks an endpoint can be behind the most advanced one before being considered
+    /// unhealthy.
+    pub max_block_lag: u64,
+}
+
+impl Default for FailoverConfig {
+    fn default() -> Self {
+        Self {
+            chain_id: None,
+            max_block_lag: 5,
+        }
+    }
+}
+
+/// Result of the health check of a single endpoint.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EndpointHealth {
+    pub chain_id: Option<Felt>,
+    pub block_number: Option<u64>,
+    pub healthy: bool,
+}
+
+#[derive(Debug)]
+struct EndpointState<P> {
+    provider: P,
+    healthy: AtomicBool,
+    paymaster: AtomicBool,
+}
+
+/// Sends requests to an ordered list of endpoints, moving on to the next one when an endpoint
+/// fails.
+///
+/// Requests go to the first healthy endpoint. An endpoint failing with a transient error, as
+/// classified by [`is_retryable`], is marked unhealthy and the request is sent to the next one.
+/// Errors returned by the node itself, e.g. a reverted call, are returned as is. Unhealthy
+/// endpoints are only used once every healthy one failed, and become healthy again on the next
+/// successful [`FailoverProvider::health_check`].
+///
+/// `cartridge_addExecuteOutsideTransaction` is only sent to endpoints flagged as supporting it.
+/// An endpoint answering that the method isn't supported loses the flag.
+///
+/// Clones share the endpoints and their state.
+#[derive(Debug)]
+pub struct FailoverProvider<P = CartridgeJsonRpcProvider> {
+    endpoints: Arc<Vec<EndpointState<P>>>,
+    config: FailoverConfig,
+}
+
+impl<P> Clone for FailoverProvider<P> {
+    fn clone(&self) -> Self {
+        Self {
+            endpoints: self.endpoints.clone(),
+            config: self.config.clone(),
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum FailoverError {
+    #[error("FailoverProvider requires at least one endpoint")]
+    NoEndpoints,
+}
+
+impl FailoverProvider<CartridgeJsonRpcProvider> {
+    /// Creates a provider over Cartridge RPC endpoints, which all serve the paymaster method.
+    pub fn from_urls(urls: impl IntoIterator<Item = Url>) -> Result<Self, FailoverError> {
+        Self::new(
+            urls.into_iter()
+                .map(|url| Endpoint::new(CartridgeJsonRpcProvider::new(url), true))
+                .collect(),
+        )
+    }
+}
+
+impl<P> FailoverProvider<P> {
+    pub fn new(endpoints: Vec<Endpoint<P>>) -> Result<Self, FailoverError> {
+        Self::with_config(endpoints, FailoverConfig::default())
+    }
+
+    pub fn with_config(
+        endpoints: Vec<Endpoint<P>>,
+        config: FailoverConfig,
+    ) -> Result<Self, FailoverError> {
+        if endpoints.is_empty() {
+            return Err(FailoverError::NoEndpoints);
+        }
+
+        let endpoints = endpoints
+            .into_iter()
+            .map(|endpoint| EndpointState {
+                provider: endpoint.provider,
+                healthy: AtomicBool::new(true),
+                paymaster: AtomicBool::new(endpoint.paymaster),
+            })
+            .collect();
+
+        Ok(Self {
+            endpoints: Arc::new(endpoints),
+            config,
+        })
+    }
+
+    pub fn config(&self) -> &FailoverConfig {
+        &self.config
+    }
+
+    pub fn provider(&self, index: usize) -> Option<&P> {
+        self.endpoints.get(index).map(|endpoint| &endpoint.provider)
+    }
+
+    pub fn is_healthy(&self, index: usize) -> bool {
+        self.endpoints
+            .get(index)
+            .is_some_and(|endpoint| endpoint.healthy.load(Ordering::Relaxed))
+    }
+
+    pub fn supports_paymaster(&self, index: usize) -> bool {
+        self.endpoints
+            .get(index)
+            .is_some_and(|endpoint| endpoint.paymaster.load(Ordering::Relaxed))
+    }
+
+    /// Returns the indices of the endpoints to try, healthy ones first, in their original order.
+    fn candidates(&self, paymaster_only: bool) -> Vec<usize> {
+        let (healthy, unhealthy): (Vec<_>, Vec<_>) = (0..self.endpoints.len())
+            .filter(|&index| !paymaster_only || self.supports_paymaster(index))
+            .partition(|&index| self.is_healthy(index));
+
+        [healthy, unhealthy].concat()
+    }
+
+    async fn route<'a, T, F, Fut>(
+        &'a self,
+        idempotency: Idempotency,
+        request: F,
+    ) -> Result<T, ProviderError>
+    where
+        F: Fn(&'a P) -> Fut,
+        Fut: Future<Output = Result<T, ProviderError>>,
+    {
+        let mut last_error = None;
+        for index in self.candidates(false) {
+            let endpoint = &self.endpoints[index];
+            match request(&endpoint.provider).await {
+                Err(e) if is_retryable(&e, idempotency) => {
+                    endpoint.healthy.store(false, Ordering::Relaxed);
+                    last_error = Some(e);
+                }
+                result => return result,
+            }
+        }
+
+        Err(last_error.expect("FailoverProvider has at least one endpoint"))
+    }
+
+    async fn read<'a, T, F, Fut>(&'a self, request: F) -> Result<T, ProviderError>
+    where
+        F: Fn(&'a P) -> Fut,
+        Fut: Future<Output = Result<T, ProviderError>>,
+    {
+        self.route(Idempotency::Idempotent, request).await
+    }
+
+    async fn write<'a, T, F, Fut>(&'a self, request: F) -> Result<T, ProviderError>
+    where
+        F: Fn(&'a P) -> Fut,
+        Fut: Future<Output = Result<T, ProviderError>>,
+    {
+        self.route(Idempotency::NonIdempotent, request).await
+    }
+}
+
+impl<P> FailoverProvider<P>
+where
+    P: Provider + Send + Sync,
+{
+    /// Queries the chain id and latest block of every endpoint and updates their health.
+    ///
+    /// An endpoint is healthy when it answers both, serves the expected chain and is at most
+    /// [`FailoverConfig::max_block_lag`] blocks behind the most advanced endpoint.
+    pub async fn health_check(&self) -> Vec<EndpointHealth> {
+        let reports = join_all(self.endpoints.iter().map(|endpoint| async move {
+            let chain_id = endpoint.provider.chain_id().await.ok();
+            let block_number = endpoint.provider.block_number().await.ok();
+            (chain_id, block_number)
+        }))
+        .await;
+
+        let chain_id = self
+            .config
+            .chain_id
+            .or_else(|| reports.iter().find_map(|(chain_id, _)| *chain_id));
+        let highest_block = reports
+            .iter()
+            .filter(|(reported, _)| reported.is_some() && *reported == chain_id)
+            .filter_map(|(_, block_number)| *block_number)
+            .max();
+
+        reports
+            .into_iter()
+            .zip(self.endpoints.iter())
+            .map(|((reported, block_number), endpoint)| {
+                let healthy = reported.is_some()
+                    && reported == chain_id
+                    && matches!(
+                        (block_number, highest_block),
+                        (Some(block), Some(highest)) if highest.saturating_sub(block) <= self.config.max_block_lag
+                    );
+                endpoint.healthy.store(healthy, Ordering::Relaxed);
+
+                EndpointHealth {
+                    chain_id: reported,
+                    block_number,
+                    healthy,
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl<P> CartridgeProvider for FailoverProvider<P>
+where
+    P: CartridgeProvider + Send + Sync,
+{
+    async fn add_execute_outside_transaction(
+        &self,
+        outside_execution: OutsideExecution,
+        address: Felt,
+        signature: Vec<Felt>,
+    ) -> Result<ExecuteFromOutsideResponse, ExecuteFromOutsideError> {
+        let mut last_error = ExecuteFromOutsideError::ExecuteFromOutsideNotSupported;
+        for index in self.candidates(true) {
+            let endpoint = &self.endpoints[index];
+            let result = endpoint
+                .provider
+                .add_execute_outside_transaction(
+                    outside_execution.clone(),
+                    address,
+                    signature.clone(),
+                )
+                .await;
+
+            match result {
+                Err(ExecuteFromOutsideError::ExecuteFromOutsideNotSupported) => {
+                    endpoint.paymaster.store(false, Ordering::Relaxed);
+                }
+                Err(e) if is_outside_execution_retryable(&e) => {
+                    endpoint.healthy.store(false, Ordering::Relaxed);
+                    last_error = e;
+                }
+                result => return result,
+            }
+        }
+
+        Err(last_error)
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl<P> Provider for FailoverProvider<P>
+where
+    P: Provider + Send + Sync,
+{
+    async fn spec_version(&self) -> Result<String, ProviderError> {
+        self.read(|p| p.spec_version()).await
+    }
+
+    async fn get_block_with_tx_hashes<B>(
+        &self,
+        block_id: B,
+    ) -> Result<MaybePendingBlockWithTxHashes, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        self.read(|p| p.get_block_with_tx_hashes(&block_id)).await
+    }
+
+    async fn get_block_with_txs<B>(
+        &self,
+        block_id: B,
+    ) -> Result<MaybePendingBlockWithTxs, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        self.read(|p| p.get_block_with_txs(&block_id)).await
+    }
+
+    async fn get_block_with_receipts<B>(
+        &self,
+        block_id: B,
+    ) -> Result<MaybePendingBlockWithReceipts, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        self.read(|p| p.get_block_with_receipts(&block_id)).await
+    }
+
+    async fn get_state_update<B>(
+        &self,
+        block_id: B,
+    ) -> Result<MaybePendingStateUpdate, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        self.read(|p| p.get_state_update(&block_id)).await
+    }
+
+    async fn get_storage_at<A, K, B>(
+        &self,
+        contract_address: A,
+        key: K,
+        block_id: B,
+    ) -> Result<Felt, ProviderError>
+    where
+        A: AsRef<Felt> + Send + Sync,
+        K: AsRef<Felt> + Send + Sync,
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        self.read(|p| p.get_storage_at(&contract_address, &key, &block_id))
+            .await
+    }
+
+    async fn get_transaction_status<H>(
+        &self,
+        transaction_hash: H,
+    ) -> Result<TransactionStatus, ProviderError>
+    where
+        H: AsRef<Felt> + Send + Sync,
+    {
+        self.read(|p| p.get_transaction_status(&transaction_hash))
+            .await
+    }
+
+    async fn get_transaction_by_hash<H>(
+        &self,
+        transaction_hash: H,
+    ) -> Result<Transaction, ProviderError>
+    where
+        H: AsRef<Felt> + Send + Sync,
+    {
+        self.read(|p| p.get_transaction_by_hash(&transaction_hash))
+            .await
+    }
+
+    async fn get_transaction_by_block_id_and_index<B>(
+        &self,
+        block_id: B,
+        index: u64,
+    ) -> Result<Transaction, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        self.read(|p| p.get_transaction_by_block_id_and_index(&block_id, index))
+            .await
+    }
+
+    async fn get_transaction_receipt<H>(
+        &self,
+        transaction_hash: H,
+    ) -> Result<TransactionReceiptWithBlockInfo, ProviderError>
+    where
+        H: AsRef<Felt> + Send + Sync,
+    {
+        self.read(|p| p.get_transaction_receipt(&transaction_hash))
+            .await
+    }
+
+    async fn get_class<B, H>(
+        &self,
+        block_id: B,
+        class_hash: H,
+    ) -> Result<ContractClass, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+        H: AsRef<Felt> + Send + Sync,
+    {
+        self.read(|p| p.get_class(&block_id, &class_hash)).await
+    }
+
+    async fn get_class_hash_at<B, A>(
+        &self,
+        block_id: B,
+        contract_address: A,
+    ) -> Result<Felt, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+        A: AsRef<Felt> + Send + Sync,
+    {
+        self.read(|p| p.get_class_hash_at(&block_id, &contract_address))
+            .await
+    }
+
+    async fn get_class_at<B, A>(
+        &self,
+        block_id: B,
+        contract_address: A,
+    ) -> Result<ContractClass, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+        A: AsRef<Felt> + Send + Sync,
+    {
+        self.read(|p| p.get_class_at(&block_id, &contract_address))
+            .await
+    }
+
+    async fn get_block_transaction_count<B>(&self, block_id: B) -> Result<u64, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        self.read(|p| p.get_block_transaction_count(&block_id))
+            .await
+    }
+
+    async fn call<R, B>(&self, request: R, block_id: B) -> Result<Vec<Felt>, ProviderError>
+    where
+        R: AsRef<FunctionCall> + Send + Sync,
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        self.read(|p| p.call(&request, &block_id)).await
+    }
+
+    async fn estimate_fee<R, S, B>(
+        &self,
+        request: R,
+        simulation_flags: S,
+        block_id: B,
+    ) -> Result<Vec<FeeEstimate>, ProviderError>
+    where
+        R: AsRef<[BroadcastedTransaction]> + Send + Sync,
+        S: AsRef<[SimulationFlagForEstimateFee]> + Send + Sync,
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        self.read(|p| p.estimate_fee(request.as_ref(), simulation_flags.as_ref(), &block_id))
+            .await
+    }
+
+    async fn estimate_message_fee<M, B>(
+        &self,
+        message: M,
+        block_id: B,
+    ) -> Result<FeeEstimate, ProviderError>
+    where
+        M: AsRef<MsgFromL1> + Send + Sync,
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        self.read(|p| p.estimate_message_fee(&message, &block_id))
+            .await
+    }
+
+    async fn block_number(&self) -> Result<u64, ProviderError> {
+        self.read(|p| p.block_number()).await
+    }
+
+    async fn block_hash_and_number(&self) -> Result<BlockHashAndNumber, ProviderError> {
+        self.read(|p| p.block_hash_and_number()).await
+    }
+
+    async fn chain_id(&self) -> Result<Felt, ProviderError> {
+        self.read(|p| p.chain_id()).await
+    }
+
+    async fn syncing(&self) -> Result<SyncStatusType, ProviderError> {
+        self.read(|p| p.syncing()).await
+    }
+
+    async fn get_events(
+        &self,
+        filter: EventFilter,
+        continuation_token: Option<String>,
+        chunk_size: u64,
+    ) -> Result<EventsPage, ProviderError> {
+        self.read(|p| p.get_events(filter.clone(), continuation_token.clone(), chunk_size))
+            .await
+    }
+
+    async fn get_nonce<B, A>(&self, block_id: B, contract_address: A) -> Result<Felt, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+        A: AsRef<Felt> + Send + Sync,
+    {
+        self.read(|p| p.get_nonce(&block_id, &contract_address))
+            .await
+    }
+
+    async fn add_invoke_transaction<I>(
+        &self,
+        invoke_transaction: I,
+    ) -> Result<InvokeTransactionResult, ProviderError>
+    where
+        I: AsRef<BroadcastedInvokeTransaction> + Send + Sync,
+    {
+        self.write(|p| p.add_invoke_transaction(&invoke_transaction))
+            .await
+    }
+
+    async fn add_declare_transaction<D>(
+        &self,
+        declare_transaction: D,
+    ) -> Result<DeclareTransactionResult, ProviderError>
+    where
+        D: AsRef<BroadcastedDeclareTransaction> + Send + Sync,
+    {
+        self.write(|p| p.add_declare_transaction(&declare_transaction))
+            .await
+    }
+
+    async fn add_deploy_account_transaction<D>(
+        &self,
+        deploy_account_transaction: D,
+    ) -> Result<DeployAccountTransactionResult, ProviderError>
+    where
+        D: AsRef<BroadcastedDeployAccountTransaction> + Send + Sync,
+    {
+        self.write(|p| p.add_deploy_account_transaction(&deploy_account_transaction))
+            .await
+    }
+
+    async fn trace_transaction<H>(
+        &self,
+        transaction_hash: H,
+    ) -> Result<TransactionTrace, ProviderError>
+    where
+        H: AsRef<Felt> + Send + Sync,
+    {
+        self.read(|p| p.trace_transaction(&transaction_hash)).await
+    }
+
+    async fn simulate_transactions<B, T, S>(
+        &self,
+        block_id: B,
+        transactions: T,
+        simulation_flags: S,
+    ) -> Result<Vec<SimulatedTransaction>, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+        T: AsRef<[BroadcastedTransaction]> + Send + Sync,
+        S: AsRef<[SimulationFlag]> + Send + Sync,
+    {
+        self.read(|p| {
+            p.simulate_transactions(&block_id, transactions.as_ref(), simulation_flags.as_ref())
+        })
+        .await
+    }
+
+    async fn trace_block_transactions<B>(
+        &self,
+        block_id: B,
+    ) -> Result<Vec<TransactionTraceWithHash>, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        self.read(|p| p.trace_block_transactions(&block_id)).await
+    }
+
+    async fn batch_requests<R>(
+        &self,
+        requests: R,
+    ) -> Result<Vec<ProviderResponseData>, ProviderError>
+    where
+        R: AsRef<[ProviderRequestData]> + Send + Sync,
+    {
+        let idempotency = batch_idempotency(requests.as_ref());
+        self.route(idempotency, |p| p.batch_requests(requests.as_ref()))
+            .await
+    }
+}
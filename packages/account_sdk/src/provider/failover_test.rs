@@ -0,0 +1,207 @@
+use hyper::StatusCode;
+use serde_json::json;
+use starknet::core::types::{BlockId, BlockTag, Felt, FunctionCall, StarknetError};
+use starknet::macros::felt;
+use starknet::providers::{Provider, ProviderError};
+
+use crate::abigen::controller::OutsideExecutionV3;
+use crate::account::outside_execution::{OutsideExecution, OutsideExecutionCaller};
+use crate::provider::{CartridgeJsonRpcProvider, CartridgeProvider};
+use crate::tests::runners::stub::{error_response, result_response, status_response, StubServer};
+
+use super::{Endpoint, EndpointHealth, FailoverConfig, FailoverError, FailoverProvider};
+
+const SEPOLIA: &str = "0x534e5f5345504f4c4941";
+const MAINNET: &str = "0x534e5f4d41494e";
+
+/// Starts a node serving `chain_id` at `block_number`. Nodes without a paymaster answer the
+/// paymaster method with the `-32003` error.
+fn node(chain_id: &'static str, block_number: u64, paymaster: bool) -> StubServer {
+    StubServer::start(move |_, request| match request["method"].as_str() {
+        Some("starknet_chainId") => result_response(request, json!(chain_id)),
+        Some("starknet_blockNumber") => result_response(request, json!(block_number)),
+        Some("starknet_call") => result_response(request, json!(["0x1"])),
+        Some("cartridge_addExecuteOutsideTransaction") if paymaster => {
+            result_response(request, json!({ "transaction_hash": "0x1" }))
+        }
+        Some("cartridge_addExecuteOutsideTransaction") => {
+            error_response(request, -32003, "Paymaster not supported")
+        }
+        method => panic!("Unexpected method: {:?}", method),
+    })
+}
+
+fn endpoint(server: &StubServer, paymaster: bool) -> Endpoint<CartridgeJsonRpcProvider> {
+    Endpoint::new(CartridgeJsonRpcProvider::new(server.url.clone()), paymaster)
+}
+
+fn function_call() -> FunctionCall {
+    FunctionCall {
+        contract_address: felt!("0x1234"),
+        entry_point_selector: felt!("0x5678"),
+        calldata: vec![],
+    }
+}
+
+fn outside_execution() -> OutsideExecution {
+    OutsideExecution::V3(OutsideExecutionV3 {
+        caller: OutsideExecutionCaller::Any.into(),
+        execute_after: 0,
+        execute_before: u64::MAX,
+        calls: vec![],
+        nonce: (Felt::ONE, 1),
+    })
+}
+
+#[tokio::test]
+async fn test_fails_over_on_transport_error() {
+    let down = StubServer::start(|_, _| status_response(StatusCode::BAD_GATEWAY));
+    let up = node(SEPOLIA, 100, true);
+
+    let provider = FailoverProvider::new(vec![endpoint(&down, true), endpoint(&up, true)]).unwrap();
+
+    let chain_id = provider.chain_id().await.unwrap();
+    assert_eq!(chain_id, Felt::from_hex(SEPOLIA).unwrap());
+    assert!(!provider.is_healthy(0));
+    assert!(provider.is_healthy(1));
+
+    // The failed endpoint is skipped until the next health check
+    provider.chain_id().await.unwrap();
+    assert_eq!(down.requests(), 1);
+    assert_eq!(up.requests(), 2);
+}
+
+#[tokio::test]
+async fn test_does_not_fail_over_on_node_error() {
+    let first = StubServer::start(|_, request| error_response(request, 20, "Contract not found"));
+    let second = node(SEPOLIA, 100, true);
+
+    let provider =
+        FailoverProvider::new(vec![endpoint(&first, true), endpoint(&second, true)]).unwrap();
+
+    let result = provider
+        .call(function_call(), BlockId::Tag(BlockTag::Latest))
+        .await;
+
+    assert!(matches!(
+        result,
+        Err(ProviderError::StarknetError(
+            StarknetError::ContractNotFound
+        ))
+    ));
+    assert!(provider.is_healthy(0));
+    assert_eq!(second.requests(), 0);
+}
+
+#[tokio::test]
+async fn test_health_check_detects_lag_and_wrong_chain() {
+    let synced = node(SEPOLIA, 100, true);
+    let lagging = node(SEPOLIA, 90, true);
+    let wrong_chain = node(MAINNET, 200, true);
+    let down = StubServer::start(|_, _| status_response(StatusCode::BAD_GATEWAY));
+
+    let provider = FailoverProvider::with_config(
+        vec![
+            endpoint(&synced, true),
+            endpoint(&lagging, true),
+            endpoint(&wrong_chain, true),
+            endpoint(&down, true),
+        ],
+        FailoverConfig {
+            chain_id: Some(Felt::from_hex(SEPOLIA).unwrap()),
+            max_block_lag: 5,
+        },
+    )
+    .unwrap();
+
+    let health = provider.health_check().await;
+
+    assert_eq!(
+        health,
+        vec![
+            EndpointHealth {
+                chain_id: Some(Felt::from_hex(SEPOLIA).unwrap()),
+                block_number: Some(100),
+                healthy: true,
+            },
+            EndpointHealth {
+                chain_id: Some(Felt::from_hex(SEPOLIA).unwrap()),
+                block_number: Some(90),
+                healthy: false,
+            },
+            EndpointHealth {
+                chain_id: Some(Felt::from_hex(MAINNET).unwrap()),
+                block_number: Some(200),
+                healthy: false,
+            },
+            EndpointHealth {
+                chain_id: None,
+                block_number: None,
+                healthy: false,
+            },
+        ]
+    );
+
+    // Requests go to the healthy endpoint first, whatever the order
+    let provider =
+        FailoverProvider::new(vec![endpoint(&lagging, true), endpoint(&synced, true)]).unwrap();
+    provider.health_check().await;
+    let before = lagging.requests();
+    provider.block_number().await.unwrap();
+    assert_eq!(lagging.requests(), before);
+}
+
+#[tokio::test]
+async fn test_paymaster_pinned_to_supporting_endpoints() {
+    let rpc_only = node(SEPOLIA, 100, false);
+    let paymaster = node(SEPOLIA, 100, true);
+
+    let provider =
+        FailoverProvider::new(vec![endpoint(&rpc_only, false), endpoint(&paymaster, true)])
+            .unwrap();
+
+    let response = provider
+        .add_execute_outside_transaction(outside_execution(), Felt::ONE, vec![])
+        .await
+        .unwrap();
+
+    assert_eq!(response.transaction_hash, Felt::ONE);
+    assert_eq!(rpc_only.requests(), 0);
+    assert_eq!(paymaster.requests(), 1);
+}
+
+#[tokio::test]
+async fn test_paymaster_flag_dropped_on_unsupported() {
+    let rpc_only = node(SEPOLIA, 100, false);
+    let paymaster = node(SEPOLIA, 100, true);
+
+    // Both endpoints are assumed to serve the paymaster, which the first one doesn't
+    let provider =
+        FailoverProvider::from_urls(vec![rpc_only.url.clone(), paymaster.url.clone()]).unwrap();
+
+    provider
+        .add_execute_outside_transaction(outside_execution(), Felt::ONE, vec![])
+        .await
+        .unwrap();
+    assert!(!provider.supports_paymaster(0));
+    assert!(provider.supports_paymaster(1));
+
+    provider
+        .add_execute_outside_transaction(outside_execution(), Felt::ONE, vec![])
+        .await
+        .unwrap();
+    assert_eq!(rpc_only.requests(), 1);
+    assert_eq!(paymaster.requests(), 2);
+}
+
+#[test]
+fn test_requires_an_endpoint() {
+    assert!(matches!(
+        FailoverProvider::<CartridgeJsonRpcProvider>::new(vec![]),
+        Err(FailoverError::NoEndpoints)
+    ));
+    assert!(matches!(
+        FailoverProvider::from_urls(vec![]),
+        Err(FailoverError::NoEndpoints)
+    ));
+}
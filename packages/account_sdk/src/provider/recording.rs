@@ -0,0 +1,674 @@
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{json, Value};
+use starknet::core::types::{
+    BlockHashAndNumber, BlockId, BroadcastedDeclareTransaction,
+    BroadcastedDeployAccountTransaction, BroadcastedInvokeTransaction, BroadcastedTransaction,
+    ContractClass, DeclareTransactionResult, DeployAccountTransactionResult, EventFilter,
+    EventsPage, FeeEstimate, Felt, FunctionCall, InvokeTransactionResult,
+    MaybePendingBlockWithReceipts, MaybePendingBlockWithTxHashes, MaybePendingBlockWithTxs,
+    MaybePendingStateUpdate, MsgFromL1, SimulatedTransaction, SimulationFlag,
+    SimulationFlagForEstimateFee, SyncStatusType, Transaction, TransactionReceiptWithBlockInfo,
+    TransactionStatus, TransactionTrace, TransactionTraceWithHash,
+};
+use starknet::providers::jsonrpc::{
+    HttpTransport, HttpTransportError, JsonRpcClient, JsonRpcClientError, JsonRpcMethod,
+    JsonRpcResponse, JsonRpcTransport,
+};
+use starknet::providers::{Provider, ProviderError, ProviderRequestData, ProviderResponseData};
+use url::Url;
+
+use crate::account::outside_execution::OutsideExecution;
+
+use super::config::ProviderConfig;
+use super::{
+    parse_outside_execution_response, post_outside_execution, CartridgeProvider,
+    ExecuteFromOutsideError, ExecuteFromOutsideResponse, OutsideExecutionParams,
+    OUTSIDE_EXECUTION_METHOD,
+};
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+#[path = "recording_test.rs"]
+mod recording_test;
+
+/// Method name under which batch requests are recorded, with the array of requests as params.
+const BATCH_METHOD: &str = "batch";
+
+/// A JSON-RPC request and the response it got.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Exchange {
+    pub method: String,
+    pub params: Value,
+    /// The whole JSON-RPC response object, including errors.
+    pub response: Value,
+}
+
+/// Exchanges recorded by a [`RecordingProvider`], in the order they happened.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Fixture {
+    pub exchanges: Vec<Exchange>,
+}
+
+impl Fixture {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, RecordingError> {
+        let content = std::fs::read(path)?;
+        Ok(serde_json::from_slice(&content)?)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), RecordingError> {
+        std::fs::write(path, serde_json::to_vec_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum RecordingError {
+    #[error(transparent)]
+    Http(#[from] HttpTransportError),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("No recorded response for {0}")]
+    NotRecorded(String),
+}
+
+#[derive(Debug, Default)]
+struct Cassette {
+    exchanges: Vec<Exchange>,
+    used: Vec<bool>,
+}
+
+impl Cassette {
+    fn from_fixture(fixture: Fixture) -> Self {
+        Self {
+            used: vec![false; fixture.exchanges.len()],
+            exchanges: fixture.exchanges,
+        }
+    }
+
+    fn fixture(&self) -> Fixture {
+        Fixture {
+            exchanges: self.exchanges.clone(),
+        }
+    }
+
+    fn record(&mut self, method: &str, params: Value, response: Value) {
+        self.exchanges.push(Exchange {
+            method: method.to_string(),
+            params,
+            response,
+        });
+        self.used.push(true);
+    }
+
+    /// Returns the response to the first unused exchange with the same method and params.
+    ///
+    /// Signatures and nonces are freshly generated on each run, so when no exchange matches
+    /// exactly, the first unused one with the same params but for these fields is taken
+    /// instead. Once every matching exchange is used, the last one with the same params is
+    /// replayed again, which lets polling loops run longer than they did while recording.
+    fn replay(&mut self, method: &str, params: &Value) -> Result<Value, RecordingError> {
+        let exchanges = &self.exchanges;
+        let used = &self.used;
+        let same_method = |i: &usize| exchanges[*i].method == method;
+        let same_params = |i: &usize| same_method(i) && exchanges[*i].params == *params;
+        let unsigned_params = without_signatures(params);
+        let same_unsigned_params = |i: &usize| {
+            same_method(i) && without_signatures(&exchanges[*i].params) == unsigned_params
+        };
+
+        let index = (0..exchanges.len())
+            .find(|i| !used[*i] && same_params(i))
+            .or_else(|| (0..exchanges.len()).find(|i| !used[*i] && same_unsigned_params(i)))
+            .or_else(|| (0..exchanges.len()).rev().find(|i| same_params(i)))
+            .ok_or_else(|| RecordingError::NotRecorded(method.to_string()))?;
+
+        self.used[index] = true;
+        Ok(self.exchanges[index].response.clone())
+    }
+}
+
+/// `params` with every `signature` and `nonce` field, at any depth, set to null.
+fn without_signatures(params: &Value) -> Value {
+    match params {
+        Value::Object(fields) => Value::Object(
+            fields
+                .iter()
+                .map(|(key, value)| match key.as_str() {
+                    "signature" | "nonce" => (key.clone(), Value::Null),
+                    _ => (key.clone(), without_signatures(value)),
+                })
+                .collect(),
+        ),
+        Value::Array(values) => Value::Array(values.iter().map(without_signatures).collect()),
+        value => value.clone(),
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Mode {
+    Record {
+        transport: Arc<HttpTransport>,
+        client: Client,
+        rpc_url: Url,
+    },
+    Replay,
+}
+
+/// A JSON-RPC transport that either forwards requests to a node and records them, or answers
+/// them from recorded exchanges.
+#[derive(Debug, Clone)]
+pub struct RecordingTransport {
+    mode: Mode,
+    cassette: Arc<Mutex<Cassette>>,
+}
+
+impl RecordingTransport {
+    fn record(&self, method: &str, params: Value, response: Value) {
+        self.cassette
+            .lock()
+            .unwrap()
+            .record(method, params, response);
+    }
+
+    fn replay(&self, method: &str, params: &Value) -> Result<Value, RecordingError> {
+        self.cassette.lock().unwrap().replay(method, params)
+    }
+}
+
+fn response_to_value(response: JsonRpcResponse<Value>) -> Value {
+    match response {
+        JsonRpcResponse::Success { id, result } => json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "result": result,
+        }),
+        JsonRpcResponse::Error { id, error } => json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": {
+                "code": error.code,
+                "message": error.message,
+                "data": error.data,
+            },
+        }),
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl JsonRpcTransport for RecordingTransport {
+    type Error = RecordingError;
+
+    async fn send_request<P, R>(
+        &self,
+        method: JsonRpcMethod,
+        params: P,
+    ) -> Result<JsonRpcResponse<R>, Self::Error>
+    where
+        P: Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        let name = serde_json::to_value(&method)?
+            .as_str()
+            .unwrap_or_default()
+            .to_string();
+        let params = serde_json::to_value(&params)?;
+
+        let response = match &self.mode {
+            Mode::Record { transport, .. } => {
+                let response = transport.send_request::<_, Value>(method, &params).await?;
+                let response = response_to_value(response);
+                self.record(&name, params, response.clone());
+                response
+            }
+            Mode::Replay => self.replay(&name, &params)?,
+        };
+
+        Ok(serde_json::from_value(response)?)
+    }
+
+    async fn send_requests<R>(
+        &self,
+        requests: R,
+    ) -> Result<Vec<JsonRpcResponse<Value>>, Self::Error>
+    where
+        R: AsRef<[ProviderRequestData]> + Send + Sync,
+    {
+        let params = serde_json::to_value(requests.as_ref())?;
+
+        let response = match &self.mode {
+            Mode::Record { transport, .. } => {
+                let responses = transport.send_requests(requests).await?;
+                let response = Value::Array(responses.into_iter().map(response_to_value).collect());
+                self.record(BATCH_METHOD, params, response.clone());
+                response
+            }
+            Mode::Replay => self.replay(BATCH_METHOD, &params)?,
+        };
+
+        Ok(serde_json::from_value(response)?)
+    }
+}
+
+/// A provider recording every JSON-RPC exchange with a node, including
+/// `cartridge_addExecuteOutsideTransaction`, so that they can be replayed later without it.
+///
+/// Clones share the recorded exchanges.
+///
+/// # Examples
+///
+/// ```ignore
+/// let provider = RecordingProvider::record(rpc_url);
+/// // ... run the flow against a live node
+/// provider.save("fixtures/flow.json")?;
+///
+/// // Later, without a node
+/// let provider = RecordingProvider::replay_file("fixtures/flow.json")?;
+/// ```
+#[derive(Debug)]
+pub struct RecordingProvider {
+    inner: JsonRpcClient<RecordingTransport>,
+    transport: RecordingTransport,
+}
+
+impl Clone for RecordingProvider {
+    fn clone(&self) -> Self {
+        Self::from_transport(self.transport.clone())
+    }
+}
+
+impl RecordingProvider {
+    pub fn record(rpc_url: Url) -> Self {
+        Self::record_with_config(rpc_url, &ProviderConfig::default())
+    }
+
+    pub fn record_with_config(rpc_url: Url, config: &ProviderConfig) -> Self {
+        let client = config.build_client();
+        Self::from_transport(RecordingTransport {
+            mode: Mode::Record {
+                transport: Arc::new(HttpTransport::new_with_client(
+                    rpc_url.clone(),
+                    client.clone(),
+                )),
+                client,
+                rpc_url,
+            },
+            cassette: Default::default(),
+        })
+    }
+
+    pub fn replay(fixture: Fixture) -> Self {
+        Self::from_transport(RecordingTransport {
+            mode: Mode::Replay,
+            cassette: Arc::new(Mutex::new(Cassette::from_fixture(fixture))),
+        })
+    }
+
+    pub fn replay_file(path: impl AsRef<Path>) -> Result<Self, RecordingError> {
+        Ok(Self::replay(Fixture::load(path)?))
+    }
+
+    fn from_transport(transport: RecordingTransport) -> Self {
+        Self {
+            inner: JsonRpcClient::new(transport.clone()),
+            transport,
+        }
+    }
+
+    pub fn is_recording(&self) -> bool {
+        matches!(self.transport.mode, Mode::Record { .. })
+    }
+
+    /// Returns the exchanges recorded so far, or the replayed ones.
+    pub fn fixture(&self) -> Fixture {
+        self.transport.cassette.lock().unwrap().fixture()
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), RecordingError> {
+        self.fixture().save(path)
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl CartridgeProvider for RecordingProvider {
+    async fn add_execute_outside_transaction(
+        &self,
+        outside_execution: OutsideExecution,
+        address: Felt,
+        signature: Vec<Felt>,
+    ) -> Result<ExecuteFromOutsideResponse, ExecuteFromOutsideError> {
+        let params = OutsideExecutionParams {
+            address,
+            outside_execution,
+            signature,
+        };
+        let recorded_params = serde_json::to_value(&params)?;
+
+        let response = match &self.transport.mode {
+            Mode::Record {
+                client, rpc_url, ..
+            } => {
+                let response = post_outside_execution(client, rpc_url, &params).await?;
+                self.transport
+                    .record(OUTSIDE_EXECUTION_METHOD, recorded_params, response.clone());
+                response
+            }
+            Mode::Replay => self
+                .transport
+                .replay(OUTSIDE_EXECUTION_METHOD, &recorded_params)
+                .map_err(|e| ProviderError::from(JsonRpcClientError::TransportError(e)))?,
+        };
+
+        parse_outside_execution_response(response)
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl Provider for RecordingProvider {
+    async fn spec_version(&self) -> Result<String, ProviderError> {
+        self.inner.spec_version().await
+    }
+
+    async fn get_block_with_tx_hashes<B>(
+        &self,
+        block_id: B,
+    ) -> Result<MaybePendingBlockWithTxHashes, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        self.inner.get_block_with_tx_hashes(block_id).await
+    }
+
+    async fn get_block_with_txs<B>(
+        &self,
+        block_id: B,
+    ) -> Result<MaybePendingBlockWithTxs, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        self.inner.get_block_with_txs(block_id).await
+    }
+
+    async fn get_block_with_receipts<B>(
+        &self,
+        block_id: B,
+    ) -> Result<MaybePendingBlockWithReceipts, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        self.inner.get_block_with_receipts(block_id).await
+    }
+
+    async fn get_state_update<B>(
+        &self,
+        block_id: B,
+    ) -> Result<MaybePendingStateUpdate, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        self.inner.get_state_update(block_id).await
+    }
+
+    async fn get_storage_at<A, K, B>(
+        &self,
+        contract_address: A,
+        key: K,
+        block_id: B,
+    ) -> Result<Felt, ProviderError>
+    where
+        A: AsRef<Felt> + Send + Sync,
+        K: AsRef<Felt> + Send + Sync,
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        self.inner
+            .get_storage_at(contract_address, key, block_id)
+            .await
+    }
+
+    async fn get_transaction_status<H>(
+        &self,
+        transaction_hash: H,
+    ) -> Result<TransactionStatus, ProviderError>
+    where
+        H: AsRef<Felt> + Send + Sync,
+    {
+        self.inner.get_transaction_status(transaction_hash).await
+    }
+
+    async fn get_transaction_by_hash<H>(
+        &self,
+        transaction_hash: H,
+    ) -> Result<Transaction, ProviderError>
+    where
+        H: AsRef<Felt> + Send + Sync,
+    {
+        self.inner.get_transaction_by_hash(transaction_hash).await
+    }
+
+    async fn get_transaction_by_block_id_and_index<B>(
+        &self,
+        block_id: B,
+        index: u64,
+    ) -> Result<Transaction, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        self.inner
+            .get_transaction_by_block_id_and_index(block_id, index)
+            .await
+    }
+
+    async fn get_transaction_receipt<H>(
+        &self,
+        transaction_hash: H,
+    ) -> Result<TransactionReceiptWithBlockInfo, ProviderError>
+    where
+        H: AsRef<Felt> + Send + Sync,
+    {
+        self.inner.get_transaction_receipt(transaction_hash).await
+    }
+
+    async fn get_class<B, H>(
+        &self,
+        block_id: B,
+        class_hash: H,
+    ) -> Result<ContractClass, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+        H: AsRef<Felt> + Send + Sync,
+    {
+        self.inner.get_class(block_id, class_hash).await
+    }
+
+    async fn get_class_hash_at<B, A>(
+        &self,
+        block_id: B,
+        contract_address: A,
+    ) -> Result<Felt, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+        A: AsRef<Felt> + Send + Sync,
+    {
+        self.inner
+            .get_class_hash_at(block_id, contract_address)
+            .await
+    }
+
+    async fn get_class_at<B, A>(
+        &self,
+        block_id: B,
+        contract_address: A,
+    ) -> Result<ContractClass, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+        A: AsRef<Felt> + Send + Sync,
+    {
+        self.inner.get_class_at(block_id, contract_address).await
+    }
+
+    async fn get_block_transaction_count<B>(&self, block_id: B) -> Result<u64, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        self.inner.get_block_transaction_count(block_id).await
+    }
+
+    async fn call<R, B>(&self, request: R, block_id: B) -> Result<Vec<Felt>, ProviderError>
+    where
+        R: AsRef<FunctionCall> + Send + Sync,
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        self.inner.call(request, block_id).await
+    }
+
+    async fn estimate_fee<R, S, B>(
+        &self,
+        request: R,
+        simulation_flags: S,
+        block_id: B,
+    ) -> Result<Vec<FeeEstimate>, ProviderError>
+    where
+        R: AsRef<[BroadcastedTransaction]> + Send + Sync,
+        S: AsRef<[SimulationFlagForEstimateFee]> + Send + Sync,
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        self.inner
+            .estimate_fee(request, simulation_flags, block_id)
+            .await
+    }
+
+    async fn estimate_message_fee<M, B>(
+        &self,
+        message: M,
+        block_id: B,
+    ) -> Result<FeeEstimate, ProviderError>
+    where
+        M: AsRef<MsgFromL1> + Send + Sync,
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        self.inner.estimate_message_fee(message, block_id).await
+    }
+
+    async fn block_number(&self) -> Result<u64, ProviderError> {
+        self.inner.block_number().await
+    }
+
+    async fn block_hash_and_number(&self) -> Result<BlockHashAndNumber, ProviderError> {
+        self.inner.block_hash_and_number().await
+    }
+
+    async fn chain_id(&self) -> Result<Felt, ProviderError> {
+        self.inner.chain_id().await
+    }
+
+    async fn syncing(&self) -> Result<SyncStatusType, ProviderError> {
+        self.inner.syncing().await
+    }
+
+    async fn get_events(
+        &self,
+        filter: EventFilter,
+        continuation_token: Option<String>,
+        chunk_size: u64,
+    ) -> Result<EventsPage, ProviderError> {
+        self.inner
+            .get_events(filter, continuation_token, chunk_size)
+            .await
+    }
+
+    async fn get_nonce<B, A>(&self, block_id: B, contract_address: A) -> Result<Felt, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+        A: AsRef<Felt> + Send + Sync,
+    {
+        self.inner.get_nonce(block_id, contract_address).await
+    }
+
+    async fn add_invoke_transaction<I>(
+        &self,
+        invoke_transaction: I,
+    ) -> Result<InvokeTransactionResult, ProviderError>
+    where
+        I: AsRef<BroadcastedInvokeTransaction> + Send + Sync,
+    {
+        self.inner.add_invoke_transaction(invoke_transaction).await
+    }
+
+    async fn add_declare_transaction<D>(
+        &self,
+        declare_transaction: D,
+    ) -> Result<DeclareTransactionResult, ProviderError>
+    where
+        D: AsRef<BroadcastedDeclareTransaction> + Send + Sync,
+    {
+        self.inner
+            .add_declare_transaction(declare_transaction)
+            .await
+    }
+
+    async fn add_deploy_account_transaction<D>(
+        &self,
+        deploy_account_transaction: D,
+    ) -> Result<DeployAccountTransactionResult, ProviderError>
+    where
+        D: AsRef<BroadcastedDeployAccountTransaction> + Send + Sync,
+    {
+        self.inner
+            .add_deploy_account_transaction(deploy_account_transaction)
+            .await
+    }
+
+    async fn trace_transaction<H>(
+        &self,
+        transaction_hash: H,
+    ) -> Result<TransactionTrace, ProviderError>
+    where
+        H: AsRef<Felt> + Send + Sync,
+    {
+        self.inner.trace_transaction(transaction_hash).await
+    }
+
+    async fn simulate_transactions<B, T, S>(
+        &self,
+        block_id: B,
+        transactions: T,
+        simulation_flags: S,
+    ) -> Result<Vec<SimulatedTransaction>, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+        T: AsRef<[BroadcastedTransaction]> + Send + Sync,
+        S: AsRef<[SimulationFlag]> + Send + Sync,
+    {
+        self.inner
+            .simulate_transactions(block_id, transactions, simulation_flags)
+            .await
+    }
+
+    async fn trace_block_transactions<B>(
+        &self,
+        block_id: B,
+    ) -> Result<Vec<TransactionTraceWithHash>, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        self.inner.trace_block_transactions(block_id).await
+    }
+
+    async fn batch_requests<R>(
+        &self,
+        requests: R,
+    ) -> Result<Vec<ProviderResponseData>, ProviderError>
+    where
+        R: AsRef<[ProviderRequestData]> + Send + Sync,
+    {
+        self.inner.batch_requests(requests).await
+    }
+}
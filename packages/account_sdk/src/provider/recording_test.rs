@@ -0,0 +1,117 @@
+use serde_json::json;
+use starknet::core::types::{BlockId, BlockTag, Felt};
+use starknet::macros::felt;
+use starknet::providers::Provider;
+
+use crate::abigen::controller::OutsideExecutionV3;
+use crate::account::outside_execution::{OutsideExecution, OutsideExecutionCaller};
+use crate::provider::CartridgeProvider;
+use crate::tests::runners::stub::{result_response, StubServer};
+
+use super::{Exchange, Fixture, RecordingProvider};
+
+fn outside_execution(nonce: u128) -> OutsideExecution {
+    OutsideExecution::V3(OutsideExecutionV3 {
+        caller: OutsideExecutionCaller::Any.into(),
+        execute_after: 0,
+        execute_before: u64::MAX,
+        calls: vec![],
+        nonce: (Felt::ONE, nonce),
+    })
+}
+
+#[tokio::test]
+async fn test_record_and_replay() {
+    let server = StubServer::start(|index, request| match request["method"].as_str() {
+        Some("starknet_chainId") => result_response(request, json!("0x534e5f5345504f4c4941")),
+        Some("starknet_blockNumber") => result_response(request, json!(index)),
+        Some("cartridge_addExecuteOutsideTransaction") => {
+            result_response(request, json!({ "transaction_hash": "0xabc" }))
+        }
+        method => panic!("Unexpected method: {:?}", method),
+    });
+
+    let provider = RecordingProvider::record(server.url.clone());
+    let chain_id = provider.chain_id().await.unwrap();
+    let first_block = provider.block_number().await.unwrap();
+    let second_block = provider.block_number().await.unwrap();
+    let response = provider
+        .add_execute_outside_transaction(outside_execution(1), Felt::ONE, vec![])
+        .await
+        .unwrap();
+    assert_eq!(server.requests(), 4);
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("fixture.json");
+    provider.save(&path).unwrap();
+    drop(server);
+
+    let fixture = Fixture::load(&path).unwrap();
+    assert_eq!(
+        fixture
+            .exchanges
+            .iter()
+            .map(|exchange| exchange.method.as_str())
+            .collect::<Vec<_>>(),
+        vec![
+            "starknet_chainId",
+            "starknet_blockNumber",
+            "starknet_blockNumber",
+            "cartridge_addExecuteOutsideTransaction",
+        ]
+    );
+
+    let replay = RecordingProvider::replay_file(&path).unwrap();
+    assert!(!replay.is_recording());
+    assert_eq!(replay.chain_id().await.unwrap(), chain_id);
+    assert_eq!(replay.block_number().await.unwrap(), first_block);
+    assert_eq!(replay.block_number().await.unwrap(), second_block);
+    // Polling past the recording keeps answering with the last response
+    assert_eq!(replay.block_number().await.unwrap(), second_block);
+
+    // The outside execution is signed with a fresh nonce, it still matches but for it
+    let replayed = replay
+        .add_execute_outside_transaction(outside_execution(2), Felt::ONE, vec![])
+        .await
+        .unwrap();
+    assert_eq!(replayed.transaction_hash, response.transaction_hash);
+}
+
+#[tokio::test]
+async fn test_replay_prefers_exact_params() {
+    let nonce_response = |nonce: &str| json!({ "jsonrpc": "2.0", "id": 1, "result": nonce });
+    let params = |address: Felt| {
+        json!({
+            "block_id": "latest",
+            "contract_address": format!("{:#x}", address),
+        })
+    };
+
+    let provider = RecordingProvider::replay(Fixture {
+        exchanges: vec![
+            Exchange {
+                method: "starknet_getNonce".to_string(),
+                params: params(felt!("0x1")),
+                response: nonce_response("0x1"),
+            },
+            Exchange {
+                method: "starknet_getNonce".to_string(),
+                params: params(felt!("0x2")),
+                response: nonce_response("0x2"),
+            },
+        ],
+    });
+
+    let block_id = BlockId::Tag(BlockTag::Latest);
+    assert_eq!(
+        provider.get_nonce(block_id, felt!("0x2")).await.unwrap(),
+        felt!("0x2")
+    );
+    // Another address never gets a response recorded for a different one
+    assert!(provider.get_nonce(block_id, felt!("0x3")).await.is_err());
+    assert_eq!(
+        provider.get_nonce(block_id, felt!("0x1")).await.unwrap(),
+        felt!("0x1")
+    );
+    assert!(provider.chain_id().await.is_err());
+}
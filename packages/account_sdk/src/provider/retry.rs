@@ -0,0 +1,582 @@
+use std::future::Future;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use rand::Rng;
+use starknet::core::types::{
+    BlockHashAndNumber, BlockId, BroadcastedDeclareTransaction,
+    BroadcastedDeployAccountTransaction, BroadcastedInvokeTransaction, BroadcastedTransaction,
+    ContractClass, DeclareTransactionResult, DeployAccountTransactionResult, EventFilter,
+    EventsPage, FeeEstimate, Felt, FunctionCall, InvokeTransactionResult,
+    MaybePendingBlockWithReceipts, MaybePendingBlockWithTxHashes, MaybePendingBlockWithTxs,
+    MaybePendingStateUpdate, MsgFromL1, SimulatedTransaction, SimulationFlag,
+    SimulationFlagForEstimateFee, SyncStatusType, Transaction, TransactionReceiptWithBlockInfo,
+    TransactionStatus, TransactionTrace, TransactionTraceWithHash,
+};
+use starknet::providers::jsonrpc::{HttpTransportError, JsonRpcClientError};
+use starknet::providers::{Provider, ProviderError, ProviderRequestData, ProviderResponseData};
+
+use crate::account::outside_execution::OutsideExecution;
+use crate::utils::time::sleep;
+
+use super::{CartridgeProvider, ExecuteFromOutsideError, ExecuteFromOutsideResponse};
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+#[path = "retry_test.rs"]
+mod retry_test;
+
+/// JSON-RPC error code used by Cartridge nodes when a client exceeds its rate limit.
+pub const RATE_LIMIT_ERROR_CODE: i64 = -32005;
+
+#[derive(Debug, Clone)]
+pub struct RetryConfig {
+    /// Maximum number of retries after the first attempt.
+    pub max_retries: u32,
+    /// Delay before the first retry.
+    pub initial_backoff: Duration,
+    /// Upper bound of the delay between two attempts.
+    pub max_backoff: Duration,
+    /// Factor applied to the delay after every failed attempt.
+    pub multiplier: u32,
+    /// Randomize each delay between half and all of its value, so that clients failing at the
+    /// same time don't retry in lockstep.
+    pub jitter: bool,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(200),
+            max_backoff: Duration::from_secs(5),
+            multiplier: 2,
+            jitter: true,
+        }
+    }
+}
+
+impl RetryConfig {
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let delay = self
+            .initial_backoff
+            .saturating_mul(self.multiplier.saturating_pow(attempt))
+            .min(self.max_backoff);
+
+        if self.jitter {
+            let millis = delay.as_millis() as u64;
+            Duration::from_millis(rand::thread_rng().gen_range(millis / 2..=millis))
+        } else {
+            delay
+        }
+    }
+}
+
+/// Whether sending a request twice can change the outcome.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Idempotency {
+    /// Reads can be retried on any transient failure.
+    Idempotent,
+    /// Transaction submissions are only retried when the request was surely rejected before the
+    /// node processed it, e.g. rate limiting or a failed connection. A timeout could mean the
+    /// transaction was accepted, so it is never retried.
+    NonIdempotent,
+}
+
+/// Returns whether `error` is transient and the request can be sent again.
+pub fn is_retryable(error: &ProviderError, idempotency: Idempotency) -> bool {
+    match error {
+        ProviderError::RateLimited => true,
+        ProviderError::Other(inner) => {
+            // The paymaster method sends its requests itself and wraps the `reqwest` error directly
+            if let Some(JsonRpcClientError::TransportError(error)) =
+                inner
+                    .as_any()
+                    .downcast_ref::<JsonRpcClientError<reqwest::Error>>()
+            {
+                return error.status() == Some(reqwest::StatusCode::TOO_MANY_REQUESTS)
+                    || error.is_connect()
+                    || idempotency == Idempotency::Idempotent;
+            }
+
+            let Some(error) = inner
+                .as_any()
+                .downcast_ref::<JsonRpcClientError<HttpTransportError>>()
+            else {
+                return false;
+            };
+
+            match error {
+                JsonRpcClientError::JsonRpcError(error) => error.code == RATE_LIMIT_ERROR_CODE,
+                JsonRpcClientError::TransportError(HttpTransportError::Reqwest(error)) => {
+                    error.status() == Some(reqwest::StatusCode::TOO_MANY_REQUESTS)
+                        || error.is_connect()
+                        || idempotency == Idempotency::Idempotent
+                }
+                // A body that isn't JSON usually comes from a gateway answering with a 429 or 5xx
+                // page, there is no way to tell whether the node saw the request.
+                JsonRpcClientError::TransportError(HttpTransportError::Json(_))
+                | JsonRpcClientError::JsonError(_) => idempotency == Idempotency::Idempotent,
+                _ => false,
+            }
+        }
+        _ => false,
+    }
+}
+
+pub(crate) fn is_outside_execution_retryable(error: &ExecuteFromOutsideError) -> bool {
+    match error {
+        ExecuteFromOutsideError::RateLimitExceeded => true,
+        ExecuteFromOutsideError::ProviderError(error) => {
+            is_retryable(error, Idempotency::NonIdempotent)
+        }
+        _ => false,
+    }
+}
+
+pub(crate) fn batch_idempotency(requests: &[ProviderRequestData]) -> Idempotency {
+    let has_write = requests.iter().any(|request| {
+        matches!(
+            request,
+            ProviderRequestData::AddInvokeTransaction(_)
+                | ProviderRequestData::AddDeclareTransaction(_)
+                | ProviderRequestData::AddDeployAccountTransaction(_)
+        )
+    });
+
+    if has_write {
+        Idempotency::NonIdempotent
+    } else {
+        Idempotency::Idempotent
+    }
+}
+
+/// Wraps a provider and retries failed requests with exponential backoff.
+///
+/// Transport failures, HTTP 429 responses and the `-32005` rate-limit code are considered
+/// transient. Transaction submissions follow the stricter [`Idempotency::NonIdempotent`] rules.
+///
+/// `HttpTransport` doesn't expose the status of responses without a JSON body, so on the
+/// `starknet_*` methods a 429 page is handled like any other gateway failure. The paymaster
+/// method checks the status itself and always retries on 429.
+#[derive(Debug, Clone)]
+pub struct RetryProvider<P> {
+    inner: P,
+    config: RetryConfig,
+}
+
+impl<P> RetryProvider<P> {
+    pub fn new(inner: P) -> Self {
+        Self::with_config(inner, RetryConfig::default())
+    }
+
+    pub fn with_config(inner: P, config: RetryConfig) -> Self {
+        Self { inner, config }
+    }
+
+    pub fn inner(&self) -> &P {
+        &self.inner
+    }
+
+    pub fn config(&self) -> &RetryConfig {
+        &self.config
+    }
+
+    async fn retry<T, E, F, Fut>(
+        &self,
+        is_retryable: impl Fn(&E) -> bool,
+        request: F,
+    ) -> Result<T, E>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        let mut attempt = 0;
+        loop {
+            match request().await {
+                Err(e) if attempt < self.config.max_retries && is_retryable(&e) => {
+                    sleep(self.config.backoff(attempt)).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    async fn read<T, F, Fut>(&self, request: F) -> Result<T, ProviderError>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<T, ProviderError>>,
+    {
+        self.retry(|e| is_retryable(e, Idempotency::Idempotent), request)
+            .await
+    }
+
+    async fn write<T, F, Fut>(&self, request: F) -> Result<T, ProviderError>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<T, ProviderError>>,
+    {
+        self.retry(|e| is_retryable(e, Idempotency::NonIdempotent), request)
+            .await
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl<P> CartridgeProvider for RetryProvider<P>
+where
+    P: CartridgeProvider + Send + Sync,
+{
+    async fn add_execute_outside_transaction(
+        &self,
+        outside_execution: OutsideExecution,
+        address: Felt,
+        signature: Vec<Felt>,
+    ) -> Result<ExecuteFromOutsideResponse, ExecuteFromOutsideError> {
+        self.retry(is_outside_execution_retryable, || {
+            self.inner.add_execute_outside_transaction(
+                outside_execution.clone(),
+                address,
+                signature.clone(),
+            )
+        })
+        .await
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl<P> Provider for RetryProvider<P>
+where
+    P: Provider + Send + Sync,
+{
+    async fn spec_version(&self) -> Result<String, ProviderError> {
+        self.read(|| self.inner.spec_version()).await
+    }
+
+    async fn get_block_with_tx_hashes<B>(
+        &self,
+        block_id: B,
+    ) -> Result<MaybePendingBlockWithTxHashes, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        self.read(|| self.inner.get_block_with_tx_hashes(&block_id))
+            .await
+    }
+
+    async fn get_block_with_txs<B>(
+        &self,
+        block_id: B,
+    ) -> Result<MaybePendingBlockWithTxs, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        self.read(|| self.inner.get_block_with_txs(&block_id)).await
+    }
+
+    async fn get_block_with_receipts<B>(
+        &self,
+        block_id: B,
+    ) -> Result<MaybePendingBlockWithReceipts, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        self.read(|| self.inner.get_block_with_receipts(&block_id))
+            .await
+    }
+
+    async fn get_state_update<B>(
+        &self,
+        block_id: B,
+    ) -> Result<MaybePendingStateUpdate, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        self.read(|| self.inner.get_state_update(&block_id)).await
+    }
+
+    async fn get_storage_at<A, K, B>(
+        &self,
+        contract_address: A,
+        key: K,
+        block_id: B,
+    ) -> Result<Felt, ProviderError>
+    where
+        A: AsRef<Felt> + Send + Sync,
+        K: AsRef<Felt> + Send + Sync,
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        self.read(|| {
+            self.inner
+                .get_storage_at(&contract_address, &key, &block_id)
+        })
+        .await
+    }
+
+    async fn get_transaction_status<H>(
+        &self,
+        transaction_hash: H,
+    ) -> Result<TransactionStatus, ProviderError>
+    where
+        H: AsRef<Felt> + Send + Sync,
+    {
+        self.read(|| self.inner.get_transaction_status(&transaction_hash))
+            .await
+    }
+
+    async fn get_transaction_by_hash<H>(
+        &self,
+        transaction_hash: H,
+    ) -> Result<Transaction, ProviderError>
+    where
+        H: AsRef<Felt> + Send + Sync,
+    {
+        self.read(|| self.inner.get_transaction_by_hash(&transaction_hash))
+            .await
+    }
+
+    async fn get_transaction_by_block_id_and_index<B>(
+        &self,
+        block_id: B,
+        index: u64,
+    ) -> Result<Transaction, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        self.read(|| {
+            self.inner
+                .get_transaction_by_block_id_and_index(&block_id, index)
+        })
+        .await
+    }
+
+    async fn get_transaction_receipt<H>(
+        &self,
+        transaction_hash: H,
+    ) -> Result<TransactionReceiptWithBlockInfo, ProviderError>
+    where
+        H: AsRef<Felt> + Send + Sync,
+    {
+        self.read(|| self.inner.get_transaction_receipt(&transaction_hash))
+            .await
+    }
+
+    async fn get_class<B, H>(
+        &self,
+        block_id: B,
+        class_hash: H,
+    ) -> Result<ContractClass, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+        H: AsRef<Felt> + Send + Sync,
+    {
+        self.read(|| self.inner.get_class(&block_id, &class_hash))
+            .await
+    }
+
+    async fn get_class_hash_at<B, A>(
+        &self,
+        block_id: B,
+        contract_address: A,
+    ) -> Result<Felt, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+        A: AsRef<Felt> + Send + Sync,
+    {
+        self.read(|| self.inner.get_class_hash_at(&block_id, &contract_address))
+            .await
+    }
+
+    async fn get_class_at<B, A>(
+        &self,
+        block_id: B,
+        contract_address: A,
+    ) -> Result<ContractClass, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+        A: AsRef<Felt> + Send + Sync,
+    {
+        self.read(|| self.inner.get_class_at(&block_id, &contract_address))
+            .await
+    }
+
+    async fn get_block_transaction_count<B>(&self, block_id: B) -> Result<u64, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        self.read(|| self.inner.get_block_transaction_count(&block_id))
+            .await
+    }
+
+    async fn call<R, B>(&self, request: R, block_id: B) -> Result<Vec<Felt>, ProviderError>
+    where
+        R: AsRef<FunctionCall> + Send + Sync,
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        self.read(|| self.inner.call(&request, &block_id)).await
+    }
+
+    async fn estimate_fee<R, S, B>(
+        &self,
+        request: R,
+        simulation_flags: S,
+        block_id: B,
+    ) -> Result<Vec<FeeEstimate>, ProviderError>
+    where
+        R: AsRef<[BroadcastedTransaction]> + Send + Sync,
+        S: AsRef<[SimulationFlagForEstimateFee]> + Send + Sync,
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        self.read(|| {
+            self.inner
+                .estimate_fee(request.as_ref(), simulation_flags.as_ref(), &block_id)
+        })
+        .await
+    }
+
+    async fn estimate_message_fee<M, B>(
+        &self,
+        message: M,
+        block_id: B,
+    ) -> Result<FeeEstimate, ProviderError>
+    where
+        M: AsRef<MsgFromL1> + Send + Sync,
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        self.read(|| self.inner.estimate_message_fee(&message, &block_id))
+            .await
+    }
+
+    async fn block_number(&self) -> Result<u64, ProviderError> {
+        self.read(|| self.inner.block_number()).await
+    }
+
+    async fn block_hash_and_number(&self) -> Result<BlockHashAndNumber, ProviderError> {
+        self.read(|| self.inner.block_hash_and_number()).await
+    }
+
+    async fn chain_id(&self) -> Result<Felt, ProviderError> {
+        self.read(|| self.inner.chain_id()).await
+    }
+
+    async fn syncing(&self) -> Result<SyncStatusType, ProviderError> {
+        self.read(|| self.inner.syncing()).await
+    }
+
+    async fn get_events(
+        &self,
+        filter: EventFilter,
+        continuation_token: Option<String>,
+        chunk_size: u64,
+    ) -> Result<EventsPage, ProviderError> {
+        self.read(|| {
+            self.inner
+                .get_events(filter.clone(), continuation_token.clone(), chunk_size)
+        })
+        .await
+    }
+
+    async fn get_nonce<B, A>(&self, block_id: B, contract_address: A) -> Result<Felt, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+        A: AsRef<Felt> + Send + Sync,
+    {
+        self.read(|| self.inner.get_nonce(&block_id, &contract_address))
+            .await
+    }
+
+    async fn add_invoke_transaction<I>(
+        &self,
+        invoke_transaction: I,
+    ) -> Result<InvokeTransactionResult, ProviderError>
+    where
+        I: AsRef<BroadcastedInvokeTransaction> + Send + Sync,
+    {
+        self.write(|| self.inner.add_invoke_transaction(&invoke_transaction))
+            .await
+    }
+
+    async fn add_declare_transaction<D>(
+        &self,
+        declare_transaction: D,
+    ) -> Result<DeclareTransactionResult, ProviderError>
+    where
+        D: AsRef<BroadcastedDeclareTransaction> + Send + Sync,
+    {
+        self.write(|| self.inner.add_declare_transaction(&declare_transaction))
+            .await
+    }
+
+    async fn add_deploy_account_transaction<D>(
+        &self,
+        deploy_account_transaction: D,
+    ) -> Result<DeployAccountTransactionResult, ProviderError>
+    where
+        D: AsRef<BroadcastedDeployAccountTransaction> + Send + Sync,
+    {
+        self.write(|| {
+            self.inner
+                .add_deploy_account_transaction(&deploy_account_transaction)
+        })
+        .await
+    }
+
+    async fn trace_transaction<H>(
+        &self,
+        transaction_hash: H,
+    ) -> Result<TransactionTrace, ProviderError>
+    where
+        H: AsRef<Felt> + Send + Sync,
+    {
+        self.read(|| self.inner.trace_transaction(&transaction_hash))
+            .await
+    }
+
+    async fn simulate_transactions<B, T, S>(
+        &self,
+        block_id: B,
+        transactions: T,
+        simulation_flags: S,
+    ) -> Result<Vec<SimulatedTransaction>, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+        T: AsRef<[BroadcastedTransaction]> + Send + Sync,
+        S: AsRef<[SimulationFlag]> + Send + Sync,
+    {
+        self.read(|| {
+            self.inner.simulate_transactions(
+                &block_id,
+                transactions.as_ref(),
+                simulation_flags.as_ref(),
+            )
+        })
+        .await
+    }
+
+    async fn trace_block_transactions<B>(
+        &self,
+        block_id: B,
+    ) -> Result<Vec<TransactionTraceWithHash>, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        self.read(|| self.inner.trace_block_transactions(&block_id))
+            .await
+    }
+
+    async fn batch_requests<R>(
+        &self,
+        requests: R,
+    ) -> Result<Vec<ProviderResponseData>, ProviderError>
+    where
+        R: AsRef<[ProviderRequestData]> + Send + Sync,
+    {
+        let idempotency = batch_idempotency(requests.as_ref());
+        self.retry(
+            |e| is_retryable(e, idempotency),
+            || self.inner.batch_requests(requests.as_ref()),
+        )
+        .await
+    }
+}
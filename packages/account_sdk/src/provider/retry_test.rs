@@ -0,0 +1,162 @@
+use std::time::Duration;
+
+use hyper::StatusCode;
+use serde_json::json;
+use starknet::core::types::{BroadcastedInvokeTransaction, BroadcastedInvokeTransactionV1, Felt};
+use starknet::providers::Provider;
+
+use crate::abigen::controller::OutsideExecutionV3;
+use crate::account::outside_execution::{OutsideExecution, OutsideExecutionCaller};
+use crate::provider::{CartridgeJsonRpcProvider, CartridgeProvider, ExecuteFromOutsideError};
+use crate::tests::runners::stub::{error_response, result_response, status_response, StubServer};
+
+use super::{RetryConfig, RetryProvider};
+
+#[derive(Debug, Clone, Copy)]
+enum Failure {
+    /// Answers with an HTTP 429 and a plain text body, like most gateways do.
+    TooManyRequests,
+    /// Answers with an HTTP 502 and a plain text body.
+    BadGateway,
+    /// Answers with the `-32005` JSON-RPC rate-limit error.
+    RateLimitCode,
+}
+
+/// Starts a server failing the first requests with `failures` before answering normally.
+fn flaky_server(failures: Vec<Failure>) -> StubServer {
+    StubServer::start(move |index, request| match failures.get(index) {
+        Some(Failure::TooManyRequests) => status_response(StatusCode::TOO_MANY_REQUESTS),
+        Some(Failure::BadGateway) => status_response(StatusCode::BAD_GATEWAY),
+        Some(Failure::RateLimitCode) => error_response(request, -32005, "rate limit exceeded"),
+        None => match request["method"].as_str() {
+            Some("starknet_chainId") => result_response(request, json!("0x534e5f5345504f4c4941")),
+            Some("starknet_addInvokeTransaction")
+            | Some("cartridge_addExecuteOutsideTransaction") => {
+                result_response(request, json!({ "transaction_hash": "0x1" }))
+            }
+            method => panic!("Unexpected method: {:?}", method),
+        },
+    })
+}
+
+fn provider(server: &StubServer, max_retries: u32) -> RetryProvider<CartridgeJsonRpcProvider> {
+    RetryProvider::with_config(
+        CartridgeJsonRpcProvider::new(server.url.clone()),
+        RetryConfig {
+            max_retries,
+            initial_backoff: Duration::from_millis(1),
+            jitter: false,
+            ..Default::default()
+        },
+    )
+}
+
+fn invoke_transaction() -> BroadcastedInvokeTransaction {
+    BroadcastedInvokeTransaction::V1(BroadcastedInvokeTransactionV1 {
+        sender_address: Felt::ONE,
+        calldata: vec![],
+        max_fee: Felt::ZERO,
+        signature: vec![],
+        nonce: Felt::ZERO,
+        is_query: false,
+    })
+}
+
+#[test]
+fn test_backoff_is_exponential_and_capped() {
+    let config = RetryConfig {
+        initial_backoff: Duration::from_millis(100),
+        max_backoff: Duration::from_millis(1000),
+        jitter: false,
+        ..Default::default()
+    };
+
+    assert_eq!(config.backoff(0), Duration::from_millis(100));
+    assert_eq!(config.backoff(1), Duration::from_millis(200));
+    assert_eq!(config.backoff(3), Duration::from_millis(800));
+    assert_eq!(config.backoff(4), Duration::from_millis(1000));
+
+    let config = RetryConfig {
+        jitter: true,
+        ..config
+    };
+    for attempt in 0..5 {
+        let backoff = config.backoff(attempt);
+        assert!(backoff <= Duration::from_millis(1000));
+        assert!(backoff >= Duration::from_millis(50));
+    }
+}
+
+#[tokio::test]
+async fn test_retries_reads_on_too_many_requests() {
+    let server = flaky_server(vec![Failure::TooManyRequests, Failure::TooManyRequests]);
+
+    let chain_id = provider(&server, 3).chain_id().await.unwrap();
+
+    assert_eq!(chain_id, Felt::from_hex("0x534e5f5345504f4c4941").unwrap());
+    assert_eq!(server.requests(), 3);
+}
+
+#[tokio::test]
+async fn test_gives_up_after_max_retries() {
+    let server = flaky_server(vec![Failure::RateLimitCode; 5]);
+
+    assert!(provider(&server, 2).chain_id().await.is_err());
+    assert_eq!(server.requests(), 3);
+}
+
+#[tokio::test]
+async fn test_retries_invoke_on_rate_limit_code() {
+    let server = flaky_server(vec![Failure::RateLimitCode]);
+
+    let result = provider(&server, 3)
+        .add_invoke_transaction(invoke_transaction())
+        .await
+        .unwrap();
+
+    assert_eq!(result.transaction_hash, Felt::ONE);
+    assert_eq!(server.requests(), 2);
+}
+
+#[tokio::test]
+async fn test_does_not_retry_invoke_on_ambiguous_failure() {
+    // The gateway page doesn't tell whether the node received the transaction
+    let server = flaky_server(vec![Failure::BadGateway]);
+
+    let result = provider(&server, 3)
+        .add_invoke_transaction(invoke_transaction())
+        .await;
+
+    assert!(result.is_err());
+    assert_eq!(server.requests(), 1);
+}
+
+#[tokio::test]
+async fn test_retries_outside_execution_on_rate_limit() {
+    let server = flaky_server(vec![Failure::TooManyRequests, Failure::RateLimitCode]);
+
+    let outside_execution = OutsideExecution::V3(OutsideExecutionV3 {
+        caller: OutsideExecutionCaller::Any.into(),
+        execute_after: 0,
+        execute_before: u64::MAX,
+        calls: vec![],
+        nonce: (Felt::ONE, 1),
+    });
+
+    let response = provider(&server, 3)
+        .add_execute_outside_transaction(outside_execution.clone(), Felt::ONE, vec![])
+        .await
+        .unwrap();
+    assert_eq!(response.transaction_hash, Felt::ONE);
+    assert_eq!(server.requests(), 3);
+
+    let server = flaky_server(vec![Failure::RateLimitCode; 3]);
+    let result = provider(&server, 1)
+        .add_execute_outside_transaction(outside_execution, Felt::ONE, vec![])
+        .await;
+    assert!(matches!(
+        result,
+        Err(ExecuteFromOutsideError::RateLimitExceeded)
+    ));
+    assert_eq!(server.requests(), 2);
+}
@@ -0,0 +1,795 @@
+use std::any::Any;
+use std::fmt;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use starknet::core::types::{
+    BlockHashAndNumber, BlockId, BroadcastedDeclareTransaction,
+    BroadcastedDeployAccountTransaction, BroadcastedInvokeTransaction, BroadcastedTransaction,
+    ContractClass, DeclareTransactionResult, DeployAccountTransactionResult, EventFilter,
+    EventsPage, FeeEstimate, Felt, FunctionCall, InvokeTransactionResult,
+    MaybePendingBlockWithReceipts, MaybePendingBlockWithTxHashes, MaybePendingBlockWithTxs,
+    MaybePendingStateUpdate, MsgFromL1, SimulatedTransaction, SimulationFlag,
+    SimulationFlagForEstimateFee, SyncStatusType, Transaction, TransactionReceiptWithBlockInfo,
+    TransactionStatus, TransactionTrace, TransactionTraceWithHash,
+};
+use starknet::providers::{Provider, ProviderError, ProviderRequestData, ProviderResponseData};
+
+use crate::account::outside_execution::OutsideExecution;
+
+use super::{CartridgeProvider, ExecuteFromOutsideError, ExecuteFromOutsideResponse};
+
+/// A cheaply clonable handle to any [`CartridgeProvider`].
+///
+/// [`CartridgeProvider`] is generic over its arguments and can't be made into a trait object, so
+/// the Controller holds its provider through this type instead. Clones share the same provider,
+/// which lets many Controllers run on a single connection pool, retry policy or failover set.
+#[derive(Clone)]
+pub struct SharedProvider {
+    inner: Arc<dyn DynCartridgeProvider>,
+}
+
+impl SharedProvider {
+    /// Wraps `provider`. Wrapping a [`SharedProvider`] returns a clone of it rather than
+    /// nesting a second handle.
+    pub fn new<P>(provider: P) -> Self
+    where
+        P: CartridgeProvider + Send + Sync + 'static,
+    {
+        if let Some(shared) = (&provider as &dyn Any).downcast_ref::<SharedProvider>() {
+            return shared.clone();
+        }
+
+        Self {
+            inner: Arc::new(provider),
+        }
+    }
+}
+
+impl fmt::Debug for SharedProvider {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SharedProvider").finish_non_exhaustive()
+    }
+}
+
+/// Object safe mirror of [`CartridgeProvider`], with every generic argument made concrete.
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+trait DynCartridgeProvider: Send + Sync {
+    async fn add_execute_outside_transaction(
+        &self,
+        outside_execution: OutsideExecution,
+        address: Felt,
+        signature: Vec<Felt>,
+    ) -> Result<ExecuteFromOutsideResponse, ExecuteFromOutsideError>;
+
+    async fn spec_version(&self) -> Result<String, ProviderError>;
+
+    async fn get_block_with_tx_hashes(
+        &self,
+        block_id: BlockId,
+    ) -> Result<MaybePendingBlockWithTxHashes, ProviderError>;
+
+    async fn get_block_with_txs(
+        &self,
+        block_id: BlockId,
+    ) -> Result<MaybePendingBlockWithTxs, ProviderError>;
+
+    async fn get_block_with_receipts(
+        &self,
+        block_id: BlockId,
+    ) -> Result<MaybePendingBlockWithReceipts, ProviderError>;
+
+    async fn get_state_update(
+        &self,
+        block_id: BlockId,
+    ) -> Result<MaybePendingStateUpdate, ProviderError>;
+
+    async fn get_storage_at(
+        &self,
+        contract_address: Felt,
+        key: Felt,
+        block_id: BlockId,
+    ) -> Result<Felt, ProviderError>;
+
+    async fn get_transaction_status(
+        &self,
+        transaction_hash: Felt,
+    ) -> Result<TransactionStatus, ProviderError>;
+
+    async fn get_transaction_by_hash(
+        &self,
+        transaction_hash: Felt,
+    ) -> Result<Transaction, ProviderError>;
+
+    async fn get_transaction_by_block_id_and_index(
+        &self,
+        block_id: BlockId,
+        index: u64,
+    ) -> Result<Transaction, ProviderError>;
+
+    async fn get_transaction_receipt(
+        &self,
+        transaction_hash: Felt,
+    ) -> Result<TransactionReceiptWithBlockInfo, ProviderError>;
+
+    async fn get_class(
+        &self,
+        block_id: BlockId,
+        class_hash: Felt,
+    ) -> Result<ContractClass, ProviderError>;
+
+    async fn get_class_hash_at(
+        &self,
+        block_id: BlockId,
+        contract_address: Felt,
+    ) -> Result<Felt, ProviderError>;
+
+    async fn get_class_at(
+        &self,
+        block_id: BlockId,
+        contract_address: Felt,
+    ) -> Result<ContractClass, ProviderError>;
+
+    async fn get_block_transaction_count(&self, block_id: BlockId) -> Result<u64, ProviderError>;
+
+    async fn call(
+        &self,
+        request: &FunctionCall,
+        block_id: BlockId,
+    ) -> Result<Vec<Felt>, ProviderError>;
+
+    async fn estimate_fee(
+        &self,
+        request: &[BroadcastedTransaction],
+        simulation_flags: &[SimulationFlagForEstimateFee],
+        block_id: BlockId,
+    ) -> Result<Vec<FeeEstimate>, ProviderError>;
+
+    async fn estimate_message_fee(
+        &self,
+        message: &MsgFromL1,
+        block_id: BlockId,
+    ) -> Result<FeeEstimate, ProviderError>;
+
+    async fn block_number(&self) -> Result<u64, ProviderError>;
+
+    async fn block_hash_and_number(&self) -> Result<BlockHashAndNumber, ProviderError>;
+
+    async fn chain_id(&self) -> Result<Felt, ProviderError>;
+
+    async fn syncing(&self) -> Result<SyncStatusType, ProviderError>;
+
+    async fn get_events(
+        &self,
+        filter: EventFilter,
+        continuation_token: Option<String>,
+        chunk_size: u64,
+    ) -> Result<EventsPage, ProviderError>;
+
+    async fn get_nonce(
+        &self,
+        block_id: BlockId,
+        contract_address: Felt,
+    ) -> Result<Felt, ProviderError>;
+
+    async fn add_invoke_transaction(
+        &self,
+        invoke_transaction: &BroadcastedInvokeTransaction,
+    ) -> Result<InvokeTransactionResult, ProviderError>;
+
+    async fn add_declare_transaction(
+        &self,
+        declare_transaction: &BroadcastedDeclareTransaction,
+    ) -> Result<DeclareTransactionResult, ProviderError>;
+
+    async fn add_deploy_account_transaction(
+        &self,
+        deploy_account_transaction: &BroadcastedDeployAccountTransaction,
+    ) -> Result<DeployAccountTransactionResult, ProviderError>;
+
+    async fn trace_transaction(
+        &self,
+        transaction_hash: Felt,
+    ) -> Result<TransactionTrace, ProviderError>;
+
+    async fn simulate_transactions(
+        &self,
+        block_id: BlockId,
+        transactions: &[BroadcastedTransaction],
+        simulation_flags: &[SimulationFlag],
+    ) -> Result<Vec<SimulatedTransaction>, ProviderError>;
+
+    async fn trace_block_transactions(
+        &self,
+        block_id: BlockId,
+    ) -> Result<Vec<TransactionTraceWithHash>, ProviderError>;
+
+    async fn batch_requests(
+        &self,
+        requests: &[ProviderRequestData],
+    ) -> Result<Vec<ProviderResponseData>, ProviderError>;
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl<P> DynCartridgeProvider for P
+where
+    P: CartridgeProvider + Send + Sync,
+{
+    async fn add_execute_outside_transaction(
+        &self,
+        outside_execution: OutsideExecution,
+        address: Felt,
+        signature: Vec<Felt>,
+    ) -> Result<ExecuteFromOutsideResponse, ExecuteFromOutsideError> {
+        CartridgeProvider::add_execute_outside_transaction(
+            self,
+            outside_execution,
+            address,
+            signature,
+        )
+        .await
+    }
+
+    async fn spec_version(&self) -> Result<String, ProviderError> {
+        Provider::spec_version(self).await
+    }
+
+    async fn get_block_with_tx_hashes(
+        &self,
+        block_id: BlockId,
+    ) -> Result<MaybePendingBlockWithTxHashes, ProviderError> {
+        Provider::get_block_with_tx_hashes(self, block_id).await
+    }
+
+    async fn get_block_with_txs(
+        &self,
+        block_id: BlockId,
+    ) -> Result<MaybePendingBlockWithTxs, ProviderError> {
+        Provider::get_block_with_txs(self, block_id).await
+    }
+
+    async fn get_block_with_receipts(
+        &self,
+        block_id: BlockId,
+    ) -> Result<MaybePendingBlockWithReceipts, ProviderError> {
+        Provider::get_block_with_receipts(self, block_id).await
+    }
+
+    async fn get_state_update(
+        &self,
+        block_id: BlockId,
+    ) -> Result<MaybePendingStateUpdate, ProviderError> {
+        Provider::get_state_update(self, block_id).await
+    }
+
+    async fn get_storage_at(
+        &self,
+        contract_address: Felt,
+        key: Felt,
+        block_id: BlockId,
+    ) -> Result<Felt, ProviderError> {
+        Provider::get_storage_at(self, contract_address, key, block_id).await
+    }
+
+    async fn get_transaction_status(
+        &self,
+        transaction_hash: Felt,
+    ) -> Result<TransactionStatus, ProviderError> {
+        Provider::get_transaction_status(self, transaction_hash).await
+    }
+
+    async fn get_transaction_by_hash(
+        &self,
+        transaction_hash: Felt,
+    ) -> Result<Transaction, ProviderError> {
+        Provider::get_transaction_by_hash(self, transaction_hash).await
+    }
+
+    async fn get_transaction_by_block_id_and_index(
+        &self,
+        block_id: BlockId,
+        index: u64,
+    ) -> Result<Transaction, ProviderError> {
+        Provider::get_transaction_by_block_id_and_index(self, block_id, index).await
+    }
+
+    async fn get_transaction_receipt(
+        &self,
+        transaction_hash: Felt,
+    ) -> Result<TransactionReceiptWithBlockInfo, ProviderError> {
+        Provider::get_transaction_receipt(self, transaction_hash).await
+    }
+
+    async fn get_class(
+        &self,
+        block_id: BlockId,
+        class_hash: Felt,
+    ) -> Result<ContractClass, ProviderError> {
+        Provider::get_class(self, block_id, class_hash).await
+    }
+
+    async fn get_class_hash_at(
+        &self,
+        block_id: BlockId,
+        contract_address: Felt,
+    ) -> Result<Felt, ProviderError> {
+        Provider::get_class_hash_at(self, block_id, contract_address).await
+    }
+
+    async fn get_class_at(
+        &self,
+        block_id: BlockId,
+        contract_address: Felt,
+    ) -> Result<ContractClass, ProviderError> {
+        Provider::get_class_at(self, block_id, contract_address).await
+    }
+
+    async fn get_block_transaction_count(&self, block_id: BlockId) -> Result<u64, ProviderError> {
+        Provider::get_block_transaction_count(self, block_id).await
+    }
+
+    async fn call(
+        &self,
+        request: &FunctionCall,
+        block_id: BlockId,
+    ) -> Result<Vec<Felt>, ProviderError> {
+        Provider::call(self, request, block_id).await
+    }
+
+    async fn estimate_fee(
+        &self,
+        request: &[BroadcastedTransaction],
+        simulation_flags: &[SimulationFlagForEstimateFee],
+        block_id: BlockId,
+    ) -> Result<Vec<FeeEstimate>, ProviderError> {
+        Provider::estimate_fee(self, request, simulation_flags, block_id).await
+    }
+
+    async fn estimate_message_fee(
+        &self,
+        message: &MsgFromL1,
+        block_id: BlockId,
+    ) -> Result<FeeEstimate, ProviderError> {
+        Provider::estimate_message_fee(self, message, block_id).await
+    }
+
+    async fn block_number(&self) -> Result<u64, ProviderError> {
+        Provider::block_number(self).await
+    }
+
+    async fn block_hash_and_number(&self) -> Result<BlockHashAndNumber, ProviderError> {
+        Provider::block_hash_and_number(self).await
+    }
+
+    async fn chain_id(&self) -> Result<Felt, ProviderError> {
+        Provider::chain_id(self).await
+    }
+
+    async fn syncing(&self) -> Result<SyncStatusType, ProviderError> {
+        Provider::syncing(self).await
+    }
+
+    async fn get_events(
+        &self,
+        filter: EventFilter,
+        continuation_token: Option<String>,
+        chunk_size: u64,
+    ) -> Result<EventsPage, ProviderError> {
+        Provider::get_events(self, filter, continuation_token, chunk_size).await
+    }
+
+    async fn get_nonce(
+        &self,
+        block_id: BlockId,
+        contract_address: Felt,
+    ) -> Result<Felt, ProviderError> {
+        Provider::get_nonce(self, block_id, contract_address).await
+    }
+
+    async fn add_invoke_transaction(
+        &self,
+        invoke_transaction: &BroadcastedInvokeTransaction,
+    ) -> Result<InvokeTransactionResult, ProviderError> {
+        Provider::add_invoke_transaction(self, invoke_transaction).await
+    }
+
+    async fn add_declare_transaction(
+        &self,
+        declare_transaction: &BroadcastedDeclareTransaction,
+    ) -> Result<DeclareTransactionResult, ProviderError> {
+        Provider::add_declare_transaction(self, declare_transaction).await
+    }
+
+    async fn add_deploy_account_transaction(
+        &self,
+        deploy_account_transaction: &BroadcastedDeployAccountTransaction,
+    ) -> Result<DeployAccountTransactionResult, ProviderError> {
+        Provider::add_deploy_account_transaction(self, deploy_account_transaction).await
+    }
+
+    async fn trace_transaction(
+        &self,
+        transaction_hash: Felt,
+    ) -> Result<TransactionTrace, ProviderError> {
+        Provider::trace_transaction(self, transaction_hash).await
+    }
+
+    async fn simulate_transactions(
+        &self,
+        block_id: BlockId,
+        transactions: &[BroadcastedTransaction],
+        simulation_flags: &[SimulationFlag],
+    ) -> Result<Vec<SimulatedTransaction>, ProviderError> {
+        Provider::simulate_transactions(self, block_id, transactions, simulation_flags).await
+    }
+
+    async fn trace_block_transactions(
+        &self,
+        block_id: BlockId,
+    ) -> Result<Vec<TransactionTraceWithHash>, ProviderError> {
+        Provider::trace_block_transactions(self, block_id).await
+    }
+
+    async fn batch_requests(
+        &self,
+        requests: &[ProviderRequestData],
+    ) -> Result<Vec<ProviderResponseData>, ProviderError> {
+        Provider::batch_requests(self, requests).await
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl CartridgeProvider for SharedProvider {
+    async fn add_execute_outside_transaction(
+        &self,
+        outside_execution: OutsideExecution,
+        address: Felt,
+        signature: Vec<Felt>,
+    ) -> Result<ExecuteFromOutsideResponse, ExecuteFromOutsideError> {
+        self.inner
+            .add_execute_outside_transaction(outside_execution, address, signature)
+            .await
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl Provider for SharedProvider {
+    async fn spec_version(&self) -> Result<String, ProviderError> {
+        self.inner.spec_version().await
+    }
+
+    async fn get_block_with_tx_hashes<B>(
+        &self,
+        block_id: B,
+    ) -> Result<MaybePendingBlockWithTxHashes, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        self.inner
+            .get_block_with_tx_hashes(*block_id.as_ref())
+            .await
+    }
+
+    async fn get_block_with_txs<B>(
+        &self,
+        block_id: B,
+    ) -> Result<MaybePendingBlockWithTxs, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        self.inner.get_block_with_txs(*block_id.as_ref()).await
+    }
+
+    async fn get_block_with_receipts<B>(
+        &self,
+        block_id: B,
+    ) -> Result<MaybePendingBlockWithReceipts, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        self.inner.get_block_with_receipts(*block_id.as_ref()).await
+    }
+
+    async fn get_state_update<B>(
+        &self,
+        block_id: B,
+    ) -> Result<MaybePendingStateUpdate, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        self.inner.get_state_update(*block_id.as_ref()).await
+    }
+
+    async fn get_storage_at<A, K, B>(
+        &self,
+        contract_address: A,
+        key: K,
+        block_id: B,
+    ) -> Result<Felt, ProviderError>
+    where
+        A: AsRef<Felt> + Send + Sync,
+        K: AsRef<Felt> + Send + Sync,
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        self.inner
+            .get_storage_at(
+                *contract_address.as_ref(),
+                *key.as_ref(),
+                *block_id.as_ref(),
+            )
+            .await
+    }
+
+    async fn get_transaction_status<H>(
+        &self,
+        transaction_hash: H,
+    ) -> Result<TransactionStatus, ProviderError>
+    where
+        H: AsRef<Felt> + Send + Sync,
+    {
+        self.inner
+            .get_transaction_status(*transaction_hash.as_ref())
+            .await
+    }
+
+    async fn get_transaction_by_hash<H>(
+        &self,
+        transaction_hash: H,
+    ) -> Result<Transaction, ProviderError>
+    where
+        H: AsRef<Felt> + Send + Sync,
+    {
+        self.inner
+            .get_transaction_by_hash(*transaction_hash.as_ref())
+            .await
+    }
+
+    async fn get_transaction_by_block_id_and_index<B>(
+        &self,
+        block_id: B,
+        index: u64,
+    ) -> Result<Transaction, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        self.inner
+            .get_transaction_by_block_id_and_index(*block_id.as_ref(), index)
+            .await
+    }
+
+    async fn get_transaction_receipt<H>(
+        &self,
+        transaction_hash: H,
+    ) -> Result<TransactionReceiptWithBlockInfo, ProviderError>
+    where
+        H: AsRef<Felt> + Send + Sync,
+    {
+        self.inner
+            .get_transaction_receipt(*transaction_hash.as_ref())
+            .await
+    }
+
+    async fn get_class<B, H>(
+        &self,
+        block_id: B,
+        class_hash: H,
+    ) -> Result<ContractClass, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+        H: AsRef<Felt> + Send + Sync,
+    {
+        self.inner
+            .get_class(*block_id.as_ref(), *class_hash.as_ref())
+            .await
+    }
+
+    async fn get_class_hash_at<B, A>(
+        &self,
+        block_id: B,
+        contract_address: A,
+    ) -> Result<Felt, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+        A: AsRef<Felt> + Send + Sync,
+    {
+        self.inner
+            .get_class_hash_at(*block_id.as_ref(), *contract_address.as_ref())
+            .await
+    }
+
+    async fn get_class_at<B, A>(
+        &self,
+        block_id: B,
+        contract_address: A,
+    ) -> Result<ContractClass, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+        A: AsRef<Felt> + Send + Sync,
+    {
+        self.inner
+            .get_class_at(*block_id.as_ref(), *contract_address.as_ref())
+            .await
+    }
+
+    async fn get_block_transaction_count<B>(&self, block_id: B) -> Result<u64, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        self.inner
+            .get_block_transaction_count(*block_id.as_ref())
+            .await
+    }
+
+    async fn call<R, B>(&self, request: R, block_id: B) -> Result<Vec<Felt>, ProviderError>
+    where
+        R: AsRef<FunctionCall> + Send + Sync,
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        self.inner.call(request.as_ref(), *block_id.as_ref()).await
+    }
+
+    async fn estimate_fee<R, S, B>(
+        &self,
+        request: R,
+        simulation_flags: S,
+        block_id: B,
+    ) -> Result<Vec<FeeEstimate>, ProviderError>
+    where
+        R: AsRef<[BroadcastedTransaction]> + Send + Sync,
+        S: AsRef<[SimulationFlagForEstimateFee]> + Send + Sync,
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        self.inner
+            .estimate_fee(
+                request.as_ref(),
+                simulation_flags.as_ref(),
+                *block_id.as_ref(),
+            )
+            .await
+    }
+
+    async fn estimate_message_fee<M, B>(
+        &self,
+        message: M,
+        block_id: B,
+    ) -> Result<FeeEstimate, ProviderError>
+    where
+        M: AsRef<MsgFromL1> + Send + Sync,
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        self.inner
+            .estimate_message_fee(message.as_ref(), *block_id.as_ref())
+            .await
+    }
+
+    async fn block_number(&self) -> Result<u64, ProviderError> {
+        self.inner.block_number().await
+    }
+
+    async fn block_hash_and_number(&self) -> Result<BlockHashAndNumber, ProviderError> {
+        self.inner.block_hash_and_number().await
+    }
+
+    async fn chain_id(&self) -> Result<Felt, ProviderError> {
+        self.inner.chain_id().await
+    }
+
+    async fn syncing(&self) -> Result<SyncStatusType, ProviderError> {
+        self.inner.syncing().await
+    }
+
+    async fn get_events(
+        &self,
+        filter: EventFilter,
+        continuation_token: Option<String>,
+        chunk_size: u64,
+    ) -> Result<EventsPage, ProviderError> {
+        self.inner
+            .get_events(filter, continuation_token, chunk_size)
+            .await
+    }
+
+    async fn get_nonce<B, A>(&self, block_id: B, contract_address: A) -> Result<Felt, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+        A: AsRef<Felt> + Send + Sync,
+    {
+        self.inner
+            .get_nonce(*block_id.as_ref(), *contract_address.as_ref())
+            .await
+    }
+
+    async fn add_invoke_transaction<I>(
+        &self,
+        invoke_transaction: I,
+    ) -> Result<InvokeTransactionResult, ProviderError>
+    where
+        I: AsRef<BroadcastedInvokeTransaction> + Send + Sync,
+    {
+        self.inner
+            .add_invoke_transaction(invoke_transaction.as_ref())
+            .await
+    }
+
+    async fn add_declare_transaction<D>(
+        &self,
+        declare_transaction: D,
+    ) -> Result<DeclareTransactionResult, ProviderError>
+    where
+        D: AsRef<BroadcastedDeclareTransaction> + Send + Sync,
+    {
+        self.inner
+            .add_declare_transaction(declare_transaction.as_ref())
+            .await
+    }
+
+    async fn add_deploy_account_transaction<D>(
+        &self,
+        deploy_account_transaction: D,
+    ) -> Result<DeployAccountTransactionResult, ProviderError>
+    where
+        D: AsRef<BroadcastedDeployAccountTransaction> + Send + Sync,
+    {
+        self.inner
+            .add_deploy_account_transaction(deploy_account_transaction.as_ref())
+            .await
+    }
+
+    async fn trace_transaction<H>(
+        &self,
+        transaction_hash: H,
+    ) -> Result<TransactionTrace, ProviderError>
+    where
+        H: AsRef<Felt> + Send + Sync,
+    {
+        self.inner
+            .trace_transaction(*transaction_hash.as_ref())
+            .await
+    }
+
+    async fn simulate_transactions<B, T, S>(
+        &self,
+        block_id: B,
+        transactions: T,
+        simulation_flags: S,
+    ) -> Result<Vec<SimulatedTransaction>, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+        T: AsRef<[BroadcastedTransaction]> + Send + Sync,
+        S: AsRef<[SimulationFlag]> + Send + Sync,
+    {
+        self.inner
+            .simulate_transactions(
+                *block_id.as_ref(),
+                transactions.as_ref(),
+                simulation_flags.as_ref(),
+            )
+            .await
+    }
+
+    async fn trace_block_transactions<B>(
+        &self,
+        block_id: B,
+    ) -> Result<Vec<TransactionTraceWithHash>, ProviderError>
+    where
+        B: AsRef<BlockId> + Send + Sync,
+    {
+        self.inner
+            .trace_block_transactions(*block_id.as_ref())
+            .await
+    }
+
+    async fn batch_requests<R>(
+        &self,
+        requests: R,
+    ) -> Result<Vec<ProviderResponseData>, ProviderError>
+    where
+        R: AsRef<[ProviderRequestData]> + Send + Sync,
+    {
+        self.inner.batch_requests(requests.as_ref()).await
+    }
+}
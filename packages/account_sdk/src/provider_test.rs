@@ -1,10 +1,22 @@
+use std::time::Duration;
+
+use reqwest::header::{HeaderName, HeaderValue};
 use serde_json::json;
 use starknet::{
-    core::types::StarknetError,
-    providers::{jsonrpc::JsonRpcResponse, ProviderError},
+    core::types::{Felt, StarknetError},
+    providers::{jsonrpc::JsonRpcResponse, Provider, ProviderError},
+};
+
+use crate::{
+    abigen::controller::OutsideExecutionV3,
+    account::outside_execution::{OutsideExecution, OutsideExecutionCaller},
+    tests::runners::stub::{result_response, StubServer},
 };
 
-use super::{ExecuteFromOutsideError, ExecuteFromOutsideResponse};
+use super::{
+    config::ProviderConfig, CartridgeJsonRpcProvider, CartridgeProvider, ExecuteFromOutsideError,
+    ExecuteFromOutsideResponse,
+};
 
 #[test]
 fn test_json_rpc_error_to_outside_execution_error() {
@@ -37,3 +49,46 @@ fn test_json_rpc_error_to_outside_execution_error() {
         }
     }
 }
+
+#[tokio::test]
+async fn test_config_headers_sent_on_every_path() {
+    let server = StubServer::start(|_, request| match request["method"].as_str() {
+        Some("starknet_chainId") => result_response(request, json!("0x534e5f5345504f4c4941")),
+        Some("cartridge_addExecuteOutsideTransaction") => {
+            result_response(request, json!({ "transaction_hash": "0x1" }))
+        }
+        method => panic!("Unexpected method: {:?}", method),
+    });
+
+    let config = ProviderConfig::default()
+        .with_header(
+            HeaderName::from_static("x-api-key"),
+            HeaderValue::from_static("secret"),
+        )
+        .with_timeout(Duration::from_secs(5));
+    let provider = CartridgeJsonRpcProvider::with_config(server.url.clone(), &config);
+
+    provider.chain_id().await.unwrap();
+    // Clones reuse the same client, and so its settings
+    provider
+        .clone()
+        .add_execute_outside_transaction(
+            OutsideExecution::V3(OutsideExecutionV3 {
+                caller: OutsideExecutionCaller::Any.into(),
+                execute_after: 0,
+                execute_before: u64::MAX,
+                calls: vec![],
+                nonce: (Felt::ONE, 1),
+            }),
+            Felt::ONE,
+            vec![],
+        )
+        .await
+        .unwrap();
+
+    let headers = server.headers();
+    assert_eq!(headers.len(), 2);
+    for headers in headers {
+        assert_eq!(headers["x-api-key"], "secret");
+    }
+}
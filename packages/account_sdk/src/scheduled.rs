@@ -0,0 +1,219 @@
+use std::time::Duration;
+
+use starknet::{core::types::Call, signers::SigningKey};
+
+use crate::{
+    abigen::controller::OutsideExecutionV3,
+    account::outside_execution::{
+        OutsideExecution, OutsideExecutionAccount, OutsideExecutionCaller, SignedOutsideExecution,
+    },
+    controller::Controller,
+    errors::ControllerError,
+    provider::{CartridgeProvider, ExecuteFromOutsideError, ExecuteFromOutsideResponse},
+    utils::time::{get_current_timestamp, sleep},
+};
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+#[path = "scheduled_test.rs"]
+mod scheduled_test;
+
+/// Describes when a series of pre-signed outside executions can be submitted.
+///
+/// The first occurrence opens at `start` and every following one opens `interval` seconds after
+/// the previous one. Each occurrence stays valid for `window` seconds once it opens.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Schedule {
+    pub start: u64,
+    pub interval: u64,
+    pub occurrences: u32,
+    pub window: u64,
+}
+
+impl Schedule {
+    pub fn once(execute_after: u64, window: u64) -> Self {
+        Self {
+            start: execute_after,
+            interval: 0,
+            occurrences: 1,
+            window,
+        }
+    }
+
+    pub fn recurring(start: u64, interval: u64, occurrences: u32, window: u64) -> Self {
+        Self {
+            start,
+            interval,
+            occurrences,
+            window,
+        }
+    }
+
+    /// Returns the `(execute_after, execute_before)` bounds of every occurrence.
+    pub fn windows(&self) -> impl Iterator<Item = (u64, u64)> + '_ {
+        (0..self.occurrences as u64).map(move |i| {
+            let execute_after = self.start.saturating_add(i.saturating_mul(self.interval));
+            (execute_after, execute_after.saturating_add(self.window))
+        })
+    }
+}
+
+impl Controller {
+    /// Signs one outside execution of `calls` per occurrence of `schedule`.
+    ///
+    /// Every execution gets its own nonce bit, so they can be submitted in any order and none of
+    /// them invalidates the others. The returned executions carry their signature and can be
+    /// stored and submitted later without access to the owner.
+    pub async fn sign_scheduled_executions(
+        &self,
+        calls: Vec<Call>,
+        schedule: &Schedule,
+    ) -> Result<Vec<SignedOutsideExecution>, ControllerError> {
+        let mut executions = Vec::with_capacity(schedule.occurrences as usize);
+        let mut namespace = SigningKey::from_random().secret_scalar();
+
+        for (i, (execute_after, execute_before)) in schedule.windows().enumerate() {
+            // A namespace holds 128 nonce bits, move to a fresh one once they are all used
+            let bit = i % 128;
+            if i > 0 && bit == 0 {
+                namespace = SigningKey::from_random().secret_scalar();
+            }
+
+            let outside_execution = OutsideExecutionV3 {
+                caller: OutsideExecutionCaller::Any.into(),
+                execute_after,
+                execute_before,
+                calls: calls.iter().cloned().map(|call| call.into()).collect(),
+                nonce: (namespace, 1u128 << bit),
+            };
+
+            executions.push(
+                self.sign_outside_execution(OutsideExecution::V3(outside_execution))
+                    .await?,
+            );
+        }
+
+        Ok(executions)
+    }
+}
+
+#[derive(Debug)]
+pub struct ScheduledSubmission {
+    pub execution: SignedOutsideExecution,
+    pub result: Result<ExecuteFromOutsideResponse, ExecuteFromOutsideError>,
+}
+
+/// Holds pre-signed outside executions and submits each of them through the paymaster once its
+/// window opens.
+pub struct ScheduledExecutor<P: CartridgeProvider> {
+    provider: P,
+    pending: Vec<SignedOutsideExecution>,
+    /// How long to wait before retrying an execution the paymaster considered too early.
+    retry_interval: Duration,
+}
+
+impl<P> ScheduledExecutor<P>
+where
+    P: CartridgeProvider + Send + Sync,
+{
+    const DEFAULT_RETRY_INTERVAL: Duration = Duration::from_secs(2);
+
+    pub fn new(provider: P) -> Self {
+        Self {
+            provider,
+            pending: Vec::new(),
+            retry_interval: Self::DEFAULT_RETRY_INTERVAL,
+        }
+    }
+
+    pub fn with_retry_interval(self, retry_interval: Duration) -> Self {
+        Self {
+            retry_interval,
+            ..self
+        }
+    }
+
+    pub fn schedule(&mut self, execution: SignedOutsideExecution) {
+        self.pending.push(execution);
+        self.pending
+            .sort_by_key(|execution| execution.outside_execution.execute_after());
+    }
+
+    pub fn schedule_all(&mut self, executions: impl IntoIterator<Item = SignedOutsideExecution>) {
+        self.pending.extend(executions);
+        self.pending
+            .sort_by_key(|execution| execution.outside_execution.execute_after());
+    }
+
+    pub fn pending(&self) -> &[SignedOutsideExecution] {
+        &self.pending
+    }
+
+    /// Returns the timestamp after which the earliest pending execution becomes valid.
+    pub fn next_window(&self) -> Option<u64> {
+        self.pending
+            .first()
+            .map(|execution| execution.outside_execution.execute_after())
+    }
+
+    /// Submits every pending execution whose window is open at `now`.
+    ///
+    /// Executions whose window already closed are reported as
+    /// `ExecuteFromOutsideError::ExecutionTimePassed` without being sent. Executions the paymaster
+    /// rejects as not yet valid stay pending.
+    pub async fn submit_due(&mut self, now: u64) -> Vec<ScheduledSubmission> {
+        let (due, pending): (Vec<_>, Vec<_>) = std::mem::take(&mut self.pending)
+            .into_iter()
+            .partition(|execution| execution.outside_execution.execute_after() < now);
+        self.pending = pending;
+
+        let mut submissions = Vec::with_capacity(due.len());
+        for execution in due {
+            if now >= execution.outside_execution.execute_before() {
+                submissions.push(ScheduledSubmission {
+                    execution,
+                    result: Err(ExecuteFromOutsideError::ExecutionTimePassed),
+                });
+                continue;
+            }
+
+            let result = self
+                .provider
+                .add_execute_outside_transaction(
+                    execution.outside_execution.clone(),
+                    execution.contract_address,
+                    execution.signature.clone(),
+                )
+                .await;
+
+            match result {
+                // The chain clock can lag behind ours, try again on the next round
+                Err(ExecuteFromOutsideError::ExecutionTimeNotReached) => self.schedule(execution),
+                result => submissions.push(ScheduledSubmission { execution, result }),
+            }
+        }
+
+        submissions
+    }
+
+    /// Waits for each window to open and submits the matching execution, until no execution is
+    /// left pending.
+    pub async fn run(mut self) -> Vec<ScheduledSubmission> {
+        let mut submissions = Vec::new();
+
+        while let Some(next_window) = self.next_window() {
+            let now = get_current_timestamp();
+            if next_window >= now {
+                sleep(Duration::from_secs(next_window + 1 - now)).await;
+                continue;
+            }
+
+            let submitted = self.submit_due(now).await;
+            if submitted.is_empty() {
+                sleep(self.retry_interval).await;
+            }
+            submissions.extend(submitted);
+        }
+
+        submissions
+    }
+}
@@ -0,0 +1,149 @@
+use std::time::Duration;
+
+use cainome::cairo_serde::{CairoSerde, ContractAddress, U256};
+use starknet::{
+    core::types::Call,
+    macros::{felt, selector},
+};
+use url::Url;
+
+use crate::{
+    abigen::erc_20::Erc20,
+    account::outside_execution::OutsideExecution,
+    artifacts::{Version, CONTROLLERS},
+    controller::Controller,
+    scheduled::{Schedule, ScheduledExecutor},
+    signers::{Owner, Signer},
+    tests::{account::FEE_TOKEN_ADDRESS, runners::katana::KatanaRunner},
+    transaction_waiter::TransactionWaiter,
+    utils::time::get_current_timestamp,
+};
+
+fn transfer_call(recipient: ContractAddress, amount: U256) -> Call {
+    Call {
+        to: *FEE_TOKEN_ADDRESS,
+        selector: selector!("transfer"),
+        calldata: [
+            <ContractAddress as CairoSerde>::cairo_serialize(&recipient),
+            <U256 as CairoSerde>::cairo_serialize(&amount),
+        ]
+        .concat(),
+    }
+}
+
+#[test]
+fn test_schedule_windows() {
+    let schedule = Schedule::recurring(1000, 604800, 3, 3600);
+    assert_eq!(
+        schedule.windows().collect::<Vec<_>>(),
+        vec![(1000, 4600), (605800, 609400), (1210600, 1214200)]
+    );
+
+    let schedule = Schedule::once(1000, 600);
+    assert_eq!(schedule.windows().collect::<Vec<_>>(), vec![(1000, 1600)]);
+}
+
+#[tokio::test]
+async fn test_sign_scheduled_executions_distinct_nonces() {
+    let controller = Controller::new(
+        "app_id".to_string(),
+        "username".to_string(),
+        CONTROLLERS[&Version::LATEST].hash,
+        Url::parse("http://localhost:5050").unwrap(),
+        Owner::Signer(Signer::new_starknet_random()),
+        felt!("0xdeadbeef"),
+        felt!("0x534e5f5345504f4c4941"),
+    )
+    .unwrap();
+
+    let call = transfer_call(
+        ContractAddress(felt!("0x18301129")),
+        U256 { low: 1, high: 0 },
+    );
+    let executions = controller
+        .sign_scheduled_executions(vec![call], &Schedule::recurring(1000, 10, 130, 5))
+        .await
+        .unwrap();
+
+    assert_eq!(executions.len(), 130);
+
+    let nonces = executions
+        .iter()
+        .map(|execution| match &execution.outside_execution {
+            OutsideExecution::V3(v3) => v3.nonce,
+            OutsideExecution::V2(_) => panic!("Expected a V3 outside execution"),
+        })
+        .collect::<Vec<_>>();
+
+    for (i, nonce) in nonces.iter().enumerate() {
+        assert_eq!(nonce.1, 1u128 << (i % 128));
+        assert_eq!(
+            nonces.iter().filter(|other| *other == nonce).count(),
+            1,
+            "Nonce should be unique"
+        );
+    }
+    assert_eq!(nonces[0].0, nonces[127].0);
+    assert_ne!(nonces[0].0, nonces[128].0);
+
+    assert_eq!(executions[1].outside_execution.execute_after(), 1010);
+    assert_eq!(executions[1].outside_execution.execute_before(), 1015);
+}
+
+#[tokio::test]
+async fn test_scheduled_executor_submits_when_window_opens() {
+    let runner = KatanaRunner::load();
+    let controller = runner
+        .deploy_controller(
+            "testuser".to_owned(),
+            Owner::Signer(Signer::new_starknet_random()),
+            Version::LATEST,
+        )
+        .await;
+
+    let recipient = ContractAddress(felt!("0x18301129"));
+    let amount = U256 {
+        low: 0x10_u128,
+        high: 0,
+    };
+
+    let now = get_current_timestamp();
+    let executions = controller
+        .sign_scheduled_executions(
+            vec![transfer_call(recipient, amount)],
+            &Schedule::recurring(now - 10, 3, 2, 600),
+        )
+        .await
+        .unwrap();
+
+    let mut executor = ScheduledExecutor::new(runner.client().clone())
+        .with_retry_interval(Duration::from_millis(500));
+    executor.schedule_all(executions);
+
+    let submissions = executor.run().await;
+    assert_eq!(submissions.len(), 2);
+
+    for submission in submissions {
+        let response = submission
+            .result
+            .expect("Scheduled execution should succeed");
+        TransactionWaiter::new(response.transaction_hash, runner.client())
+            .with_timeout(Duration::from_secs(5))
+            .wait()
+            .await
+            .unwrap();
+    }
+
+    let balance = Erc20::new(*FEE_TOKEN_ADDRESS, &controller)
+        .balanceOf(&recipient)
+        .call()
+        .await
+        .unwrap();
+    assert_eq!(
+        balance,
+        U256 {
+            low: 0x20_u128,
+            high: 0
+        }
+    );
+}
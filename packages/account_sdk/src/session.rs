@@ -24,7 +24,7 @@ impl Controller {
         methods: Vec<Policy>,
         expires_at: u64,
     ) -> Result<SessionAccount, ControllerError> {
-        self.create_session_with_guardian(methods, expires_at, Felt::ZERO)
+        self.create_session_with_guardian(methods, expires_at, self.guardian)
             .await
     }
 
@@ -15,10 +15,8 @@ use crate::{
     constants::GUARDIAN_SIGNER,
     hash::MessageHashRev1,
     signers::{Owner, Signer},
-    tests::{
-        account::FEE_TOKEN_ADDRESS, ensure_txn, runners::katana::KatanaRunner,
-        transaction_waiter::TransactionWaiter,
-    },
+    tests::{account::FEE_TOKEN_ADDRESS, ensure_txn, runners::katana::KatanaRunner},
+    transaction_waiter::TransactionWaiter,
 };
 
 pub async fn test_verify_execute(owner: Owner) {
@@ -0,0 +1,121 @@
+use std::fmt;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use starknet::accounts::ConnectedAccount;
+use starknet::core::types::{Call, Felt};
+
+use crate::transaction_waiter::TransactionWaiter;
+
+use super::SignError;
+
+/// An account owning Controllers, which acts for them by executing calls.
+///
+/// The Controller contract never validates a signature of such an owner. The owner instead
+/// calls the Controller itself, e.g. to register the sessions it then executes through.
+///
+/// Implemented for every starknet-rs [`ConnectedAccount`], including another
+/// [`Controller`](crate::controller::Controller).
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+pub trait OwnerAccount: Send + Sync {
+    /// Address the account is registered with as an external owner.
+    fn owner_address(&self) -> Felt;
+
+    /// Executes `calls` from the account and waits for them to be accepted. Returns the hash
+    /// of the transaction.
+    async fn execute_as_owner(&self, calls: Vec<Call>) -> Result<Felt, SignError>;
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl<A> OwnerAccount for A
+where
+    A: ConnectedAccount + Send + Sync,
+    A::Provider: Send,
+    A::SignError: fmt::Display,
+{
+    fn owner_address(&self) -> Felt {
+        self.address()
+    }
+
+    async fn execute_as_owner(&self, calls: Vec<Call>) -> Result<Felt, SignError> {
+        let result = self
+            .execute_v1(calls)
+            .send()
+            .await
+            .map_err(|e| SignError::OwnerAccount(e.to_string()))?;
+
+        TransactionWaiter::new(result.transaction_hash, self.provider())
+            .wait()
+            .await
+            .map_err(|e| SignError::OwnerAccount(e.to_string()))?;
+
+        Ok(result.transaction_hash)
+    }
+}
+
+/// Owner of a Controller which is itself an account.
+///
+/// Only the address is known for owners restored from storage or read from the chain. Such an
+/// owner can't act for the Controller until an account is connected with
+/// [`AccountOwner::connected`].
+#[derive(Clone)]
+pub struct AccountOwner {
+    address: Felt,
+    account: Option<Arc<dyn OwnerAccount>>,
+}
+
+impl AccountOwner {
+    /// Owner at `address`, without an account to act through.
+    pub fn new(address: Felt) -> Self {
+        Self {
+            address,
+            account: None,
+        }
+    }
+
+    /// Owner acting through `account`.
+    pub fn connected<A>(account: A) -> Self
+    where
+        A: OwnerAccount + 'static,
+    {
+        Self {
+            address: account.owner_address(),
+            account: Some(Arc::new(account)),
+        }
+    }
+
+    pub fn address(&self) -> Felt {
+        self.address
+    }
+
+    /// The account the owner acts through, if one is connected.
+    pub fn account(&self) -> Option<&dyn OwnerAccount> {
+        self.account.as_deref()
+    }
+
+    /// Executes `calls` through the connected account, like
+    /// [`OwnerAccount::execute_as_owner`].
+    pub async fn execute(&self, calls: Vec<Call>) -> Result<Felt, SignError> {
+        match &self.account {
+            Some(account) => account.execute_as_owner(calls).await,
+            None => Err(SignError::AccountOwnerCannotSign),
+        }
+    }
+}
+
+impl From<Felt> for AccountOwner {
+    fn from(address: Felt) -> Self {
+        Self::new(address)
+    }
+}
+
+impl fmt::Debug for AccountOwner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AccountOwner")
+            .field("address", &self.address)
+            .field("connected", &self.account.is_some())
+            .finish()
+    }
+}
@@ -1,3 +1,4 @@
+pub mod account_owner;
 pub mod starknet;
 
 #[cfg(feature = "webauthn")]
@@ -18,17 +19,21 @@ use webauthn::WebauthnSigner;
 use crate::abigen::controller::SignerSignature;
 use async_trait::async_trait;
 
+pub use account_owner::{AccountOwner, OwnerAccount};
+
 #[derive(Debug, Clone)]
 pub enum Owner {
     Signer(Signer),
-    Account(Felt),
+    Account(AccountOwner),
 }
 
 impl From<Owner> for crate::abigen::controller::Owner {
     fn from(owner: Owner) -> Self {
         match owner {
             Owner::Signer(signer) => crate::abigen::controller::Owner::Signer(signer.into()),
-            Owner::Account(address) => crate::abigen::controller::Owner::Account(address.into()),
+            Owner::Account(owner) => {
+                crate::abigen::controller::Owner::Account(owner.address().into())
+            }
         }
     }
 }
@@ -37,7 +42,7 @@ impl From<Owner> for Felt {
     fn from(owner: Owner) -> Self {
         match owner {
             Owner::Signer(signer) => Felt::from(crate::abigen::controller::Signer::from(signer)),
-            Owner::Account(address) => address,
+            Owner::Account(owner) => owner.address(),
         }
     }
 }
@@ -145,6 +150,15 @@ pub enum SignError {
 
     #[error("Account owner cannot sign")]
     AccountOwnerCannotSign,
+
+    #[error("Owner account error: {0}")]
+    OwnerAccount(String),
+
+    #[error("No registered session authorizes the calls")]
+    SessionNotAuthorized,
+
+    #[error("Controllers can't declare Cairo 0 classes")]
+    LegacyDeclarationUnsupported,
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -0,0 +1,242 @@
+use std::collections::BTreeMap;
+
+use cainome::cairo_serde::U256;
+use primitive_types::U256 as BigUint256;
+use starknet::accounts::Account;
+use starknet::core::types::{
+    Call, EmittedEvent, ExecuteInvocation, FeeEstimate, Felt, FunctionInvocation,
+    SimulatedTransaction, TransactionTrace,
+};
+use starknet::macros::selector;
+
+use crate::{
+    abigen::controller::ControllerEvent,
+    controller::Controller,
+    errors::ControllerError,
+    tokens::{from_big, to_big},
+};
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+#[path = "simulation_test.rs"]
+mod simulation_test;
+
+/// Outcome of executing calls against the pending state, without submitting them.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SimulationReport {
+    pub status: SimulationStatus,
+    /// Fee the transaction would be charged. It isn't part of `balance_changes`.
+    pub fee_estimate: FeeEstimate,
+    /// Events emitted by the execution, in the order they were emitted.
+    pub events: Vec<SimulatedEvent>,
+    /// Net change of the Controller's balance in every token it sent or received.
+    pub balance_changes: Vec<BalanceChange>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SimulationStatus {
+    Succeeded,
+    Reverted { reason: String },
+}
+
+/// An event emitted during a simulation, decoded when it is one the SDK knows about.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SimulatedEvent {
+    Transfer {
+        token: Felt,
+        from: Felt,
+        to: Felt,
+        amount: U256,
+    },
+    Approval {
+        token: Felt,
+        owner: Felt,
+        spender: Felt,
+        amount: U256,
+    },
+    Controller(ControllerEvent),
+    Unknown {
+        from_address: Felt,
+        keys: Vec<Felt>,
+        data: Vec<Felt>,
+    },
+}
+
+/// Net change of the Controller's balance in `token`. Tokens whose transfers cancel out are
+/// left out of the report.
+#[derive(Clone, Debug, PartialEq)]
+pub enum BalanceChange {
+    Increase { token: Felt, amount: U256 },
+    Decrease { token: Felt, amount: U256 },
+}
+
+impl Controller {
+    /// Simulates executing `calls` in a V1 transaction, signed in query mode so the signature
+    /// can't be replayed on chain.
+    ///
+    /// A call that reverts doesn't fail the simulation, it is reported in
+    /// [`SimulationReport::status`].
+    pub async fn simulate(&self, calls: Vec<Call>) -> Result<SimulationReport, ControllerError> {
+        let simulated = self.execute_v1(calls).simulate(false, true).await?;
+        Ok(SimulationReport::new(self.address, simulated))
+    }
+}
+
+impl SimulationReport {
+    fn new(account: Felt, simulated: SimulatedTransaction) -> Self {
+        let (status, events) = match simulated.transaction_trace {
+            TransactionTrace::Invoke(trace) => match trace.execute_invocation {
+                ExecuteInvocation::Success(invocation) => {
+                    (SimulationStatus::Succeeded, collect_events(&invocation))
+                }
+                ExecuteInvocation::Reverted(reverted) => (
+                    SimulationStatus::Reverted {
+                        reason: reverted.revert_reason,
+                    },
+                    Vec::new(),
+                ),
+            },
+            _ => (SimulationStatus::Succeeded, Vec::new()),
+        };
+
+        let events = events
+            .into_iter()
+            .map(|(from_address, keys, data)| decode_event(account, from_address, keys, data))
+            .collect::<Vec<_>>();
+
+        Self {
+            status,
+            fee_estimate: simulated.fee_estimation,
+            balance_changes: balance_changes(account, &events),
+            events,
+        }
+    }
+}
+
+/// Events emitted by `invocation` and every call it made, as `(from_address, keys, data)`, in
+/// the order they were emitted.
+fn collect_events(invocation: &FunctionInvocation) -> Vec<(Felt, Vec<Felt>, Vec<Felt>)> {
+    fn collect(
+        invocation: &FunctionInvocation,
+        events: &mut Vec<(u64, Felt, Vec<Felt>, Vec<Felt>)>,
+    ) {
+        for event in &invocation.events {
+            events.push((
+                event.order,
+                invocation.contract_address,
+                event.keys.clone(),
+                event.data.clone(),
+            ));
+        }
+        for call in &invocation.calls {
+            collect(call, events);
+        }
+    }
+
+    let mut events = Vec::new();
+    collect(invocation, &mut events);
+    events.sort_by_key(|(order, ..)| *order);
+    events
+        .into_iter()
+        .map(|(_, from_address, keys, data)| (from_address, keys, data))
+        .collect()
+}
+
+/// Decodes an event emitted by `from_address`. ERC20 events are accepted both with their
+/// addresses indexed, as emitted by Cairo 1 tokens, and in their data, as emitted by legacy ones.
+fn decode_event(
+    account: Felt,
+    from_address: Felt,
+    keys: Vec<Felt>,
+    data: Vec<Felt>,
+) -> SimulatedEvent {
+    let erc20 = match (keys.as_slice(), data.as_slice()) {
+        ([selector, a, b], [low, high]) | ([selector], [a, b, low, high]) => {
+            Some((*selector, *a, *b, u256(*low, *high)))
+        }
+        _ => None,
+    };
+
+    match erc20 {
+        Some((selector, from, to, Some(amount))) if selector == selector!("Transfer") => {
+            return SimulatedEvent::Transfer {
+                token: from_address,
+                from,
+                to,
+                amount,
+            }
+        }
+        Some((selector, owner, spender, Some(amount))) if selector == selector!("Approval") => {
+            return SimulatedEvent::Approval {
+                token: from_address,
+                owner,
+                spender,
+                amount,
+            }
+        }
+        _ => {}
+    }
+
+    if from_address == account {
+        let emitted = EmittedEvent {
+            from_address,
+            keys: keys.clone(),
+            data: data.clone(),
+            block_hash: None,
+            block_number: None,
+            transaction_hash: Felt::ZERO,
+        };
+        if let Ok(event) = ControllerEvent::try_from(emitted) {
+            return SimulatedEvent::Controller(event);
+        }
+    }
+
+    SimulatedEvent::Unknown {
+        from_address,
+        keys,
+        data,
+    }
+}
+
+fn u256(low: Felt, high: Felt) -> Option<U256> {
+    Some(U256 {
+        low: low.try_into().ok()?,
+        high: high.try_into().ok()?,
+    })
+}
+
+/// Nets the transfers `account` sent and received, per token.
+fn balance_changes(account: Felt, events: &[SimulatedEvent]) -> Vec<BalanceChange> {
+    let mut totals = BTreeMap::<Felt, (BigUint256, BigUint256)>::new();
+    for event in events {
+        if let SimulatedEvent::Transfer {
+            token,
+            from,
+            to,
+            amount,
+        } = event
+        {
+            let (received, sent) = totals.entry(*token).or_default();
+            if *to == account {
+                *received = to_big(*amount).saturating_add(*received);
+            }
+            if *from == account {
+                *sent = to_big(*amount).saturating_add(*sent);
+            }
+        }
+    }
+
+    totals
+        .into_iter()
+        .filter_map(|(token, (received, sent))| match received.cmp(&sent) {
+            std::cmp::Ordering::Greater => Some(BalanceChange::Increase {
+                token,
+                amount: from_big(received - sent),
+            }),
+            std::cmp::Ordering::Less => Some(BalanceChange::Decrease {
+                token,
+                amount: from_big(sent - received),
+            }),
+            std::cmp::Ordering::Equal => None,
+        })
+        .collect()
+}
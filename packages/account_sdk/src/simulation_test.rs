@@ -0,0 +1,158 @@
+use cainome::cairo_serde::{ContractAddress, U256};
+use starknet::core::types::Felt;
+use starknet::macros::{felt, selector};
+
+use crate::{
+    abigen::erc_20::Erc20,
+    artifacts::Version,
+    signers::{Owner, Signer},
+    tests::{account::FEE_TOKEN_ADDRESS, runners::katana::KatanaRunner},
+};
+
+use super::{balance_changes, decode_event, BalanceChange, SimulatedEvent, SimulationStatus};
+
+const ACCOUNT: Felt = felt!("0xacc");
+const TOKEN: Felt = felt!("0xe7");
+
+fn transfer(from: Felt, to: Felt, low: u128) -> SimulatedEvent {
+    SimulatedEvent::Transfer {
+        token: TOKEN,
+        from,
+        to,
+        amount: U256 { low, high: 0 },
+    }
+}
+
+#[test]
+fn test_decode_erc20_events() {
+    // Cairo 1 tokens index the addresses
+    assert_eq!(
+        decode_event(
+            ACCOUNT,
+            TOKEN,
+            vec![selector!("Transfer"), ACCOUNT, felt!("0x2")],
+            vec![felt!("0x5"), Felt::ZERO],
+        ),
+        transfer(ACCOUNT, felt!("0x2"), 5)
+    );
+    // Legacy tokens put them in the data
+    assert_eq!(
+        decode_event(
+            ACCOUNT,
+            TOKEN,
+            vec![selector!("Approval")],
+            vec![ACCOUNT, felt!("0x2"), felt!("0x5"), Felt::ZERO],
+        ),
+        SimulatedEvent::Approval {
+            token: TOKEN,
+            owner: ACCOUNT,
+            spender: felt!("0x2"),
+            amount: U256 { low: 5, high: 0 },
+        }
+    );
+    assert_eq!(
+        decode_event(ACCOUNT, TOKEN, vec![selector!("Minted")], vec![]),
+        SimulatedEvent::Unknown {
+            from_address: TOKEN,
+            keys: vec![selector!("Minted")],
+            data: vec![],
+        }
+    );
+}
+
+#[test]
+fn test_balance_changes() {
+    let other = felt!("0x2");
+    let events = vec![
+        transfer(ACCOUNT, other, 10),
+        transfer(other, ACCOUNT, 3),
+        SimulatedEvent::Transfer {
+            token: felt!("0x57"),
+            from: other,
+            to: ACCOUNT,
+            amount: U256 { low: 0, high: 1 },
+        },
+        SimulatedEvent::Transfer {
+            token: felt!("0x58"),
+            from: ACCOUNT,
+            to: ACCOUNT,
+            amount: U256 { low: 1, high: 0 },
+        },
+        transfer(other, felt!("0x3"), 100),
+    ];
+
+    assert_eq!(
+        balance_changes(ACCOUNT, &events),
+        vec![
+            BalanceChange::Increase {
+                token: felt!("0x57"),
+                amount: U256 { low: 0, high: 1 },
+            },
+            BalanceChange::Decrease {
+                token: TOKEN,
+                amount: U256 { low: 7, high: 0 },
+            },
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_simulate() {
+    let runner = KatanaRunner::load();
+    let controller = runner
+        .deploy_controller(
+            "testuser".to_owned(),
+            Owner::Signer(Signer::new_starknet_random()),
+            Version::LATEST,
+        )
+        .await;
+    let recipient = felt!("0x18301129");
+
+    let erc20 = Erc20::new(*FEE_TOKEN_ADDRESS, &controller);
+    let call = erc20.transfer_getcall(&ContractAddress(recipient), &U256 { low: 5, high: 0 });
+
+    let report = controller.simulate(vec![call]).await.unwrap();
+    assert_eq!(report.status, SimulationStatus::Succeeded);
+    assert!(report
+        .events
+        .contains(&transfer_of(controller.address, recipient, 5)));
+    assert!(report
+        .events
+        .iter()
+        .any(|event| matches!(event, SimulatedEvent::Controller(_))));
+    assert_eq!(
+        report.balance_changes,
+        vec![BalanceChange::Decrease {
+            token: *FEE_TOKEN_ADDRESS,
+            amount: U256 { low: 5, high: 0 },
+        }]
+    );
+
+    // Simulating leaves the chain untouched
+    let balance = erc20
+        .balanceOf(&ContractAddress(recipient))
+        .call()
+        .await
+        .unwrap();
+    assert_eq!(balance, U256 { low: 0, high: 0 });
+
+    let overdraft = erc20.transfer_getcall(
+        &ContractAddress(recipient),
+        &U256 {
+            low: u128::MAX,
+            high: u128::MAX,
+        },
+    );
+    let report = controller.simulate(vec![overdraft]).await.unwrap();
+    assert!(matches!(report.status, SimulationStatus::Reverted { .. }));
+    assert!(report.balance_changes.is_empty());
+}
+
+fn transfer_of(from: Felt, to: Felt, low: u128) -> SimulatedEvent {
+    SimulatedEvent::Transfer {
+        token: *FEE_TOKEN_ADDRESS,
+        from,
+        to,
+        amount: U256 { low, high: 0 },
+    }
+}
@@ -34,6 +34,37 @@ impl FileSystemBackend {
         self.base_path.join(key)
     }
 
+    /// Adds the keys of every file under `dir` to `keys`. Keys are paths relative to the base
+    /// path, since keys such as selectors contain `/` and are stored in nested directories.
+    fn collect_keys(&self, dir: PathBuf, keys: &mut Vec<String>) -> Result<(), StorageError> {
+        for entry in fs::read_dir(&dir).map_err(|e| {
+            StorageError::OperationFailed(format!("Failed to read directory: {}", e))
+        })? {
+            let entry = entry.map_err(|e| {
+                StorageError::OperationFailed(format!("Failed to read directory entry: {}", e))
+            })?;
+            let file_type = entry.file_type().map_err(|e| {
+                StorageError::OperationFailed(format!("Failed to get file type: {}", e))
+            })?;
+
+            if file_type.is_dir() {
+                self.collect_keys(entry.path(), keys)?;
+            } else if file_type.is_file() {
+                let path = entry.path();
+                let relative = path.strip_prefix(&self.base_path).map_err(|e| {
+                    StorageError::OperationFailed(format!("Failed to resolve key: {}", e))
+                })?;
+                let key = relative
+                    .components()
+                    .filter_map(|component| component.as_os_str().to_str())
+                    .collect::<Vec<_>>()
+                    .join("/");
+                keys.push(key);
+            }
+        }
+        Ok(())
+    }
+
     fn ensure_path_exists(&self, path: PathBuf) -> Result<(), StorageError> {
         fs::create_dir_all(path).map_err(|e| {
             StorageError::OperationFailed(format!("Failed to create directory: {}", e))
@@ -73,7 +104,8 @@ impl StorageBackend for FileSystemBackend {
 
     fn remove(&mut self, key: &str) -> Result<(), StorageError> {
         let path = self.file_path(key);
-        if path.exists() {
+        // a key can also name the directory of the keys nested under it
+        if path.is_file() {
             fs::remove_file(&path).map_err(|e| {
                 StorageError::OperationFailed(format!("Failed to remove file: {}", e))
             })?;
@@ -94,24 +126,7 @@ impl StorageBackend for FileSystemBackend {
     fn keys(&self) -> Result<Vec<String>, StorageError> {
         let mut keys = Vec::new();
         if self.base_path.exists() {
-            for entry in fs::read_dir(&self.base_path).map_err(|e| {
-                StorageError::OperationFailed(format!("Failed to read directory: {}", e))
-            })? {
-                let entry = entry.map_err(|e| {
-                    StorageError::OperationFailed(format!("Failed to read directory entry: {}", e))
-                })?;
-                if entry
-                    .file_type()
-                    .map_err(|e| {
-                        StorageError::OperationFailed(format!("Failed to get file type: {}", e))
-                    })?
-                    .is_file()
-                {
-                    if let Some(file_name) = entry.file_name().to_str() {
-                        keys.push(file_name.to_string());
-                    }
-                }
-            }
+            self.collect_keys(self.base_path.clone(), &mut keys)?;
         }
         Ok(keys)
     }
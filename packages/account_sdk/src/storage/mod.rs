@@ -4,6 +4,7 @@ use starknet::{
     core::types::Felt,
     signers::{SigningKey, VerifyingKey},
 };
+use std::sync::{Arc, Mutex, MutexGuard};
 
 use crate::{
     account::session::{hash::Session, policy::Policy},
@@ -25,6 +26,10 @@ pub mod inmemory;
 pub mod localstorage;
 pub mod selectors;
 
+#[cfg(all(test, not(target_arch = "wasm32")))]
+#[path = "storage_test.rs"]
+mod storage_test;
+
 #[derive(Debug, thiserror::Error)]
 pub enum StorageError {
     #[error("Serialization error: {0}")]
@@ -33,6 +38,8 @@ pub enum StorageError {
     OperationFailed(String),
     #[error("Type mismatch in storage")]
     TypeMismatch,
+    #[error("No controller stored for address {0:#x} on chain {1:#x}")]
+    ControllerNotFound(Felt, Felt),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -67,6 +74,10 @@ pub struct ControllerMetadata {
     pub rpc_url: String,
     pub salt: Felt,
     pub owner: Owner,
+    /// Guids of the owners of the Controller, as changed through the SDK. Metadata stored
+    /// before the list existed only has the owner above.
+    #[serde(default)]
+    pub owners: Vec<Felt>,
     pub address: Felt,
     pub chain_id: Felt,
 }
@@ -82,6 +93,7 @@ impl From<&Controller> for ControllerMetadata {
             rpc_url: controller.rpc_url.to_string(),
             salt: controller.salt,
             owner: (&controller.owner).into(),
+            owners: vec![controller.owner_guid()],
             username: controller.username.clone(),
         }
     }
@@ -91,7 +103,7 @@ impl From<&crate::signers::Owner> for Owner {
     fn from(owner: &crate::signers::Owner) -> Self {
         match owner {
             crate::signers::Owner::Signer(signer) => Owner::Signer(signer.into()),
-            crate::signers::Owner::Account(address) => Owner::Account(*address),
+            crate::signers::Owner::Account(owner) => Owner::Account(owner.address()),
         }
     }
 }
@@ -163,7 +175,7 @@ impl TryFrom<Owner> for crate::signers::Owner {
     fn try_from(owner: Owner) -> Result<Self, Self::Error> {
         match owner {
             Owner::Signer(signer) => Ok(crate::signers::Owner::Signer(signer.try_into()?)),
-            Owner::Account(address) => Ok(crate::signers::Owner::Account(address)),
+            Owner::Account(address) => Ok(crate::signers::Owner::Account(address.into())),
         }
     }
 }
@@ -205,6 +217,10 @@ pub struct Credentials {
 #[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
 pub struct ActiveMetadata {
     address: Felt,
+    /// Missing from entries stored before Controllers were keyed by chain, which then point to
+    /// no Controller.
+    #[serde(default)]
+    chain_id: Felt,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -238,7 +254,10 @@ pub trait StorageBackend: Send + Sync {
         self.get(&selectors::Selectors::active(app_id))
             .and_then(|value| match value {
                 Some(StorageValue::Active(metadata)) => self
-                    .get(&selectors::Selectors::account(&metadata.address))
+                    .get(&selectors::Selectors::account(
+                        &metadata.address,
+                        &metadata.chain_id,
+                    ))
                     .and_then(|value| match value {
                         Some(StorageValue::Controller(metadata)) => Ok(Some(metadata)),
                         Some(_) => Err(StorageError::TypeMismatch),
@@ -255,15 +274,178 @@ pub trait StorageBackend: Send + Sync {
         address: Felt,
         metadata: ControllerMetadata,
     ) -> Result<(), StorageError> {
+        let chain_id = metadata.chain_id;
+        // The legacy entry would be a file where the chains of the address are nested
+        self.remove(&selectors::Selectors::legacy_account(&address))?;
         self.set(
             &selectors::Selectors::active(app_id),
-            &StorageValue::Active(ActiveMetadata { address }),
+            &StorageValue::Active(ActiveMetadata { address, chain_id }),
         )?;
         self.set(
-            &selectors::Selectors::account(&address),
+            &selectors::Selectors::account(&address, &chain_id),
             &StorageValue::Controller(metadata),
         )
     }
+
+    /// Every stored Controller, whichever app or chain it was used with, ordered by chain and
+    /// address. Values under account keys that aren't Controllers are skipped.
+    fn controllers(&self) -> Result<Vec<ControllerMetadata>, StorageError> {
+        let mut controllers = Vec::new();
+        for key in self.keys()? {
+            if !selectors::Selectors::is_account(&key) {
+                continue;
+            }
+
+            if let Some(StorageValue::Controller(metadata)) = self.get(&key)? {
+                controllers.push(metadata);
+            }
+        }
+
+        controllers.sort_by_key(|metadata| (metadata.chain_id, metadata.address));
+        Ok(controllers)
+    }
+
+    /// Makes the Controller stored at `address` on `chain_id` the active account of `app_id`.
+    fn set_active(
+        &mut self,
+        app_id: &str,
+        address: Felt,
+        chain_id: Felt,
+    ) -> Result<(), StorageError> {
+        match self.get(&selectors::Selectors::account(&address, &chain_id))? {
+            Some(StorageValue::Controller(_)) => self.set(
+                &selectors::Selectors::active(app_id),
+                &StorageValue::Active(ActiveMetadata { address, chain_id }),
+            ),
+            Some(_) => Err(StorageError::TypeMismatch),
+            None => Err(StorageError::ControllerNotFound(address, chain_id)),
+        }
+    }
+
+    /// Removes the Controller stored at `address` on `chain_id` with everything scoped to it,
+    /// and unsets it as the active account of the apps using it. Other Controllers, including
+    /// the one at the same address on another chain, are left untouched.
+    fn remove_controller(&mut self, address: Felt, chain_id: Felt) -> Result<(), StorageError> {
+        let keys = self.keys()?;
+        for key in &keys {
+            let remove = if selectors::Selectors::is_scoped_to(key, &address, &chain_id) {
+                true
+            } else if selectors::Selectors::is_active(key) {
+                matches!(
+                    self.get(key)?,
+                    Some(StorageValue::Active(active))
+                        if active.address == address && active.chain_id == chain_id
+                )
+            } else {
+                false
+            };
+
+            if remove {
+                self.remove(key)?;
+            }
+        }
+
+        // Data shared by all chains goes with the last Controller at the address
+        if !self
+            .keys()?
+            .iter()
+            .any(|key| selectors::Selectors::is_account_at(key, &address))
+        {
+            for key in keys
+                .iter()
+                .filter(|key| selectors::Selectors::is_shared_by(key, &address))
+            {
+                self.remove(key)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A handle to a [`StorageBackend`] that clones share, so that every copy of a Controller, and
+/// any number of Controllers, read and write the same data.
+#[derive(Clone)]
+pub struct SharedStorage {
+    inner: Arc<Mutex<dyn StorageBackend>>,
+}
+
+impl SharedStorage {
+    pub fn new<S>(backend: S) -> Self
+    where
+        S: StorageBackend + 'static,
+    {
+        Self {
+            inner: Arc::new(Mutex::new(backend)),
+        }
+    }
+
+    fn lock(&self) -> Result<MutexGuard<'_, dyn StorageBackend + 'static>, StorageError> {
+        self.inner
+            .lock()
+            .map_err(|e| StorageError::OperationFailed(e.to_string()))
+    }
+}
+
+impl Default for SharedStorage {
+    fn default() -> Self {
+        Self::new(Storage::default())
+    }
+}
+
+impl std::fmt::Debug for SharedStorage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SharedStorage").finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl StorageBackend for SharedStorage {
+    fn set(&mut self, key: &str, value: &StorageValue) -> Result<(), StorageError> {
+        self.lock()?.set(key, value)
+    }
+
+    fn get(&self, key: &str) -> Result<Option<StorageValue>, StorageError> {
+        self.lock()?.get(key)
+    }
+
+    fn remove(&mut self, key: &str) -> Result<(), StorageError> {
+        self.lock()?.remove(key)
+    }
+
+    fn clear(&mut self) -> Result<(), StorageError> {
+        self.lock()?.clear()
+    }
+
+    fn keys(&self) -> Result<Vec<String>, StorageError> {
+        self.lock()?.keys()
+    }
+
+    fn set_controller(
+        &mut self,
+        app_id: &str,
+        address: Felt,
+        metadata: ControllerMetadata,
+    ) -> Result<(), StorageError> {
+        // Both entries are written under the same lock
+        self.lock()?.set_controller(app_id, address, metadata)
+    }
+
+    fn controllers(&self) -> Result<Vec<ControllerMetadata>, StorageError> {
+        self.lock()?.controllers()
+    }
+
+    fn set_active(
+        &mut self,
+        app_id: &str,
+        address: Felt,
+        chain_id: Felt,
+    ) -> Result<(), StorageError> {
+        self.lock()?.set_active(app_id, address, chain_id)
+    }
+
+    fn remove_controller(&mut self, address: Felt, chain_id: Felt) -> Result<(), StorageError> {
+        self.lock()?.remove_controller(address, chain_id)
+    }
 }
 
 #[cfg(all(not(target_arch = "wasm32"), not(feature = "filestorage")))]
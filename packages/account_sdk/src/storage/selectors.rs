@@ -7,7 +7,12 @@ impl Selectors {
         format!("@cartridge/{}/active", app_id)
     }
 
-    pub fn account(address: &Felt) -> String {
+    pub fn account(address: &Felt, chain_id: &Felt) -> String {
+        format!("@cartridge/account/0x{:x}/0x{:x}", address, chain_id)
+    }
+
+    /// Key of the metadata of the account at `address` before it was keyed by chain.
+    pub fn legacy_account(address: &Felt) -> String {
         format!("@cartridge/account/0x{:x}", address)
     }
 
@@ -31,4 +36,34 @@ impl Selectors {
             chain_id
         )
     }
+
+    pub fn is_account(key: &str) -> bool {
+        key.starts_with("@cartridge/account/")
+    }
+
+    /// Whether `key` holds the metadata of the account at `address`, on any chain.
+    pub fn is_account_at(key: &str, address: &Felt) -> bool {
+        key.starts_with(&format!("@cartridge/account/0x{:x}/", address))
+    }
+
+    pub fn is_active(key: &str) -> bool {
+        key.starts_with("@cartridge/") && key.ends_with("/active")
+    }
+
+    /// Whether `key` holds data of the account at `address` on `chain_id`, such as its metadata
+    /// or sessions.
+    pub fn is_scoped_to(key: &str, address: &Felt, chain_id: &Felt) -> bool {
+        if key == Self::account(address, chain_id) || key == Self::deployment(address, chain_id) {
+            return true;
+        }
+
+        key.starts_with(&format!("@cartridge/session/0x{:x}/", address))
+            && key.ends_with(&format!("/0x{:x}", chain_id))
+    }
+
+    /// Whether `key` holds data of the account at `address` shared by all chains, such as its
+    /// admin origins.
+    pub fn is_shared_by(key: &str, address: &Felt) -> bool {
+        key.starts_with(&format!("@cartridge/admin/0x{:x}/", address))
+    }
 }
@@ -0,0 +1,161 @@
+use starknet::core::types::Felt;
+use starknet::macros::felt;
+use url::Url;
+
+use crate::account::session::policy::Policy;
+use crate::artifacts::{Version, CONTROLLERS};
+use crate::controller::{Controller, ControllerBuilder};
+use crate::signers::{Owner, Signer};
+
+use super::inmemory::InMemoryBackend;
+use super::selectors::Selectors;
+use super::{ActiveMetadata, SharedStorage, StorageBackend, StorageError, StorageValue};
+
+const SN_SEPOLIA: Felt = felt!("0x534e5f5345504f4c4941");
+const SN_MAIN: Felt = felt!("0x534e5f4d41494e");
+
+fn controller(storage: &SharedStorage, app_id: &str, address: Felt) -> Controller {
+    controller_on(storage, app_id, address, SN_SEPOLIA)
+}
+
+fn controller_on(
+    storage: &SharedStorage,
+    app_id: &str,
+    address: Felt,
+    chain_id: Felt,
+) -> Controller {
+    ControllerBuilder::new(
+        app_id.to_string(),
+        "username".to_string(),
+        CONTROLLERS[&Version::LATEST].hash,
+        Url::parse("http://localhost:1/").unwrap(),
+        Owner::Signer(Signer::new_starknet_random()),
+        address,
+        chain_id,
+    )
+    .with_storage(storage.clone())
+    .build()
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_controller_registry() {
+    let mut storage = SharedStorage::new(InMemoryBackend::new());
+    let mut first = controller(&storage, "app", felt!("0x1"));
+    let mut second = controller(&storage, "app", felt!("0x2"));
+    controller(&storage, "other_app", felt!("0x1"));
+
+    let policies = vec![Policy::new_call(Felt::ONE, felt!("0x2"))];
+    first
+        .create_session(policies.clone(), u64::MAX)
+        .await
+        .unwrap();
+    second
+        .create_session(policies.clone(), u64::MAX)
+        .await
+        .unwrap();
+
+    let addresses = |storage: &SharedStorage| {
+        storage
+            .controllers()
+            .unwrap()
+            .iter()
+            .map(|metadata| metadata.address)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(addresses(&storage), vec![felt!("0x1"), felt!("0x2")]);
+
+    // The last stored Controller is active until switched
+    assert_eq!(
+        storage.controller("app").unwrap().unwrap().address,
+        felt!("0x2")
+    );
+    storage.set_active("app", felt!("0x1"), SN_SEPOLIA).unwrap();
+    assert_eq!(
+        storage.controller("app").unwrap().unwrap().address,
+        felt!("0x1")
+    );
+    assert!(matches!(
+        storage.set_active("app", felt!("0x3"), SN_SEPOLIA),
+        Err(StorageError::ControllerNotFound(..))
+    ));
+
+    storage.remove_controller(felt!("0x1"), SN_SEPOLIA).unwrap();
+    assert_eq!(addresses(&storage), vec![felt!("0x2")]);
+    assert!(storage.controller("app").unwrap().is_none());
+    assert!(storage.controller("other_app").unwrap().is_none());
+    assert!(first.session_metadata(&policies, None).is_none());
+    assert!(second.session_metadata(&policies, None).is_some());
+}
+
+#[tokio::test]
+async fn test_controller_registry_across_chains() {
+    let mut storage = SharedStorage::new(InMemoryBackend::new());
+    let mut sepolia = controller_on(&storage, "app", felt!("0x1"), SN_SEPOLIA);
+    let mut mainnet = controller_on(&storage, "app", felt!("0x1"), SN_MAIN);
+
+    let policies = vec![Policy::new_call(Felt::ONE, felt!("0x2"))];
+    sepolia
+        .create_session(policies.clone(), u64::MAX)
+        .await
+        .unwrap();
+    mainnet
+        .create_session(policies.clone(), u64::MAX)
+        .await
+        .unwrap();
+
+    // A value that isn't a Controller under an account key is skipped
+    storage
+        .set(
+            "@cartridge/account/0x4",
+            &StorageValue::Active(ActiveMetadata::default()),
+        )
+        .unwrap();
+
+    let chains = |storage: &SharedStorage| {
+        storage
+            .controllers()
+            .unwrap()
+            .iter()
+            .map(|metadata| (metadata.address, metadata.chain_id))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        chains(&storage),
+        vec![(felt!("0x1"), SN_MAIN), (felt!("0x1"), SN_SEPOLIA)]
+    );
+
+    storage.set_active("app", felt!("0x1"), SN_SEPOLIA).unwrap();
+    assert_eq!(
+        storage.controller("app").unwrap().unwrap().chain_id,
+        SN_SEPOLIA
+    );
+
+    // Only the Controller on the given chain goes, with its sessions
+    storage.remove_controller(felt!("0x1"), SN_MAIN).unwrap();
+    assert_eq!(chains(&storage), vec![(felt!("0x1"), SN_SEPOLIA)]);
+    assert!(storage.controller("app").unwrap().is_some());
+    assert!(sepolia.session_metadata(&policies, None).is_some());
+    assert!(mainnet.session_metadata(&policies, None).is_none());
+    assert!(storage
+        .get(&Selectors::account(&felt!("0x1"), &SN_SEPOLIA))
+        .unwrap()
+        .is_some());
+}
+
+#[cfg(feature = "filestorage")]
+#[test]
+fn test_filesystem_keys_are_nested() {
+    use super::filestorage::FileSystemBackend;
+
+    let dir = tempfile::tempdir().unwrap();
+    let mut storage = FileSystemBackend::new(dir.path().to_path_buf());
+    storage
+        .set(
+            &Selectors::active("app"),
+            &StorageValue::Active(ActiveMetadata::default()),
+        )
+        .unwrap();
+
+    assert_eq!(storage.keys().unwrap(), vec![Selectors::active("app")]);
+}
@@ -8,7 +8,6 @@ use starknet::{
 };
 use starknet_crypto::Felt;
 
-use crate::artifacts::{Version, CONTROLLERS};
 use crate::provider::CartridgeJsonRpcProvider;
 
 use super::pending::PendingTransaction;
@@ -39,16 +38,6 @@ impl<'a> AccountDeclaration<'a> {
             client,
         }
     }
-    pub fn cartridge_account(client: &'a CartridgeJsonRpcProvider, version: Version) -> Self
-    where
-        &'a CartridgeJsonRpcProvider: Provider,
-    {
-        let contract_class = CONTROLLERS[&version];
-
-        let contract_artifact: SierraClass = serde_json::from_str(contract_class.content).unwrap();
-
-        Self::new(contract_artifact, contract_class.casm_hash, client)
-    }
     pub fn erc_20(client: &'a CartridgeJsonRpcProvider) -> Self
     where
         &'a CartridgeJsonRpcProvider: Provider,
@@ -8,8 +8,6 @@ mod pending;
 pub use declare::AccountDeclaration;
 
 lazy_static! {
-    pub static ref UDC_ADDRESS: Felt =
-        felt!("0x041a78e741e5af2fec34b695679bc6891742439f7afb8484ecd7766661ad02bf");
     pub static ref FEE_TOKEN_ADDRESS: Felt =
         felt!("0x049d36570d4e46f48e99674bd3fcc84644ddd6b96f7c741b1562b82f9e004dc7");
     pub static ref ERC20_CONTRACT_CLASS_HASH: Felt =
@@ -1,7 +1,13 @@
+use std::sync::Arc;
+
+use starknet::accounts::{AccountError, ConnectedAccount};
+use starknet::core::types::contract::legacy::LegacyContractClass;
+use starknet::core::types::Felt;
+
 use crate::account::session::policy::Policy;
 use crate::account::DECLARATION_SELECTOR;
 use crate::artifacts::Version;
-use crate::signers::{Owner, Signer};
+use crate::signers::{Owner, SignError, Signer};
 use crate::tests::account::AccountDeclaration;
 
 use crate::tests::runners::katana::KatanaRunner;
@@ -53,3 +59,67 @@ async fn test_declare_with_session() {
         .wait_for_completion()
         .await;
 }
+
+// An empty Cairo 0 class, the signer rejects it before it's ever sent
+fn legacy_class() -> Arc<LegacyContractClass> {
+    let class = serde_json::json!({
+        "abi": [],
+        "entry_points_by_type": { "CONSTRUCTOR": [], "EXTERNAL": [], "L1_HANDLER": [] },
+        "program": {
+            "builtins": [],
+            "data": [],
+            "debug_info": null,
+            "hints": {},
+            "identifiers": {},
+            "main_scope": "__main__",
+            "prime": "0x800000000000011000000000000000000000000000000000000000000000001",
+            "reference_manager": { "references": [] }
+        }
+    });
+    Arc::new(serde_json::from_value(class).unwrap())
+}
+
+async fn assert_legacy_declaration_unsupported<A>(account: &A)
+where
+    A: ConnectedAccount<SignError = SignError> + Sync,
+{
+    let error = account
+        .declare_legacy(legacy_class())
+        .nonce(Felt::ZERO)
+        .max_fee(Felt::ONE)
+        .send()
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(
+            error,
+            AccountError::Signing(SignError::LegacyDeclarationUnsupported)
+        ),
+        "unexpected error {error:?}"
+    );
+}
+
+#[tokio::test]
+async fn test_declare_legacy_unsupported() {
+    let signer = Signer::new_starknet_random();
+    let runner = KatanaRunner::load();
+    let mut controller = runner
+        .deploy_controller(
+            "username".to_owned(),
+            Owner::Signer(signer),
+            Version::LATEST,
+        )
+        .await;
+
+    assert_legacy_declaration_unsupported(&controller).await;
+
+    let session_account = controller
+        .create_session(
+            vec![Policy::new_call(controller.address, DECLARATION_SELECTOR)],
+            u64::MAX,
+        )
+        .await
+        .unwrap();
+
+    assert_legacy_declaration_unsupported(&session_account).await;
+}
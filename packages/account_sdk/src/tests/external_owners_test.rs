@@ -95,7 +95,7 @@ async fn test_verify_constructor_external_owner() {
     let controller = runner
         .deploy_controller(
             "external_owner".to_string(),
-            Owner::Account(external_account.address()),
+            Owner::Account(external_account.address().into()),
             Version::LATEST,
         )
         .await;
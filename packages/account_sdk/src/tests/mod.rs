@@ -7,11 +7,10 @@ use starknet::{
 use starknet_crypto::Felt;
 use thiserror::Error;
 
-use crate::tests::transaction_waiter::{TransactionWaiter, TransactionWaitingError};
+use crate::transaction_waiter::{TransactionWaiter, TransactionWaitingError};
 
 pub(crate) mod account;
 pub(crate) mod runners;
-pub(crate) mod transaction_waiter;
 
 mod declare_test;
 mod delegate_account_test;
@@ -18,10 +18,8 @@ use crate::{
     artifacts::{Version, CONTROLLERS},
     controller::Controller,
     signers::{webauthn::WebauthnSigner, Owner, Signer},
-    tests::{
-        account::FEE_TOKEN_ADDRESS, runners::katana::KatanaRunner,
-        transaction_waiter::TransactionWaiter,
-    },
+    tests::{account::FEE_TOKEN_ADDRESS, runners::katana::KatanaRunner},
+    transaction_waiter::TransactionWaiter,
 };
 
 pub async fn test_verify_paymaster_execute(signer: Signer, use_session: bool) {
@@ -166,7 +164,8 @@ async fn test_verify_execute_paymaster_should_fail() {
         Owner::Signer(Signer::new_starknet_random()),
         controller.address(),
         runner.client().chain_id().await.unwrap(),
-    );
+    )
+    .unwrap();
 
     let outside_execution = wrong_account
         .sign_outside_execution(OutsideExecution::V3(outside_execution.clone()))
@@ -322,7 +322,8 @@ async fn test_change_owner_invalidate_old_sessions() {
         Owner::Signer(new_signer.clone()),
         controller.address(),
         runner.client().chain_id().await.unwrap(),
-    );
+    )
+    .unwrap();
 
     let session_account = controller
         .create_session(vec![transfer_method], u64::MAX)
@@ -1,7 +1,7 @@
 use cainome::cairo_serde::{ContractAddress, U256};
 use starknet::accounts::{AccountFactory, ExecutionEncoding, SingleOwnerAccount};
 use starknet::contract::ContractFactory;
-use starknet::core::types::{BlockId, BlockTag, DeclareTransactionResult};
+use starknet::core::types::{BlockId, BlockTag};
 use starknet::core::utils::cairo_short_string_to_felt;
 use starknet::providers::jsonrpc::HttpTransport;
 use starknet::providers::{JsonRpcClient, Provider};
@@ -16,12 +16,14 @@ use lazy_static::lazy_static;
 
 use crate::abigen::erc_20::Erc20;
 use crate::artifacts::{Version, CONTROLLERS};
+use crate::constants::UDC_ADDRESS;
 use crate::controller::Controller;
+use crate::declaration::declare_controller_classes;
 use crate::factory::ControllerFactory;
 use crate::provider::CartridgeJsonRpcProvider;
 use crate::signers::Owner;
-use crate::tests::account::{AccountDeclaration, FEE_TOKEN_ADDRESS, UDC_ADDRESS};
-use crate::tests::transaction_waiter::TransactionWaiter;
+use crate::tests::account::FEE_TOKEN_ADDRESS;
+use crate::transaction_waiter::TransactionWaiter;
 
 use super::cartridge::CartridgeProxy;
 use super::{find_free_port, SubprocessRunner, TestnetConfig};
@@ -110,8 +112,12 @@ impl KatanaRunner {
     }
 
     pub async fn fund(&self, address: &Felt) {
+        self.fund_token(*FEE_TOKEN_ADDRESS, address).await
+    }
+
+    pub async fn fund_token(&self, token: Felt, address: &Felt) {
         let prefunded = self.executor().await;
-        let erc20_prefunded = Erc20::new(*FEE_TOKEN_ADDRESS, prefunded);
+        let erc20_prefunded = Erc20::new(token, prefunded);
 
         let tx = erc20_prefunded
             .transfer(
@@ -132,17 +138,11 @@ impl KatanaRunner {
     }
 
     pub async fn declare_controller(&self, version: Version) -> Felt {
-        let prefunded = self.executor().await;
-
-        let DeclareTransactionResult { class_hash, .. } =
-            AccountDeclaration::cartridge_account(self.client(), version)
-                .declare(&prefunded)
-                .await
-                .unwrap()
-                .wait_for_completion()
-                .await;
+        let declared = declare_controller_classes(&self.executor().await, &[version])
+            .await
+            .unwrap();
 
-        class_hash
+        declared[0].class_hash
     }
 
     pub async fn deploy_controller(
@@ -159,7 +159,7 @@ impl KatanaRunner {
 
         let salt = cairo_short_string_to_felt(&username).unwrap();
 
-        let contract_factory = ContractFactory::new_with_udc(class_hash, prefunded, *UDC_ADDRESS);
+        let contract_factory = ContractFactory::new_with_udc(class_hash, prefunded, UDC_ADDRESS);
         let factory = ControllerFactory::new(
             class_hash,
             self.chain_id,
@@ -190,6 +190,7 @@ impl KatanaRunner {
             address,
             self.chain_id,
         )
+        .unwrap()
     }
 }
 
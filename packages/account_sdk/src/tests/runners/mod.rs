@@ -6,6 +6,7 @@ use self::waiter::OutputWaiter;
 
 pub mod cartridge;
 pub mod katana;
+pub mod stub;
 pub mod waiter;
 
 #[derive(Debug)]
@@ -0,0 +1,119 @@
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, HeaderMap, Request, Response, Server, StatusCode};
+use serde_json::{json, Value};
+use tokio::task::JoinHandle;
+use url::Url;
+
+use super::find_free_port;
+
+/// A local JSON-RPC server answering every request with `handler`.
+///
+/// The handler receives the index of the request, counting from zero, and its JSON body. It is
+/// meant to stand in for a node in tests that need precise control over the responses, such as
+/// failures or rate limiting.
+pub struct StubServer {
+    pub url: Url,
+    requests: Arc<AtomicUsize>,
+    headers: Arc<Mutex<Vec<HeaderMap>>>,
+    handle: JoinHandle<()>,
+}
+
+impl StubServer {
+    pub fn start<H>(handler: H) -> Self
+    where
+        H: Fn(usize, &Value) -> Response<Body> + Send + Sync + 'static,
+    {
+        let addr = SocketAddr::from(([127, 0, 0, 1], find_free_port()));
+        let requests = Arc::new(AtomicUsize::new(0));
+        let headers = Arc::new(Mutex::new(Vec::new()));
+        let handler = Arc::new(handler);
+
+        let counter = requests.clone();
+        let received_headers = headers.clone();
+        let make_svc = make_service_fn(move |_conn| {
+            let counter = counter.clone();
+            let received_headers = received_headers.clone();
+            let handler = handler.clone();
+            async move {
+                Ok::<_, hyper::Error>(service_fn(move |req: Request<Body>| {
+                    let counter = counter.clone();
+                    let received_headers = received_headers.clone();
+                    let handler = handler.clone();
+                    async move {
+                        let index = counter.fetch_add(1, Ordering::SeqCst);
+                        received_headers.lock().unwrap().push(req.headers().clone());
+                        let body = hyper::body::to_bytes(req.into_body()).await?;
+                        let body: Value = serde_json::from_slice(&body).unwrap_or(Value::Null);
+                        Ok::<_, hyper::Error>(handler(index, &body))
+                    }
+                }))
+            }
+        });
+
+        let server = Server::bind(&addr).serve(make_svc);
+        let handle = tokio::spawn(async move {
+            if let Err(e) = server.await {
+                eprintln!("stub server error: {}", e);
+            }
+        });
+
+        Self {
+            url: Url::parse(&format!("http://{}/", addr)).unwrap(),
+            requests,
+            headers,
+            handle,
+        }
+    }
+
+    /// Number of requests received so far.
+    pub fn requests(&self) -> usize {
+        self.requests.load(Ordering::SeqCst)
+    }
+
+    /// Headers of every request received so far, in order.
+    pub fn headers(&self) -> Vec<HeaderMap> {
+        self.headers.lock().unwrap().clone()
+    }
+}
+
+impl Drop for StubServer {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+pub fn json_response(body: Value) -> Response<Body> {
+    Response::builder()
+        .header("Content-Type", "application/json")
+        .body(Body::from(serde_json::to_vec(&body).unwrap()))
+        .unwrap()
+}
+
+pub fn result_response(request: &Value, result: Value) -> Response<Body> {
+    json_response(json!({
+        "jsonrpc": "2.0",
+        "id": request["id"],
+        "result": result,
+    }))
+}
+
+pub fn error_response(request: &Value, code: i64, message: &str) -> Response<Body> {
+    json_response(json!({
+        "jsonrpc": "2.0",
+        "id": request["id"],
+        "error": { "code": code, "message": message },
+    }))
+}
+
+pub fn status_response(status: StatusCode) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .body(Body::from(
+            status.canonical_reason().unwrap_or_default().to_string(),
+        ))
+        .unwrap()
+}
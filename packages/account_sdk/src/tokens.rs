@@ -0,0 +1,187 @@
+use cainome::cairo_serde::{self, ByteArray, CairoSerde, ContractAddress, U256};
+use primitive_types::U256 as BigUint256;
+use starknet::core::types::requests::CallRequest;
+use starknet::core::types::{BlockId, BlockTag, Felt, FunctionCall};
+use starknet::core::utils::parse_cairo_short_string;
+use starknet::macros::selector;
+use starknet::providers::{Provider, ProviderError, ProviderRequestData, ProviderResponseData};
+
+use crate::{abigen::erc_20::Erc20, controller::Controller, errors::ControllerError};
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+#[path = "tokens_test.rs"]
+mod tokens_test;
+
+/// Balance of a token held by a Controller, with what is needed to display it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TokenBalance {
+    pub token: Felt,
+    pub symbol: String,
+    pub decimals: u8,
+    pub balance: U256,
+}
+
+impl TokenBalance {
+    /// The balance in whole tokens, e.g. `1.5` for 1.5 ETH.
+    pub fn formatted(&self) -> String {
+        format_units(self.balance, self.decimals)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ParseUnitsError {
+    #[error("Invalid decimal amount: {0}")]
+    InvalidAmount(String),
+    #[error("Amount has more than {0} decimals")]
+    TooManyDecimals(u8),
+    #[error("Amount doesn't fit in a u256")]
+    Overflow,
+}
+
+impl Controller {
+    /// Balance of the Controller in `token`, an ERC20 contract.
+    pub async fn token_balance(&self, token: Felt) -> Result<U256, ControllerError> {
+        Erc20::new(token, self)
+            .balanceOf(&ContractAddress(self.address))
+            .block_id(BlockId::Tag(BlockTag::Pending))
+            .call()
+            .await
+            .map_err(ControllerError::CairoSerde)
+    }
+
+    /// Balances of the Controller in every token of `tokens`, along with their symbol and
+    /// decimals, fetched in a single batch request.
+    pub async fn portfolio(&self, tokens: &[Felt]) -> Result<Vec<TokenBalance>, ControllerError> {
+        let requests = tokens
+            .iter()
+            .flat_map(|token| {
+                [
+                    (selector!("balanceOf"), vec![self.address]),
+                    (selector!("decimals"), vec![]),
+                    (selector!("symbol"), vec![]),
+                ]
+                .map(|(entry_point_selector, calldata)| {
+                    ProviderRequestData::Call(CallRequest {
+                        request: FunctionCall {
+                            contract_address: *token,
+                            entry_point_selector,
+                            calldata,
+                        },
+                        block_id: BlockId::Tag(BlockTag::Pending),
+                    })
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let responses = self.provider.batch_requests(&requests).await?;
+        if responses.len() != requests.len() {
+            return Err(ProviderError::ArrayLengthMismatch.into());
+        }
+
+        let mut results = responses.into_iter().map(|response| match response {
+            ProviderResponseData::Call(result) => Ok(result),
+            _ => Err(ControllerError::from(ProviderError::ArrayLengthMismatch)),
+        });
+
+        let mut portfolio = Vec::with_capacity(tokens.len());
+        for token in tokens {
+            let balance = U256::cairo_deserialize(&results.next().unwrap()?, 0)?;
+            let decimals = u8::cairo_deserialize(&results.next().unwrap()?, 0)?;
+            let symbol = decode_symbol(&results.next().unwrap()?)?;
+
+            portfolio.push(TokenBalance {
+                token: *token,
+                symbol,
+                decimals,
+                balance,
+            });
+        }
+
+        Ok(portfolio)
+    }
+}
+
+/// Decodes a token symbol, returned as a short string by legacy tokens and as a `ByteArray` by
+/// newer ones.
+fn decode_symbol(result: &[Felt]) -> Result<String, cairo_serde::Error> {
+    if let [short_string] = result {
+        return parse_cairo_short_string(short_string)
+            .map_err(|e| cairo_serde::Error::Deserialize(e.to_string()));
+    }
+
+    ByteArray::cairo_deserialize(result, 0)?
+        .to_string()
+        .map_err(|e| cairo_serde::Error::Deserialize(e.to_string()))
+}
+
+/// Formats `amount`, in the smallest unit of a token with `decimals` decimals, as a decimal
+/// number of whole tokens. Trailing zeros of the fractional part are dropped.
+pub fn format_units(amount: U256, decimals: u8) -> String {
+    let amount = to_big(amount);
+    let digits = amount.to_string();
+    let decimals = usize::from(decimals);
+
+    let (integer, fraction) = if digits.len() > decimals {
+        digits.split_at(digits.len() - decimals)
+    } else {
+        ("0", digits.as_str())
+    };
+    let fraction = format!("{:0>width$}", fraction, width = decimals);
+    let fraction = fraction.trim_end_matches('0');
+
+    if fraction.is_empty() {
+        integer.to_string()
+    } else {
+        format!("{}.{}", integer, fraction)
+    }
+}
+
+/// Parses a decimal number of whole tokens, such as `"1.5"`, into the smallest unit of a token
+/// with `decimals` decimals.
+pub fn parse_units(value: &str, decimals: u8) -> Result<U256, ParseUnitsError> {
+    let invalid = || ParseUnitsError::InvalidAmount(value.to_string());
+
+    let (integer, fraction) = value.trim().split_once('.').unwrap_or((value.trim(), ""));
+    if integer.is_empty() && fraction.is_empty() {
+        return Err(invalid());
+    }
+    if !integer
+        .chars()
+        .chain(fraction.chars())
+        .all(|c| c.is_ascii_digit())
+    {
+        return Err(invalid());
+    }
+
+    // Zeros past the token's precision don't change the amount
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.len() > usize::from(decimals) {
+        return Err(ParseUnitsError::TooManyDecimals(decimals));
+    }
+
+    let digits = format!(
+        "{}{:0<width$}",
+        integer,
+        fraction,
+        width = usize::from(decimals)
+    );
+    let digits = digits.trim_start_matches('0');
+    if digits.is_empty() {
+        return Ok(U256 { low: 0, high: 0 });
+    }
+
+    BigUint256::from_dec_str(digits)
+        .map(from_big)
+        .map_err(|_| ParseUnitsError::Overflow)
+}
+
+pub(crate) fn to_big(value: U256) -> BigUint256 {
+    (BigUint256::from(value.high) << 128) | BigUint256::from(value.low)
+}
+
+pub(crate) fn from_big(value: BigUint256) -> U256 {
+    U256 {
+        low: value.low_u128(),
+        high: (value >> 128).low_u128(),
+    }
+}
@@ -0,0 +1,146 @@
+use cainome::cairo_serde::U256;
+use serde_json::{json, Value};
+use starknet::core::types::Felt;
+use starknet::macros::{felt, selector};
+
+use crate::artifacts::{Version, CONTROLLERS};
+use crate::controller::ControllerBuilder;
+use crate::signers::{Owner, Signer};
+use crate::tests::runners::stub::{json_response, StubServer};
+
+use super::{format_units, parse_units, ParseUnitsError, TokenBalance};
+
+const ETH: Felt = felt!("0xe7");
+const STRK: Felt = felt!("0x57");
+
+fn u256(low: u128, high: u128) -> U256 {
+    U256 { low, high }
+}
+
+#[test]
+fn test_format_units() {
+    assert_eq!(format_units(u256(0, 0), 18), "0");
+    assert_eq!(format_units(u256(1_500_000_000_000_000_000, 0), 18), "1.5");
+    assert_eq!(format_units(u256(1, 0), 18), "0.000000000000000001");
+    assert_eq!(format_units(u256(1_000_000, 0), 6), "1");
+    assert_eq!(format_units(u256(42, 0), 0), "42");
+    assert_eq!(
+        format_units(u256(0, 1), 0),
+        "340282366920938463463374607431768211456"
+    );
+}
+
+#[test]
+fn test_parse_units() {
+    assert_eq!(
+        parse_units("1.5", 18),
+        Ok(u256(1_500_000_000_000_000_000, 0))
+    );
+    assert_eq!(parse_units("0.000001", 6), Ok(u256(1, 0)));
+    assert_eq!(parse_units(".5", 1), Ok(u256(5, 0)));
+    assert_eq!(parse_units("2.", 2), Ok(u256(200, 0)));
+    assert_eq!(parse_units("1.50", 1), Ok(u256(15, 0)));
+    assert_eq!(parse_units("0", 18), Ok(u256(0, 0)));
+    assert_eq!(
+        parse_units("340282366920938463463374607431768211456", 0),
+        Ok(u256(0, 1))
+    );
+
+    assert_eq!(
+        parse_units("1.05", 1),
+        Err(ParseUnitsError::TooManyDecimals(1))
+    );
+    assert!(matches!(
+        parse_units("1,5", 18),
+        Err(ParseUnitsError::InvalidAmount(_))
+    ));
+    assert!(matches!(
+        parse_units(".", 18),
+        Err(ParseUnitsError::InvalidAmount(_))
+    ));
+    assert!(matches!(
+        parse_units("-1", 18),
+        Err(ParseUnitsError::InvalidAmount(_))
+    ));
+    assert_eq!(
+        parse_units(&"9".repeat(80), 0),
+        Err(ParseUnitsError::Overflow)
+    );
+
+    let amount = u256(123_456_789, 7);
+    assert_eq!(parse_units(&format_units(amount, 18), 18), Ok(amount));
+}
+
+/// Answers a batch of `starknet_call` as an ETH-like token returning its symbol as a short
+/// string, and a STRK-like token returning it as a `ByteArray`.
+fn call_result(request: &Value) -> Value {
+    let call = &request["params"]["request"];
+    let token = Felt::from_hex(call["contract_address"].as_str().unwrap()).unwrap();
+    let selector = Felt::from_hex(call["entry_point_selector"].as_str().unwrap()).unwrap();
+
+    let results = [
+        (
+            ETH,
+            selector!("balanceOf"),
+            json!(["0x14d1120d7b160000", "0x0"]),
+        ),
+        (ETH, selector!("decimals"), json!(["0x12"])),
+        (ETH, selector!("symbol"), json!(["0x455448"])),
+        (STRK, selector!("balanceOf"), json!(["0x0", "0x1"])),
+        (STRK, selector!("decimals"), json!(["0x12"])),
+        (
+            STRK,
+            selector!("symbol"),
+            json!(["0x0", "0x5354524b", "0x4"]),
+        ),
+    ];
+    let (_, _, result) = results
+        .into_iter()
+        .find(|(t, s, _)| *t == token && *s == selector)
+        .unwrap_or_else(|| panic!("Unexpected call: {}", call));
+
+    json!({ "jsonrpc": "2.0", "id": request["id"], "result": result })
+}
+
+#[tokio::test]
+async fn test_portfolio() {
+    let server = StubServer::start(|_, request| {
+        let requests = request.as_array().expect("Expected a batch request");
+        json_response(Value::Array(requests.iter().map(call_result).collect()))
+    });
+
+    let controller = ControllerBuilder::new(
+        "app_id".to_string(),
+        "username".to_string(),
+        CONTROLLERS[&Version::LATEST].hash,
+        server.url.clone(),
+        Owner::Signer(Signer::new_starknet_random()),
+        felt!("0xdead"),
+        felt!("0x534e5f5345504f4c4941"),
+    )
+    .with_persistence(false)
+    .build()
+    .unwrap();
+
+    let portfolio = controller.portfolio(&[ETH, STRK]).await.unwrap();
+
+    assert_eq!(server.requests(), 1);
+    assert_eq!(
+        portfolio,
+        vec![
+            TokenBalance {
+                token: ETH,
+                symbol: "ETH".to_string(),
+                decimals: 18,
+                balance: u256(1_500_000_000_000_000_000, 0),
+            },
+            TokenBalance {
+                token: STRK,
+                symbol: "STRK".to_string(),
+                decimals: 18,
+                balance: u256(0, 1),
+            },
+        ]
+    );
+    assert_eq!(portfolio[0].formatted(), "1.5");
+}
@@ -7,6 +7,8 @@ use starknet::core::types::{
 };
 use starknet::providers::{Provider, ProviderError};
 
+use crate::utils::time::sleep;
+
 #[derive(Debug, thiserror::Error)]
 pub enum TransactionWaitingError {
     #[error("request timed out")]
@@ -17,6 +19,24 @@ pub enum TransactionWaitingError {
     Provider(ProviderError),
 }
 
+/// How long and for which finality status to wait for a transaction, as in
+/// [`Controller::execute_and_wait_with_options`](crate::controller::Controller::execute_and_wait_with_options).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WaitOptions {
+    pub finality: TransactionFinalityStatus,
+    pub timeout: Duration,
+}
+
+impl Default for WaitOptions {
+    /// Acceptance on L2 within 60 seconds.
+    fn default() -> Self {
+        Self {
+            finality: TransactionFinalityStatus::AcceptedOnL2,
+            timeout: Duration::from_secs(60),
+        }
+    }
+}
+
 /// A type that waits for a transaction to achieve the desired status. The waiter will poll for the
 /// transaction receipt every `interval` miliseconds until it achieves the desired status or until
 /// `timeout` is reached.
@@ -28,7 +48,7 @@ pub enum TransactionWaitingError {
 /// # Examples
 ///
 /// ```ignore
-/// ues url::Url;
+/// use url::Url;
 /// use starknet::providers::jsonrpc::HttpTransport;
 /// use starknet::providers::JsonRpcClient;
 /// use starknet::core::types::TransactionFinalityStatus;
@@ -36,7 +56,7 @@ pub enum TransactionWaitingError {
 /// let provider = JsonRpcClient::new(HttpTransport::new(Url::parse("http://localhost:5000").unwrap()));
 ///
 /// let tx_hash = Felt::from(0xbadbeefu64);
-/// let receipt = TransactionWaiter::new(tx_hash, &provider).with_finality(TransactionFinalityStatus::ACCEPTED_ON_L2).await.unwrap();
+/// let receipt = TransactionWaiter::new(tx_hash, &provider).with_finality(TransactionFinalityStatus::AcceptedOnL2).wait().await.unwrap();
 /// ```
 #[must_use = "TransactionWaiter does nothing unless polled"]
 pub struct TransactionWaiter<'a, P: Provider> {
@@ -56,55 +76,16 @@ pub struct TransactionWaiter<'a, P: Provider> {
     /// return an error.
     must_succeed: bool,
     /// Poll the transaction every `interval` miliseconds. Miliseconds are used so that
-    /// we can be more precise with the polling interval. Defaults to 250ms.
+    /// we can be more precise with the polling interval. Defaults to 2500ms.
     interval: Duration,
     /// The maximum amount of time to wait for the transaction to achieve the desired status. An
     /// error will be returned if it is unable to finish within the `timeout` duration. Defaults to
-    /// 60 seconds.
+    /// 10 seconds.
     timeout: Duration,
     /// The provider to use for polling the transaction.
     provider: &'a P,
 }
 
-enum Sleeper {}
-
-impl Sleeper {
-    pub async fn sleep(delay: Duration) {
-        #[cfg(not(target_arch = "wasm32"))]
-        {
-            tokio::time::sleep(delay).await;
-        }
-        #[cfg(target_arch = "wasm32")]
-        {
-            use wasm_bindgen::JsCast;
-            use web_sys::WorkerGlobalScope;
-            let mut cb = |resolve: js_sys::Function, _reject: js_sys::Function| {
-                // if we are in a worker, use the global worker's scope
-                // otherwise, use the window's scope
-                if let Ok(worker_scope) = js_sys::global().dyn_into::<WorkerGlobalScope>() {
-                    worker_scope
-                        .set_timeout_with_callback_and_timeout_and_arguments_0(
-                            &resolve,
-                            delay.as_millis() as i32,
-                        )
-                        .expect("should register `setTimeout`");
-                } else {
-                    web_sys::window()
-                        .unwrap()
-                        .set_timeout_with_callback_and_timeout_and_arguments_0(
-                            &resolve,
-                            delay.as_millis() as i32,
-                        )
-                        .expect("should register `setTimeout`");
-                }
-            };
-            let p = js_sys::Promise::new(&mut cb);
-            wasm_bindgen_futures::JsFuture::from(p).await.unwrap();
-        }
-    }
-}
-
-#[allow(dead_code)]
 impl<'a, P> TransactionWaiter<'a, P>
 where
     P: Provider + Send + Sync,
@@ -141,18 +122,24 @@ where
         Self { timeout, ..self }
     }
 
+    pub fn with_must_succeed(self, must_succeed: bool) -> Self {
+        Self {
+            must_succeed,
+            ..self
+        }
+    }
+
     pub async fn wait(self) -> Result<TransactionReceiptWithBlockInfo, TransactionWaitingError> {
         let timeout = self.timeout;
         select! {
             result = self.wait_without_timeout().fuse() => result,
-            _ = Sleeper::sleep(timeout).fuse() => Err(TransactionWaitingError::Timeout),
+            _ = sleep(timeout).fuse() => Err(TransactionWaitingError::Timeout),
         }
     }
     async fn wait_without_timeout(
         self,
     ) -> Result<TransactionReceiptWithBlockInfo, TransactionWaitingError> {
         loop {
-            let now = std::time::Instant::now();
             let transaction = self.provider.get_transaction_receipt(self.tx_hash).await;
             match transaction {
                 Ok(receipt) => match &receipt.block {
@@ -199,7 +186,7 @@ where
                     return Err(TransactionWaitingError::Provider(e));
                 }
             }
-            Sleeper::sleep(self.interval.checked_sub(now.elapsed()).unwrap_or_default()).await;
+            sleep(self.interval).await;
         }
     }
 }
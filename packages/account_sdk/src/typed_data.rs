@@ -6,11 +6,13 @@ use std::str::FromStr;
 use crate::signers::SignError as Error;
 use cainome::cairo_serde::ByteArray;
 use indexmap::IndexMap;
-use serde::{Deserialize, Serialize};
+use primitive_types::U256 as BigUint256;
+use serde::{Deserialize, Deserializer, Serialize};
 use serde_json::Number;
+use starknet::core::crypto::compute_hash_on_elements;
 use starknet::core::types::Felt;
 use starknet::core::utils::{cairo_short_string_to_felt, get_selector_from_name};
-use starknet_crypto::poseidon_hash_many;
+use starknet_crypto::{pedersen_hash, poseidon_hash, poseidon_hash_many};
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct SimpleField {
@@ -32,6 +34,22 @@ pub enum Field {
     SimpleType(SimpleField),
 }
 
+impl Field {
+    pub fn name(&self) -> &str {
+        match self {
+            Field::ParentType(parent_field) => &parent_field.name,
+            Field::SimpleType(simple_field) => &simple_field.name,
+        }
+    }
+
+    pub fn r#type(&self) -> &str {
+        match self {
+            Field::ParentType(parent_field) => &parent_field.r#type,
+            Field::SimpleType(simple_field) => &simple_field.r#type,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum PrimitiveType {
@@ -102,6 +120,10 @@ fn get_fields(name: &str, types: &IndexMap<String, Vec<Field>>) -> Result<Vec<Fi
         return Ok(fields.clone());
     }
 
+    if let Some(fields) = get_preset_types().get(name) {
+        return Ok(fields.clone());
+    }
+
     Err(Error::InvalidMessageError(format!(
         "Type {} not found",
         name
@@ -111,6 +133,7 @@ fn get_fields(name: &str, types: &IndexMap<String, Vec<Field>>) -> Result<Vec<Fi
 fn get_dependencies(
     name: &str,
     types: &IndexMap<String, Vec<Field>>,
+    revision: Revision,
     dependencies: &mut Vec<String>,
 ) -> Result<(), Error> {
     if dependencies.contains(&name.to_string()) {
@@ -119,31 +142,59 @@ fn get_dependencies(
 
     dependencies.push(name.to_string());
 
+    // revision 1 encodes the preset types used like any other dependency
+    let preset_types = get_preset_types();
+    let is_dependency = |r#type: &str| {
+        types.contains_key(r#type)
+            || (revision == Revision::V1 && preset_types.contains_key(r#type))
+    };
+
     for field in get_fields(name, types)? {
-        let mut field_type = match field {
-            Field::SimpleType(simple_field) => simple_field.r#type.clone(),
-            Field::ParentType(parent_field) => parent_field.contains.clone(),
+        let field_types = match &field {
+            // only enums depend on the type they contain, merkle trees are hashed by leaf
+            Field::ParentType(parent_field)
+                if revision == Revision::V1 && parent_field.r#type == "enum" =>
+            {
+                vec![parent_field.contains.clone()]
+            }
+            Field::ParentType(parent_field) => vec![parent_field.r#type.clone()],
+            // enum variants depend on the types of their parameters
+            Field::SimpleType(simple_field) if revision == Revision::V1 => {
+                variant_types(&simple_field.r#type)
+                    .unwrap_or_else(|| vec![simple_field.r#type.as_str()])
+                    .into_iter()
+                    .map(str::to_string)
+                    .collect()
+            }
+            Field::SimpleType(simple_field) => vec![simple_field.r#type.clone()],
         };
 
-        field_type = field_type.trim_end_matches('*').to_string();
-
-        if types.contains_key(&field_type) && !dependencies.contains(&field_type) {
-            get_dependencies(&field_type, types, dependencies)?;
+        for field_type in field_types {
+            let field_type = field_type.trim_end_matches('*');
+            if is_dependency(field_type) {
+                get_dependencies(field_type, types, revision, dependencies)?;
+            }
         }
     }
 
     Ok(())
 }
 
+// Parameter types of an enum variant, e.g. `(u128,u128*)`
+fn variant_types(r#type: &str) -> Option<Vec<&str>> {
+    let inner = r#type.strip_prefix('(')?.strip_suffix(')')?;
+    Some(inner.split(',').filter(|t| !t.is_empty()).collect())
+}
+
 pub fn encode_type(name: &str, types: &IndexMap<String, Vec<Field>>) -> Result<String, Error> {
     let mut type_hash = String::new();
 
     // get dependencies
     let mut dependencies: Vec<String> = Vec::new();
-    get_dependencies(name, types, &mut dependencies)?;
+    get_dependencies(name, types, Revision::V1, &mut dependencies)?;
 
-    // sort dependencies
-    dependencies.sort_by_key(|dep| dep.to_lowercase());
+    // the primary type comes first, followed by the sorted dependencies
+    dependencies[1..].sort();
 
     for dep in dependencies {
         type_hash += &format!("\"{}\"", dep);
@@ -173,10 +224,13 @@ pub fn encode_type(name: &str, types: &IndexMap<String, Vec<Field>>) -> Result<S
                             &format!("\"{}\":\"{}\"", simple_field.name, simple_field.r#type);
                     }
                 }
-                Field::ParentType(parent_field) => {
+                Field::ParentType(parent_field) if parent_field.r#type == "enum" => {
                     type_hash +=
                         &format!("\"{}\":\"{}\"", parent_field.name, parent_field.contains);
                 }
+                Field::ParentType(parent_field) => {
+                    type_hash += &format!("\"{}\":\"{}\"", parent_field.name, parent_field.r#type);
+                }
             }
 
             if idx < fields.len() - 1 {
@@ -190,65 +244,186 @@ pub fn encode_type(name: &str, types: &IndexMap<String, Vec<Field>>) -> Result<S
     Ok(type_hash)
 }
 
-#[derive(Debug, Default)]
-pub struct Ctx {
-    pub base_type: String,
-    pub parent_type: String,
-    pub is_preset: bool,
+/// Encodes the type `name` with the revision 0 rules: the primary type comes first, followed by
+/// its dependencies in alphabetical order, and nothing is quoted.
+pub fn encode_type_v0(name: &str, types: &IndexMap<String, Vec<Field>>) -> Result<String, Error> {
+    let mut dependencies: Vec<String> = Vec::new();
+    get_dependencies(name, types, Revision::V0, &mut dependencies)?;
+
+    // the primary type is always the first dependency found
+    dependencies[1..].sort();
+
+    let mut encoded = String::new();
+    for dep in dependencies {
+        let fields = get_fields(&dep, types)?
+            .iter()
+            .map(|field| format!("{}:{}", field.name(), field.r#type()))
+            .collect::<Vec<_>>()
+            .join(",");
+
+        encoded += &format!("{}({})", dep, fields);
+    }
+
+    Ok(encoded)
 }
 
-pub(crate) struct FieldInfo {
-    _name: String,
-    r#type: String,
-    base_type: String,
-    index: usize,
+/// Hashes `value` of type `type` with the revision 0 rules, where structs and arrays are hashed
+/// with Pedersen over their elements and their length.
+fn encode_value_v0(
+    value: &PrimitiveType,
+    r#type: &str,
+    types: &IndexMap<String, Vec<Field>>,
+) -> Result<Felt, Error> {
+    if types.contains_key(r#type) {
+        let PrimitiveType::Object(obj) = value else {
+            return Err(Error::InvalidMessageError(format!(
+                "Value of type {} must be an object",
+                r#type
+            )));
+        };
+        return encode_struct_v0(obj, r#type, types);
+    }
+
+    if let Some(element_type) = r#type.strip_suffix('*') {
+        let PrimitiveType::Array(array) = value else {
+            return Err(Error::InvalidMessageError(format!(
+                "Value of type {} must be an array",
+                r#type
+            )));
+        };
+        let hashes = array
+            .iter()
+            .map(|element| encode_value_v0(element, element_type, types))
+            .collect::<Result<Vec<_>, _>>()?;
+        return Ok(compute_hash_on_elements(&hashes));
+    }
+
+    match value {
+        PrimitiveType::Bool(boolean) => Ok(Felt::from(*boolean as u32)),
+        PrimitiveType::Number(number) => Felt::from_str(&number.to_string())
+            .map_err(|_| Error::InvalidMessageError(format!("Invalid number {}", number))),
+        PrimitiveType::String(string) => match r#type {
+            // a selector can be given already hashed
+            "selector" if string.starts_with("0x") => get_hex(string),
+            "selector" => get_selector_from_name(string)
+                .map_err(|e| Error::InvalidMessageError(format!("Invalid selector: {}", e))),
+            // strings are short strings in revision 0
+            _ => get_hex(string),
+        },
+        PrimitiveType::Object(_) | PrimitiveType::Array(_) => Err(Error::InvalidMessageError(
+            format!("Invalid value for type {}", r#type),
+        )),
+    }
 }
 
-pub(crate) fn get_value_type(
-    name: &str,
+fn encode_struct_v0(
+    obj: &IndexMap<String, PrimitiveType>,
+    r#type: &str,
     types: &IndexMap<String, Vec<Field>>,
-) -> Result<FieldInfo, Error> {
-    // iter both "types" and "preset_types" to find the field
-    for (idx, (key, value)) in types.iter().enumerate() {
-        if key == name {
-            return Ok(FieldInfo {
-                _name: name.to_string(),
-                r#type: key.clone(),
-                base_type: "".to_string(),
-                index: idx,
-            });
-        }
+) -> Result<Felt, Error> {
+    let type_hash = get_selector_from_name(&encode_type_v0(r#type, types)?).map_err(|e| {
+        Error::InvalidMessageError(format!("Invalid type {} for selector: {}", r#type, e))
+    })?;
+
+    let fields = get_fields(r#type, types)?;
+    if let Some(name) = obj
+        .keys()
+        .find(|name| !fields.iter().any(|field| field.name() == name.as_str()))
+    {
+        return Err(Error::InvalidMessageError(format!(
+            "Unexpected field {} of type {}",
+            name, r#type
+        )));
+    }
 
-        for (idx, field) in value.iter().enumerate() {
-            match field {
-                Field::SimpleType(simple_field) => {
-                    if simple_field.name == name {
-                        return Ok(FieldInfo {
-                            _name: name.to_string(),
-                            r#type: simple_field.r#type.clone(),
-                            base_type: "".to_string(),
-                            index: idx,
-                        });
-                    }
-                }
-                Field::ParentType(parent_field) => {
-                    if parent_field.name == name {
-                        return Ok(FieldInfo {
-                            _name: name.to_string(),
-                            r#type: parent_field.contains.clone(),
-                            base_type: parent_field.r#type.clone(),
-                            index: idx,
-                        });
-                    }
-                }
+    let mut hashes = vec![type_hash];
+
+    // fields are hashed in the order of the type, not of the message
+    for field in fields {
+        let value = obj.get(field.name()).ok_or_else(|| {
+            Error::InvalidMessageError(format!("Missing field {} of type {}", field.name(), r#type))
+        })?;
+
+        let hash = match &field {
+            Field::ParentType(parent_field) if parent_field.r#type == "merkletree" => {
+                let PrimitiveType::Array(leaves) = value else {
+                    return Err(Error::InvalidMessageError(format!(
+                        "Merkle tree {} must be an array",
+                        field.name()
+                    )));
+                };
+                let leaves = leaves
+                    .iter()
+                    .map(|leaf| encode_value_v0(leaf, &parent_field.contains, types))
+                    .collect::<Result<Vec<_>, _>>()?;
+                merkle_root(leaves, |a, b| pedersen_hash(&a, &b)).ok_or_else(|| {
+                    Error::InvalidMessageError(format!(
+                        "Merkle tree {} must have leaves",
+                        field.name()
+                    ))
+                })?
             }
+            _ => encode_value_v0(value, field.r#type(), types)?,
+        };
+        hashes.push(hash);
+    }
+
+    Ok(compute_hash_on_elements(&hashes))
+}
+
+/// Root of the merkle tree over `leaves`, where each pair of nodes is hashed in ascending order
+/// and an odd node is paired with zero. `None` when there are no leaves.
+pub fn merkle_root(mut nodes: Vec<Felt>, hash: impl Fn(Felt, Felt) -> Felt) -> Option<Felt> {
+    if nodes.is_empty() {
+        return None;
+    }
+
+    while nodes.len() > 1 {
+        nodes = nodes
+            .chunks(2)
+            .map(|pair| {
+                let (left, right) = (pair[0], pair.get(1).copied().unwrap_or(Felt::ZERO));
+                if left < right {
+                    hash(left, right)
+                } else {
+                    hash(right, left)
+                }
+            })
+            .collect();
+    }
+
+    Some(nodes[0])
+}
+
+#[derive(Debug, Default)]
+pub struct Ctx {
+    pub base_type: String,
+    pub parent_type: String,
+    pub is_preset: bool,
+    /// Path of the value being encoded from the root of the message, for errors.
+    pub path: Vec<String>,
+}
+
+impl Ctx {
+    fn error(&self, message: impl std::fmt::Display) -> Error {
+        if self.path.is_empty() {
+            Error::InvalidMessageError(message.to_string())
+        } else {
+            Error::InvalidMessageError(format!("{}: {}", self.path.join("."), message))
         }
     }
 
-    Err(Error::InvalidMessageError(format!(
-        "Field {} not found in types",
-        name
-    )))
+    // Runs `f` with `segment` appended to the path
+    fn nested<T>(
+        &mut self,
+        segment: impl ToString,
+        f: impl FnOnce(&mut Self) -> Result<T, Error>,
+    ) -> Result<T, Error> {
+        self.path.push(segment.to_string());
+        let result = f(self);
+        self.path.pop();
+        result
+    }
 }
 
 fn get_hex(value: &str) -> Result<Felt, Error> {
@@ -261,6 +436,13 @@ fn get_hex(value: &str) -> Result<Felt, Error> {
     }
 }
 
+// Whether `felt` fits in an unsigned integer of `bits` bits
+fn fits_in(felt: Felt, bits: usize) -> bool {
+    felt.to_bytes_be()[..32 - bits / 8]
+        .iter()
+        .all(|byte| *byte == 0)
+}
+
 impl PrimitiveType {
     pub fn encode(
         &self,
@@ -269,139 +451,345 @@ impl PrimitiveType {
         preset_types: &IndexMap<String, Vec<Field>>,
         ctx: &mut Ctx,
     ) -> Result<Felt, Error> {
-        match self {
-            PrimitiveType::Object(obj) => {
-                ctx.is_preset = preset_types.contains_key(r#type);
-
-                let mut hashes = Vec::new();
-
-                if ctx.base_type == "enum" {
-                    let (variant_name, value) = obj.first().ok_or_else(|| {
-                        Error::InvalidMessageError("Enum value must be populated".to_string())
-                    })?;
-                    let variant_type = get_value_type(variant_name, types)?;
-
-                    let arr: &Vec<PrimitiveType> = match value {
-                        PrimitiveType::Array(arr) => arr,
-                        _ => {
-                            return Err(Error::InvalidMessageError(
-                                "Enum value must be an array".to_string(),
-                            ));
-                        }
-                    };
-
-                    // variant index
-                    hashes.push(Felt::from(variant_type.index as u32));
-
-                    // variant parameters
-                    for (idx, param) in arr.iter().enumerate() {
-                        let field_type = &variant_type
-                            .r#type
-                            .trim_start_matches('(')
-                            .trim_end_matches(')')
-                            .split(',')
-                            .nth(idx)
-                            .ok_or_else(|| {
-                                Error::InvalidMessageError("Invalid enum variant type".to_string())
-                            })?;
+        // enums and merkle trees are declared by the field holding them
+        match std::mem::take(&mut ctx.base_type).as_str() {
+            "enum" => return self.encode_enum(r#type, types, preset_types, ctx),
+            "merkletree" => return self.encode_merkle_tree(r#type, types, preset_types, ctx),
+            _ => {}
+        }
+
+        if let Some(element_type) = r#type.strip_suffix('*') {
+            let PrimitiveType::Array(array) = self else {
+                return Err(ctx.error(format!("expected an array of {}", element_type)));
+            };
+            let hashes = array
+                .iter()
+                .enumerate()
+                .map(|(idx, element)| {
+                    ctx.nested(idx, |ctx| {
+                        element.encode(element_type, types, preset_types, ctx)
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            return Ok(poseidon_hash_many(hashes.as_slice()));
+        }
+
+        if r#type == "u256" {
+            if let PrimitiveType::String(_) | PrimitiveType::Number(_) = self {
+                return self
+                    .to_u256_object(ctx)?
+                    .encode(r#type, types, preset_types, ctx);
+            }
+        }
+
+        if preset_types.contains_key(r#type) || types.contains_key(r#type) {
+            return self.encode_struct(r#type, types, preset_types, ctx);
+        }
+
+        self.encode_basic(r#type, ctx)
+    }
+
+    fn encode_struct(
+        &self,
+        r#type: &str,
+        types: &IndexMap<String, Vec<Field>>,
+        preset_types: &IndexMap<String, Vec<Field>>,
+        ctx: &mut Ctx,
+    ) -> Result<Felt, Error> {
+        let PrimitiveType::Object(obj) = self else {
+            return Err(ctx.error(format!("expected an object of type {}", r#type)));
+        };
+
+        let is_preset = preset_types.contains_key(r#type);
+        let struct_types = if is_preset { preset_types } else { types };
+        let fields = get_fields(r#type, struct_types)?;
 
-                        let field_hash = param.encode(field_type, types, preset_types, ctx)?;
-                        hashes.push(field_hash);
+        if let Some(name) = obj
+            .keys()
+            .find(|name| !fields.iter().any(|field| field.name() == name.as_str()))
+        {
+            return ctx.nested(name, |ctx| {
+                Err(ctx.error(format!("unexpected field of type {}", r#type)))
+            });
+        }
+
+        let type_hash = encode_type(r#type, struct_types)?;
+        let mut hashes = vec![get_selector_from_name(&type_hash)
+            .map_err(|e| ctx.error(format!("Invalid type {} for selector: {}", r#type, e)))?];
+
+        // fields are hashed in the order of the type, not of the message
+        for field in fields {
+            let hash = ctx.nested(field.name(), |ctx| {
+                let value = obj
+                    .get(field.name())
+                    .ok_or_else(|| ctx.error(format!("missing field of type {}", r#type)))?;
+
+                ctx.is_preset = is_preset;
+                ctx.parent_type = r#type.to_string();
+                let field_type = match &field {
+                    Field::SimpleType(simple_field) => &simple_field.r#type,
+                    Field::ParentType(parent_field) => {
+                        ctx.base_type = parent_field.r#type.clone();
+                        &parent_field.contains
                     }
+                };
 
-                    return Ok(poseidon_hash_many(hashes.as_slice()));
-                }
+                value.encode(field_type, types, preset_types, ctx)
+            })?;
+            hashes.push(hash);
+        }
 
-                let type_hash =
-                    encode_type(r#type, if ctx.is_preset { preset_types } else { types })?;
-                hashes.push(get_selector_from_name(&type_hash).map_err(|e| {
-                    Error::InvalidMessageError(format!(
-                        "Invalid type {} for selector: {}",
-                        r#type, e
-                    ))
+        Ok(poseidon_hash_many(hashes.as_slice()))
+    }
+
+    fn encode_enum(
+        &self,
+        r#type: &str,
+        types: &IndexMap<String, Vec<Field>>,
+        preset_types: &IndexMap<String, Vec<Field>>,
+        ctx: &mut Ctx,
+    ) -> Result<Felt, Error> {
+        let obj = match self {
+            PrimitiveType::Object(obj) if obj.len() == 1 => obj,
+            _ => return Err(ctx.error("enum value must be an object with a single variant")),
+        };
+        let (variant_name, value) = obj.first().unwrap();
+
+        let variants = get_fields(r#type, types)?;
+        let (index, variant) = variants
+            .iter()
+            .enumerate()
+            .find(|(_, variant)| variant.name() == variant_name)
+            .ok_or_else(|| ctx.error(format!("unknown variant {} of {}", variant_name, r#type)))?;
+
+        ctx.nested(variant_name, |ctx| {
+            let param_types = variant_types(variant.r#type())
+                .ok_or_else(|| ctx.error(format!("invalid variant type {}", variant.r#type())))?;
+            let params = match value {
+                PrimitiveType::Array(params) => params,
+                _ => return Err(ctx.error("enum value must be an array")),
+            };
+            if params.len() != param_types.len() {
+                return Err(ctx.error(format!(
+                    "expected {} parameters, got {}",
+                    param_types.len(),
+                    params.len()
+                )));
+            }
+
+            // variant index, followed by its parameters
+            let mut hashes = vec![Felt::from(index)];
+            for (idx, (param, param_type)) in params.iter().zip(param_types).enumerate() {
+                hashes.push(ctx.nested(idx, |ctx| {
+                    param.encode(param_type, types, preset_types, ctx)
                 })?);
+            }
+
+            Ok(poseidon_hash_many(hashes.as_slice()))
+        })
+    }
 
-                for (field_name, value) in obj {
-                    // recheck if we're currently in a preset type
-                    ctx.is_preset = preset_types.contains_key(r#type);
-
-                    // pass correct types - preset or types
-                    let field_type = get_value_type(
-                        field_name,
-                        if ctx.is_preset { preset_types } else { types },
-                    )?;
-                    ctx.base_type = field_type.base_type;
-                    ctx.parent_type = r#type.to_string();
-                    let field_hash =
-                        value.encode(field_type.r#type.as_str(), types, preset_types, ctx)?;
-                    hashes.push(field_hash);
+    fn encode_merkle_tree(
+        &self,
+        r#type: &str,
+        types: &IndexMap<String, Vec<Field>>,
+        preset_types: &IndexMap<String, Vec<Field>>,
+        ctx: &mut Ctx,
+    ) -> Result<Felt, Error> {
+        let PrimitiveType::Array(leaves) = self else {
+            return Err(ctx.error("merkle tree must be an array of its leaves"));
+        };
+
+        let leaves = leaves
+            .iter()
+            .enumerate()
+            .map(|(idx, leaf)| ctx.nested(idx, |ctx| leaf.encode(r#type, types, preset_types, ctx)))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        merkle_root(leaves, poseidon_hash).ok_or_else(|| ctx.error("merkle tree has no leaves"))
+    }
+
+    // u256 given as a single number, split into its preset `low` and `high` fields
+    fn to_u256_object(&self, ctx: &Ctx) -> Result<PrimitiveType, Error> {
+        let value = match self {
+            PrimitiveType::String(string) => match string.strip_prefix("0x") {
+                Some(hex) => BigUint256::from_str_radix(hex, 16).ok(),
+                None => BigUint256::from_dec_str(string).ok(),
+            },
+            PrimitiveType::Number(number) => number.as_u64().map(BigUint256::from),
+            _ => None,
+        }
+        .ok_or_else(|| ctx.error("invalid u256"))?;
+
+        let low = value.low_u128();
+        let high = (value >> 128).low_u128();
+
+        Ok(PrimitiveType::Object(IndexMap::from([
+            (
+                "low".to_string(),
+                PrimitiveType::String(format!("{:#x}", low)),
+            ),
+            (
+                "high".to_string(),
+                PrimitiveType::String(format!("{:#x}", high)),
+            ),
+        ])))
+    }
+
+    fn encode_basic(&self, r#type: &str, ctx: &Ctx) -> Result<Felt, Error> {
+        match (r#type, self) {
+            ("bool", PrimitiveType::Bool(boolean)) => Ok(Felt::from(*boolean as u32)),
+            ("bool", PrimitiveType::String(string)) => match string.as_str() {
+                "true" => Ok(Felt::ONE),
+                "false" => Ok(Felt::ZERO),
+                _ => Err(ctx.error(format!("invalid bool {}", string))),
+            },
+            ("bool", PrimitiveType::Number(number)) => match number.as_u64() {
+                Some(value @ (0 | 1)) => Ok(Felt::from(value)),
+                _ => Err(ctx.error(format!("invalid bool {}", number))),
+            },
+            ("string", PrimitiveType::String(string)) => {
+                // split the string into short strings and encode
+                let byte_array = ByteArray::from_string(string)
+                    .map_err(|e| ctx.error(format!("Invalid string for bytearray: {}", e)))?;
+
+                let mut hashes = vec![Felt::from(byte_array.data.len())];
+
+                for hash in byte_array.data {
+                    hashes.push(hash.felt());
                 }
 
+                hashes.push(byte_array.pending_word);
+                hashes.push(Felt::from(byte_array.pending_word_len));
+
                 Ok(poseidon_hash_many(hashes.as_slice()))
             }
-            PrimitiveType::Array(array) => Ok(poseidon_hash_many(
-                array
-                    .iter()
-                    .map(|x| x.encode(r#type.trim_end_matches('*'), types, preset_types, ctx))
-                    .collect::<Result<Vec<_>, _>>()?
-                    .as_slice(),
-            )),
-            PrimitiveType::Bool(boolean) => {
-                let v = if *boolean {
-                    Felt::from(1_u32)
-                } else {
-                    Felt::from(0_u32)
-                };
-                Ok(v)
+            // a selector can be given already hashed
+            ("selector", PrimitiveType::String(string)) if !string.starts_with("0x") => {
+                get_selector_from_name(string)
+                    .map_err(|e| ctx.error(format!("Invalid selector: {}", e)))
             }
-            PrimitiveType::String(string) => match r#type {
-                "shortstring" => get_hex(string),
-                "string" => {
-                    // split the string into short strings and encode
-                    let byte_array = ByteArray::from_string(string).map_err(|e| {
-                        Error::InvalidMessageError(format!("Invalid string for bytearray: {}", e))
-                    })?;
-
-                    let mut hashes = vec![Felt::from(byte_array.data.len())];
-
-                    for hash in byte_array.data {
-                        hashes.push(hash.felt());
-                    }
+            ("i128", _) => self.encode_i128(ctx),
+            ("u128", _) => self.encode_unsigned(128, ctx),
+            ("timestamp", _) => self.encode_unsigned(64, ctx),
+            ("felt" | "shortstring" | "selector" | "ContractAddress" | "ClassHash", _) => {
+                self.encode_felt(ctx)
+            }
+            (
+                "bool" | "string" | "felt" | "shortstring" | "selector" | "ContractAddress"
+                | "ClassHash",
+                _,
+            ) => Err(ctx.error(format!("invalid value for type {}", r#type))),
+            _ => Err(ctx.error(format!("unknown type {}", r#type))),
+        }
+    }
 
-                    hashes.push(byte_array.pending_word);
-                    hashes.push(Felt::from(byte_array.pending_word_len));
+    fn encode_felt(&self, ctx: &Ctx) -> Result<Felt, Error> {
+        match self {
+            PrimitiveType::String(string) => get_hex(string).map_err(|e| ctx.error(e)),
+            PrimitiveType::Number(number) => Felt::from_str(&number.to_string())
+                .map_err(|_| ctx.error(format!("Invalid number {}", number))),
+            _ => Err(ctx.error("expected a felt")),
+        }
+    }
 
-                    Ok(poseidon_hash_many(hashes.as_slice()))
-                }
-                "selector" => get_selector_from_name(string)
-                    .map_err(|e| Error::InvalidMessageError(format!("Invalid selector: {}", e))),
-                "felt" => get_hex(string),
-                "ContractAddress" => get_hex(string),
-                "ClassHash" => get_hex(string),
-                "timestamp" => get_hex(string),
-                "u128" => get_hex(string),
-                "i128" => get_hex(string),
-                _ => Err(Error::InvalidMessageError(format!(
-                    "Invalid type {} for string",
-                    r#type
-                ))),
-            },
+    fn encode_unsigned(&self, bits: usize, ctx: &Ctx) -> Result<Felt, Error> {
+        let felt = match self {
+            // unlike felts, integers can't be short strings
+            PrimitiveType::String(string) => Felt::from_str(string).ok(),
+            PrimitiveType::Number(number) => Felt::from_str(&number.to_string()).ok(),
+            _ => None,
+        }
+        .ok_or_else(|| ctx.error(format!("expected a u{}", bits)))?;
+
+        if !fits_in(felt, bits) {
+            return Err(ctx.error(format!("{:#x} overflows a u{}", felt, bits)));
+        }
+        Ok(felt)
+    }
+
+    fn encode_i128(&self, ctx: &Ctx) -> Result<Felt, Error> {
+        let value = match self {
+            PrimitiveType::String(string) => string.strip_prefix('-').map_or_else(
+                || Felt::from_str(string).map(|felt| (false, felt)),
+                |magnitude| Felt::from_str(magnitude).map(|felt| (true, felt)),
+            ),
             PrimitiveType::Number(number) => {
-                let felt = Felt::from_str(&number.to_string()).map_err(|_| {
-                    Error::InvalidMessageError(format!("Invalid number {}", number))
-                })?;
-                Ok(felt)
+                let number = number.to_string();
+                match number.strip_prefix('-') {
+                    Some(magnitude) => Felt::from_str(magnitude).map(|felt| (true, felt)),
+                    None => Felt::from_str(&number).map(|felt| (false, felt)),
+                }
             }
+            _ => return Err(ctx.error("expected an i128")),
+        };
+        let (negative, magnitude) = value.map_err(|_| ctx.error("expected an i128"))?;
+
+        // i128 ranges from -2^127 to 2^127 - 1
+        let bound = Felt::TWO.pow(127_u32);
+        if magnitude > bound || (!negative && magnitude == bound) {
+            return Err(ctx.error("i128 out of range"));
         }
+
+        // negative values wrap around the field prime, as in Cairo
+        Ok(if negative { -magnitude } else { magnitude })
     }
 }
 
+/// Revision of the SNIP-12 standard a typed data follows.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Revision {
+    /// Legacy revision, hashed with Pedersen under the `StarkNetDomain` type.
+    V0,
+    /// Current revision, hashed with Poseidon under the `StarknetDomain` type.
+    V1,
+}
+
+impl Revision {
+    fn domain_type(&self) -> &'static str {
+        match self {
+            Revision::V0 => "StarkNetDomain",
+            Revision::V1 => "StarknetDomain",
+        }
+    }
+}
+
+// Revision 0 domains often give the chain id and version as JSON numbers
+fn string_or_number<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum StringOrNumber {
+        String(String),
+        Number(Number),
+    }
+
+    Ok(match StringOrNumber::deserialize(deserializer)? {
+        StringOrNumber::String(string) => string,
+        StringOrNumber::Number(number) => number.to_string(),
+    })
+}
+
+fn optional_string_or_number<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    struct Wrapper(#[serde(deserialize_with = "string_or_number")] String);
+
+    Ok(Option::<Wrapper>::deserialize(deserializer)?.map(|Wrapper(value)| value))
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Domain {
     pub name: String,
+    #[serde(deserialize_with = "string_or_number")]
     pub version: String,
-    #[serde(rename = "chainId")]
+    #[serde(rename = "chainId", deserialize_with = "string_or_number")]
     pub chain_id: String,
+    #[serde(default, deserialize_with = "optional_string_or_number")]
     pub revision: Option<String>,
 }
 
@@ -415,7 +803,30 @@ impl Domain {
         }
     }
 
+    /// Revision of the domain. Without an explicit revision, it is the one whose domain type
+    /// is declared in `types`, defaulting to revision 1.
+    pub fn revision(&self, types: &IndexMap<String, Vec<Field>>) -> Result<Revision, Error> {
+        match self.revision.as_deref() {
+            Some("1") => Ok(Revision::V1),
+            Some("0") => Ok(Revision::V0),
+            Some(revision) => Err(Error::InvalidMessageError(format!(
+                "Unsupported revision {}",
+                revision
+            ))),
+            None if types.contains_key(Revision::V0.domain_type())
+                && !types.contains_key(Revision::V1.domain_type()) =>
+            {
+                Ok(Revision::V0)
+            }
+            None => Ok(Revision::V1),
+        }
+    }
+
     pub fn encode(&self, types: &IndexMap<String, Vec<Field>>) -> Result<Felt, Error> {
+        if self.revision(types)? == Revision::V0 {
+            return self.encode_v0(types);
+        }
+
         let mut object = IndexMap::new();
 
         object.insert("name".to_string(), PrimitiveType::String(self.name.clone()));
@@ -439,9 +850,38 @@ impl Domain {
             "StarknetDomain",
             types,
             &IndexMap::new(),
-            &mut Default::default(),
+            &mut Ctx {
+                path: vec!["domain".to_string()],
+                ..Default::default()
+            },
         )
     }
+
+    fn encode_v0(&self, types: &IndexMap<String, Vec<Field>>) -> Result<Felt, Error> {
+        let mut object = IndexMap::new();
+
+        object.insert("name".to_string(), PrimitiveType::String(self.name.clone()));
+        object.insert(
+            "version".to_string(),
+            PrimitiveType::String(self.version.clone()),
+        );
+        object.insert(
+            "chainId".to_string(),
+            PrimitiveType::String(self.chain_id.clone()),
+        );
+        // a legacy domain may be given a revision its type doesn't declare
+        let declares_revision = get_fields(Revision::V0.domain_type(), types)?
+            .iter()
+            .any(|field| field.name() == "revision");
+        if let (Some(revision), true) = (&self.revision, declares_revision) {
+            object.insert(
+                "revision".to_string(),
+                PrimitiveType::String(revision.clone()),
+            );
+        }
+
+        encode_struct_v0(&object, Revision::V0.domain_type(), types)
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -468,17 +908,35 @@ impl TypedData {
         }
     }
 
+    /// Revision of the typed data, see [`Domain::revision`].
+    pub fn revision(&self) -> Result<Revision, Error> {
+        self.domain.revision(&self.types)
+    }
+
+    /// Checks the domain and message against `types`: every field must be given, with a value
+    /// of its type, and no other field may be. Errors point to the offending value, e.g.
+    /// `message.posts.1.title: missing field of type Post`.
+    pub fn validate(&self) -> Result<(), Error> {
+        self.encode(Felt::ZERO).map(|_| ())
+    }
+
     pub fn encode(&self, account: Felt) -> Result<Felt, Error> {
         let preset_types = get_preset_types();
 
-        if self.domain.revision.clone().unwrap_or("1".to_string()) != "1" {
-            return Err(Error::InvalidMessageError(
-                "Legacy revision 0 is not supported".to_string(),
-            ));
-        }
-
         let prefix_message = cairo_short_string_to_felt("StarkNet Message").unwrap();
 
+        if self.revision()? == Revision::V0 {
+            let domain_hash = self.domain.encode(&self.types)?;
+            let message_hash = encode_struct_v0(&self.message, &self.primary_type, &self.types)?;
+
+            return Ok(compute_hash_on_elements(&[
+                prefix_message,
+                domain_hash,
+                account,
+                message_hash,
+            ]));
+        }
+
         // encode domain separator
         let domain_hash = self.domain.encode(&self.types)?;
 
@@ -487,7 +945,10 @@ impl TypedData {
             &self.primary_type,
             &self.types,
             &preset_types,
-            &mut Default::default(),
+            &mut Ctx {
+                path: vec!["message".to_string()],
+                ..Default::default()
+            },
         )?;
 
         // return full hash
@@ -537,8 +998,8 @@ mod tests {
         assert_eq!(
             encoded,
             "\"Example\"(\"n0\":\"felt\",\"n1\":\"bool\",\"n2\":\"string\",\"n3\":\"selector\",\"\
-             n4\":\"u128\",\"n5\":\"ContractAddress\",\"n6\":\"ClassHash\",\"n7\":\"timestamp\",\"\
-             n8\":\"shortstring\")"
+             n4\":\"u128\",\"n5\":\"i128\",\"n6\":\"ContractAddress\",\"n7\":\"ClassHash\",\"\
+             n8\":\"timestamp\",\"n9\":\"shortstring\")"
         );
 
         let reader = std::io::BufReader::new(MAIL_STUCT_ARRAY.as_bytes());
@@ -574,7 +1035,9 @@ mod tests {
 
         assert_eq!(
             encoded,
-            "\"Example\"(\"n0\":\"TokenAmount\",\"n1\":\"NftId\")"
+            "\"Example\"(\"n0\":\"TokenAmount\",\"n1\":\"NftId\")\"NftId\"(\"collection_address\":\"\
+             ContractAddress\",\"token_id\":\"u256\")\"TokenAmount\"(\"token_address\":\"\
+             ContractAddress\",\"amount\":\"u256\")\"u256\"(\"low\":\"u128\",\"high\":\"u128\")"
         );
     }
 
@@ -613,6 +1076,65 @@ mod tests {
         );
     }
 
+    // The fixtures and expected hashes below are the revision 1 examples of starknet.js, from
+    // `__mocks__/typedData/example_*.json` and `__tests__/utils/typedData.test.ts`
+    #[test]
+    fn test_type_hash() {
+        let typed_data: TypedData = serde_json::from_str(EXAMPLE_BASE_TYPES).unwrap();
+        let type_hash = |name: &str| {
+            get_selector_from_name(&encode_type(name, &typed_data.types).unwrap()).unwrap()
+        };
+
+        assert_eq!(
+            type_hash("StarknetDomain"),
+            Felt::from_hex("0x1ff2f602e42168014d405a94f75e8a93d640751d71d16311266e140d8b0a210")
+                .unwrap()
+        );
+        assert_eq!(
+            type_hash("Example"),
+            Felt::from_hex("0x1f94cd0be8b4097a41486170fdf09a4cd23aefbc74bb2344718562994c2c111")
+                .unwrap()
+        );
+
+        let typed_data: TypedData = serde_json::from_str(EXAMPLE_ENUM).unwrap();
+        assert_eq!(
+            get_selector_from_name(&encode_type("Example", &typed_data.types).unwrap()).unwrap(),
+            Felt::from_hex("0x380a54d417fb58913b904675d94a8a62e2abc3467f4b5439de0fd65fafdd1a8")
+                .unwrap()
+        );
+
+        let typed_data: TypedData = serde_json::from_str(EXAMPLE_PRESET_TYPES).unwrap();
+        let preset_types = get_preset_types();
+        for (name, types, expected) in [
+            (
+                "Example",
+                &typed_data.types,
+                "0x1a25a8bb84b761090b1fadaebe762c4b679b0d8883d2bedda695ea340839a55",
+            ),
+            (
+                "u256",
+                &preset_types,
+                "0x3b143be38b811560b45593fb2a071ec4ddd0a020e10782be62ffe6f39e0e82c",
+            ),
+            (
+                "TokenAmount",
+                &preset_types,
+                "0x14648649d4413eb385eea9ac7e6f2b9769671f5d9d7ad40f7b4aadd67839d4",
+            ),
+            (
+                "NftId",
+                &preset_types,
+                "0xaf7d0f5e34446178d80fadf5ddaaed52347121d2fac19ff184ff508d4776f2",
+            ),
+        ] {
+            assert_eq!(
+                get_selector_from_name(&encode_type(name, types).unwrap()).unwrap(),
+                Felt::from_hex(expected).unwrap(),
+                "type hash of {name}"
+            );
+        }
+    }
+
     #[test]
     fn test_message_hash() {
         let address = Felt::from_hex("0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826").unwrap();
@@ -625,7 +1147,7 @@ mod tests {
 
         assert_eq!(
             message_hash,
-            Felt::from_hex("0x790d9fa99cf9ad91c515aaff9465fcb1c87784d9cfb27271ed193675cd06f9c")
+            Felt::from_hex("0x2d80b87b8bc32068247c779b2ef0f15f65c9c449325e44a9df480fb01eb43ec")
                 .unwrap()
         );
 
@@ -649,11 +1171,378 @@ mod tests {
 
         assert_eq!(
             message_hash,
-            Felt::from_hex("0x26e7b8cedfa63cdbed14e7e51b60ee53ac82bdf26724eb1e3f0710cb8987522")
+            Felt::from_hex("0x185b339d5c566a883561a88fb36da301051e2c0225deb325c91bb7aa2f3473a")
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_revision() {
+        let typed_data: TypedData = serde_json::from_str(MAIL_REVISION_0).unwrap();
+        assert_eq!(typed_data.revision().unwrap(), Revision::V0);
+        assert_eq!(typed_data.domain.chain_id, "1");
+
+        let typed_data: TypedData = serde_json::from_str(MAIL_STUCT_ARRAY).unwrap();
+        assert_eq!(typed_data.revision().unwrap(), Revision::V1);
+
+        let typed_data: TypedData = serde_json::from_str(EXAMPLE_BASE_TYPES).unwrap();
+        assert_eq!(typed_data.revision().unwrap(), Revision::V1);
+    }
+
+    #[test]
+    fn test_type_encode_v0() {
+        let typed_data: TypedData = serde_json::from_str(MAIL_REVISION_0).unwrap();
+
+        assert_eq!(
+            encode_type_v0("Mail", &typed_data.types).unwrap(),
+            "Mail(from:Person,to:Person,contents:felt)Person(name:felt,wallet:felt)"
+        );
+        assert_eq!(
+            get_selector_from_name(&encode_type_v0("StarkNetDomain", &typed_data.types).unwrap())
+                .unwrap(),
+            Felt::from_hex("0x1bfc207425a47a5dfa1a50a4f5241203f50624ca5fdf5e18755765416b8e288")
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_message_hash_v0() {
+        let address = Felt::from_hex("0xcd2a3d9f938e13cd947ec05abc7fe734df8dd826").unwrap();
+
+        let typed_data: TypedData = serde_json::from_str(MAIL_REVISION_0).unwrap();
+
+        assert_eq!(
+            typed_data.encode(address).unwrap(),
+            Felt::from_hex("0x6fcff244f63e38b9d88b9e3378d44757710d1b244282b435cb472053c8d78d0")
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_missing_field_v0() {
+        let mut typed_data: TypedData = serde_json::from_str(MAIL_REVISION_0).unwrap();
+        typed_data.message.shift_remove("contents");
+
+        assert!(typed_data.encode(Felt::ONE).is_err());
+    }
+
+    fn typed_data_with(json: &str, message: serde_json::Value) -> TypedData {
+        let mut value: serde_json::Value = serde_json::from_str(json).unwrap();
+        value["message"] = message;
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn error_message(typed_data: &TypedData) -> String {
+        match typed_data.validate().unwrap_err() {
+            Error::InvalidMessageError(message) => message,
+            e => panic!("unexpected error {e}"),
+        }
+    }
+
+    #[test]
+    fn test_merkle_root() {
+        let leaves = vec![Felt::ONE, Felt::TWO, Felt::from(3_u32)];
+
+        // root of `new MerkleTree(['0x1', '0x2', '0x3'])` in the starknet.js typed data tests
+        assert_eq!(
+            merkle_root(leaves.clone(), |a, b| pedersen_hash(&a, &b)).unwrap(),
+            Felt::from_hex("0x15ac9e457789ef0c56e5d559809e7336a909c14ee2511503fa7af69be1ba639")
+                .unwrap()
+        );
+        // the same tree hashed with Poseidon, as revision 1 does
+        assert_eq!(
+            merkle_root(leaves, poseidon_hash).unwrap(),
+            Felt::from_hex("0x48924a3b2a7a7b7cc1c9371357e95e322899880a6534bdfe24e96a828b9d780")
+                .unwrap()
+        );
+        assert_eq!(merkle_root(vec![Felt::TWO], poseidon_hash), Some(Felt::TWO));
+        assert_eq!(merkle_root(vec![], poseidon_hash), None);
+    }
+
+    #[test]
+    fn test_merkle_tree_encode() {
+        let typed_data: TypedData = serde_json::from_str(EXAMPLE_MERKLE_TREE).unwrap();
+
+        let encoded = encode_type(&typed_data.primary_type, &typed_data.types).unwrap();
+        assert_eq!(
+            encoded,
+            "\"Example\"(\"value\":\"felt\",\"root\":\"merkletree\")"
+        );
+
+        let message_hash = PrimitiveType::Object(typed_data.message.clone())
+            .encode(
+                &typed_data.primary_type,
+                &typed_data.types,
+                &get_preset_types(),
+                &mut Default::default(),
+            )
+            .unwrap();
+        assert_eq!(
+            message_hash,
+            poseidon_hash_many(&[
+                get_selector_from_name(&encoded).unwrap(),
+                Felt::from(42_u32),
+                Felt::from_hex("0x48924a3b2a7a7b7cc1c9371357e95e322899880a6534bdfe24e96a828b9d780")
+                    .unwrap(),
+            ])
+        );
+
+        let empty = typed_data_with(
+            EXAMPLE_MERKLE_TREE,
+            serde_json::json!({ "value": 42, "root": [] }),
+        );
+        assert_eq!(
+            error_message(&empty),
+            "message.root: merkle tree has no leaves"
+        );
+    }
+
+    #[test]
+    fn test_enum_variant_dependencies() {
+        let mut typed_data: TypedData = serde_json::from_str(EXAMPLE_ENUM).unwrap();
+        typed_data.types.insert(
+            "Point".to_string(),
+            vec![
+                Field::SimpleType(SimpleField {
+                    name: "x".to_string(),
+                    r#type: "u128".to_string(),
+                }),
+                Field::SimpleType(SimpleField {
+                    name: "y".to_string(),
+                    r#type: "u128".to_string(),
+                }),
+            ],
+        );
+        typed_data.types["MyEnum"].push(Field::SimpleType(SimpleField {
+            name: "Variant 4".to_string(),
+            r#type: "(Point*)".to_string(),
+        }));
+
+        assert!(encode_type("Example", &typed_data.types)
+            .unwrap()
+            .ends_with("\"Point\"(\"x\":\"u128\",\"y\":\"u128\")"));
+    }
+
+    #[test]
+    fn test_u256_forms() {
+        let address = Felt::from_hex("0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826").unwrap();
+        let token = "0x049d36570d4e46f48e99674bd3fcc84644ddd6b96f7c741b1562b82f9e004dc7";
+        // hash of the starknet.js preset types example, which gives both u256 as objects
+        let expected =
+            Felt::from_hex("0x185b339d5c566a883561a88fb36da301051e2c0225deb325c91bb7aa2f3473a")
+                .unwrap();
+
+        for value in [
+            serde_json::json!("1000"),
+            serde_json::json!(1000),
+            serde_json::json!("0x3e8"),
+        ] {
+            let typed_data = typed_data_with(
+                EXAMPLE_PRESET_TYPES,
+                serde_json::json!({
+                    "n0": { "token_address": token, "amount": value },
+                    "n1": { "collection_address": token, "token_id": value }
+                }),
+            );
+            assert_eq!(typed_data.encode(address).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn test_basic_types() {
+        let types = IndexMap::new();
+        let preset_types = get_preset_types();
+        let encode = |value: serde_json::Value, r#type: &str| {
+            serde_json::from_value::<PrimitiveType>(value)
                 .unwrap()
+                .encode(r#type, &types, &preset_types, &mut Default::default())
+        };
+
+        // signed integers wrap around the field prime
+        assert_eq!(encode(serde_json::json!(-1), "i128").unwrap(), -Felt::ONE);
+        assert_eq!(encode(serde_json::json!("-1"), "i128").unwrap(), -Felt::ONE);
+        assert_eq!(
+            encode(serde_json::json!("0x7"), "i128").unwrap(),
+            Felt::from(7_u32)
+        );
+        assert_eq!(
+            encode(
+                serde_json::json!("-170141183460469231731687303715884105728"),
+                "i128"
+            )
+            .unwrap(),
+            -Felt::TWO.pow(127_u32)
+        );
+        assert!(encode(
+            serde_json::json!("170141183460469231731687303715884105728"),
+            "i128"
+        )
+        .is_err());
+
+        assert_eq!(
+            encode(serde_json::json!("true"), "bool").unwrap(),
+            Felt::ONE
+        );
+        assert_eq!(
+            encode(serde_json::json!(false), "bool").unwrap(),
+            Felt::ZERO
+        );
+        assert!(encode(serde_json::json!("yes"), "bool").is_err());
+
+        assert_eq!(
+            encode(serde_json::json!(1000), "ClassHash").unwrap(),
+            Felt::from(1000_u32)
+        );
+        assert_eq!(
+            encode(serde_json::json!(1000), "timestamp").unwrap(),
+            encode(serde_json::json!("0x3e8"), "timestamp").unwrap()
+        );
+        assert!(encode(serde_json::json!("0x10000000000000000"), "timestamp").is_err());
+        assert!(encode(
+            serde_json::json!("0x100000000000000000000000000000000"),
+            "u128"
+        )
+        .is_err());
+        assert!(encode(serde_json::json!("transfer"), "u128").is_err());
+
+        assert_eq!(
+            encode(serde_json::json!("transfer"), "selector").unwrap(),
+            encode(
+                serde_json::json!(format!(
+                    "{:#x}",
+                    get_selector_from_name("transfer").unwrap()
+                )),
+                "selector"
+            )
+            .unwrap()
+        );
+
+        assert!(encode(serde_json::json!("0x1"), "felt252").is_err());
+    }
+
+    #[test]
+    fn test_validation_errors() {
+        let mut typed_data: TypedData = serde_json::from_str(MAIL_STUCT_ARRAY).unwrap();
+        typed_data.domain.revision = Some("1".to_string());
+        typed_data.types["StarknetDomain"].push(Field::SimpleType(SimpleField {
+            name: "revision".to_string(),
+            r#type: "shortstring".to_string(),
+        }));
+        assert!(typed_data.validate().is_ok());
+
+        let mut missing = typed_data.clone();
+        let PrimitiveType::Array(posts) = &mut missing.message["posts"] else {
+            unreachable!()
+        };
+        let PrimitiveType::Object(post) = &mut posts[1] else {
+            unreachable!()
+        };
+        post.shift_remove("title");
+        assert_eq!(
+            error_message(&missing),
+            "message.posts.1.title: missing field of type Post"
+        );
+
+        let mut extra = typed_data.clone();
+        let PrimitiveType::Object(to) = &mut extra.message["to"] else {
+            unreachable!()
+        };
+        to.insert("age".to_string(), PrimitiveType::String("0x1".to_string()));
+        assert_eq!(
+            error_message(&extra),
+            "message.to.age: unexpected field of type Person"
+        );
+
+        let unknown_variant: TypedData = typed_data_with(
+            EXAMPLE_ENUM,
+            serde_json::json!({ "someEnum": { "Variant 5": [] } }),
+        );
+        assert_eq!(
+            error_message(&unknown_variant),
+            "message.someEnum: unknown variant Variant 5 of MyEnum"
+        );
+
+        let wrong_arity: TypedData = typed_data_with(
+            EXAMPLE_ENUM,
+            serde_json::json!({ "someEnum": { "Variant 3": [1, 2] } }),
+        );
+        assert_eq!(
+            error_message(&wrong_arity),
+            "message.someEnum.Variant 3: expected 1 parameters, got 2"
+        );
+
+        let overflow: TypedData = typed_data_with(
+            EXAMPLE_ENUM,
+            serde_json::json!({ "someEnum": { "Variant 2": [1, [0, "0x100000000000000000000000000000000"]] } }),
+        );
+        assert_eq!(
+            error_message(&overflow),
+            "message.someEnum.Variant 2.1.1: 0x100000000000000000000000000000000 overflows a u128"
         );
     }
 
+    const EXAMPLE_MERKLE_TREE: &str = r#"
+{
+  "types": {
+    "StarknetDomain": [
+      { "name": "name", "type": "shortstring" },
+      { "name": "version", "type": "shortstring" },
+      { "name": "chainId", "type": "shortstring" },
+      { "name": "revision", "type": "shortstring" }
+    ],
+    "Example": [
+      { "name": "value", "type": "felt" },
+      { "name": "root", "type": "merkletree", "contains": "felt" }
+    ]
+  },
+  "primaryType": "Example",
+  "domain": {
+    "name": "StarkNet Mail",
+    "version": "1",
+    "chainId": "1",
+    "revision": "1"
+  },
+  "message": {
+    "value": 42,
+    "root": ["0x1", "0x2", "0x3"]
+  }
+}"#;
+    const MAIL_REVISION_0: &str = r#"
+{
+  "types": {
+    "StarkNetDomain": [
+      { "name": "name", "type": "felt" },
+      { "name": "version", "type": "felt" },
+      { "name": "chainId", "type": "felt" }
+    ],
+    "Person": [
+      { "name": "name", "type": "felt" },
+      { "name": "wallet", "type": "felt" }
+    ],
+    "Mail": [
+      { "name": "from", "type": "Person" },
+      { "name": "to", "type": "Person" },
+      { "name": "contents", "type": "felt" }
+    ]
+  },
+  "primaryType": "Mail",
+  "domain": {
+    "name": "StarkNet Mail",
+    "version": "1",
+    "chainId": 1
+  },
+  "message": {
+    "from": {
+      "name": "Cow",
+      "wallet": "0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826"
+    },
+    "to": {
+      "name": "Bob",
+      "wallet": "0xbBbBBBBbbBBBbbbBbbBbbbbBBbBbbbbBbBbbBBbB"
+    },
+    "contents": "Hello, Bob!"
+  }
+}"#;
     const EXAMPLE_BASE_TYPES: &str = r#"
 {
   "types": {
@@ -669,10 +1558,11 @@ mod tests {
       { "name": "n2", "type": "string" },
       { "name": "n3", "type": "selector" },
       { "name": "n4", "type": "u128" },
-      { "name": "n5", "type": "ContractAddress" },
-      { "name": "n6", "type": "ClassHash" },
-      { "name": "n7", "type": "timestamp" },
-      { "name": "n8", "type": "shortstring" }
+      { "name": "n5", "type": "i128" },
+      { "name": "n6", "type": "ContractAddress" },
+      { "name": "n7", "type": "ClassHash" },
+      { "name": "n8", "type": "timestamp" },
+      { "name": "n9", "type": "shortstring" }
     ]
   },
   "primaryType": "Example",
@@ -688,10 +1578,11 @@ mod tests {
     "n2": "Lorem ipsum dolor sit amet, consectetur adipiscing elit, sed do eiusmod tempor incididunt ut labore et dolore magna aliqua.",
     "n3": "transfer",
     "n4": "0x3e8",
-    "n5": "0x3e8",
+    "n5": "-170141183460469231731687303715884105727",
     "n6": "0x3e8",
-    "n7": 1000,
-    "n8": "transfer"
+    "n7": "0x3e8",
+    "n8": 1000,
+    "n9": "transfer"
   }
 }"#;
     const EXAMPLE_ENUM: &str = r#"
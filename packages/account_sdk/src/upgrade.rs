@@ -1,14 +1,103 @@
-use starknet::core::types::Call;
+use starknet::core::types::{BlockId, BlockTag, Call, TransactionReceiptWithBlockInfo};
+use starknet::providers::Provider;
 use starknet_crypto::Felt;
 
-use crate::controller::Controller;
+use crate::{
+    artifacts::{Version, CONTROLLERS, VERSIONS},
+    controller::Controller,
+    declaration::{declare_controller_classes, DeclarationError},
+    errors::ControllerError,
+    signers::SignError,
+    simulation::SimulationStatus,
+};
 
 #[cfg(all(test, not(target_arch = "wasm32")))]
 #[path = "upgrade_test.rs"]
 mod upgrade_test;
 
+#[derive(Debug, thiserror::Error)]
+pub enum UpgradeError {
+    #[error("Class hash {0:#x} isn't a known Controller version")]
+    UnknownClassHash(Felt),
+    #[error("Can't upgrade from {current:?} to {target:?}")]
+    NotAnUpgrade { current: Version, target: Version },
+    #[error("Upgrade would revert: {0}")]
+    Reverted(String),
+    #[error(transparent)]
+    Declaration(#[from] DeclarationError<SignError>),
+    #[error(transparent)]
+    Controller(#[from] ControllerError),
+}
+
+/// Version a Controller runs on chain and the one it would be upgraded to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UpgradePlan {
+    pub current: Version,
+    pub target: Version,
+}
+
+impl UpgradePlan {
+    pub fn target_class_hash(&self) -> Felt {
+        CONTROLLERS[&self.target].hash
+    }
+}
+
+/// The Controller version whose class hash is `class_hash`, if any.
+pub fn version_of(class_hash: Felt) -> Option<Version> {
+    VERSIONS
+        .iter()
+        .copied()
+        .find(|version| CONTROLLERS[version].hash == class_hash)
+}
+
 impl Controller {
     pub fn upgrade(&self, new_class_hash: Felt) -> Call {
         self.contract().upgrade_getcall(&new_class_hash.into())
     }
+
+    /// Checks that the Controller can be upgraded to `target`. Its class hash on chain has to
+    /// be one of [`VERSIONS`], and `target` has to come after it.
+    pub async fn plan_upgrade(&self, target: Version) -> Result<UpgradePlan, UpgradeError> {
+        let class_hash = self
+            .provider
+            .get_class_hash_at(BlockId::Tag(BlockTag::Pending), self.address)
+            .await
+            .map_err(ControllerError::from)?;
+        let current = version_of(class_hash).ok_or(UpgradeError::UnknownClassHash(class_hash))?;
+
+        let position = |version| VERSIONS.iter().position(|v| *v == version);
+        if position(target) <= position(current) {
+            return Err(UpgradeError::NotAnUpgrade { current, target });
+        }
+
+        Ok(UpgradePlan { current, target })
+    }
+
+    /// Upgrades the Controller to `target`, paying at most `max_fee` as in
+    /// [`Controller::execute`], and waits for the upgrade to be accepted.
+    ///
+    /// The target class is declared first if the network doesn't know it yet, and the upgrade
+    /// is simulated before being sent so that a failing one costs nothing. The class hash of
+    /// the Controller is then updated, in storage as well when it is stored.
+    pub async fn upgrade_to(
+        &mut self,
+        target: Version,
+        max_fee: Felt,
+    ) -> Result<TransactionReceiptWithBlockInfo, UpgradeError> {
+        let plan = self.plan_upgrade(target).await?;
+        declare_controller_classes(&*self, &[plan.target]).await?;
+
+        let call = self.upgrade(plan.target_class_hash());
+        let report = self.simulate(vec![call.clone()]).await?;
+        if let SimulationStatus::Reverted { reason } = report.status {
+            return Err(UpgradeError::Reverted(reason));
+        }
+
+        let receipt = self.execute_and_wait(vec![call], max_fee).await?;
+        self.class_hash = plan.target_class_hash();
+
+        self.update_stored_metadata()?;
+
+        Ok(receipt)
+    }
 }
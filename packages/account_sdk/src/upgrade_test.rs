@@ -1,15 +1,19 @@
 use starknet::{
     accounts::{Account, ConnectedAccount},
     core::types::{BlockId, BlockTag},
+    macros::felt,
     providers::Provider,
 };
 
 use crate::{
     artifacts::{Version, CONTROLLERS},
     signers::{Owner, Signer},
+    storage::StorageBackend,
     tests::{ensure_txn, runners::katana::KatanaRunner},
 };
 
+use super::{version_of, UpgradeError, UpgradePlan};
+
 #[tokio::test]
 async fn test_controller_upgrade() {
     let runner = KatanaRunner::load();
@@ -48,3 +52,63 @@ async fn test_controller_upgrade() {
 
     assert_eq!(hash, CONTROLLERS[&Version::LATEST].hash);
 }
+
+#[test]
+fn test_version_of() {
+    for (version, class) in CONTROLLERS.iter() {
+        assert_eq!(version_of(class.hash), Some(*version));
+    }
+    assert_eq!(version_of(felt!("0x1234")), None);
+}
+
+#[tokio::test]
+async fn test_upgrade_to() {
+    let runner = KatanaRunner::load();
+    let mut controller = runner
+        .deploy_controller(
+            "username".to_owned(),
+            Owner::Signer(Signer::new_starknet_random()),
+            Version::V1_0_4,
+        )
+        .await;
+
+    assert_eq!(
+        controller.plan_upgrade(Version::LATEST).await.unwrap(),
+        UpgradePlan {
+            current: Version::V1_0_4,
+            target: Version::LATEST,
+        }
+    );
+
+    // The latest class isn't declared yet so the upgrade can't be estimated, it declares it
+    let max_fee = felt!("0x10000000000000");
+    controller
+        .upgrade_to(Version::LATEST, max_fee)
+        .await
+        .unwrap();
+
+    let hash = controller
+        .provider()
+        .get_class_hash_at(BlockId::Tag(BlockTag::Pending), controller.address())
+        .await
+        .unwrap();
+    assert_eq!(hash, CONTROLLERS[&Version::LATEST].hash);
+    assert_eq!(controller.class_hash, hash);
+    assert_eq!(
+        controller
+            .storage
+            .controller("app_id")
+            .unwrap()
+            .unwrap()
+            .class_hash,
+        hash
+    );
+
+    assert!(matches!(
+        controller.upgrade_to(Version::V1_0_6, max_fee).await,
+        Err(UpgradeError::NotAnUpgrade {
+            current: Version::LATEST,
+            target: Version::V1_0_6,
+        })
+    ));
+}
@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 #[cfg(target_arch = "wasm32")]
 use js_sys::Date;
 
@@ -13,3 +15,37 @@ pub fn get_current_timestamp() -> u64 {
 pub fn get_current_timestamp() -> u64 {
     (Date::now() / 1000.0) as u64
 }
+
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn sleep(delay: Duration) {
+    tokio::time::sleep(delay).await;
+}
+
+#[cfg(target_arch = "wasm32")]
+pub async fn sleep(delay: Duration) {
+    use wasm_bindgen::JsCast;
+    use web_sys::WorkerGlobalScope;
+
+    let mut cb = |resolve: js_sys::Function, _reject: js_sys::Function| {
+        // if we are in a worker, use the global worker's scope
+        // otherwise, use the window's scope
+        if let Ok(worker_scope) = js_sys::global().dyn_into::<WorkerGlobalScope>() {
+            worker_scope
+                .set_timeout_with_callback_and_timeout_and_arguments_0(
+                    &resolve,
+                    delay.as_millis() as i32,
+                )
+                .expect("should register `setTimeout`");
+        } else {
+            web_sys::window()
+                .unwrap()
+                .set_timeout_with_callback_and_timeout_and_arguments_0(
+                    &resolve,
+                    delay.as_millis() as i32,
+                )
+                .expect("should register `setTimeout`");
+        }
+    };
+    let p = js_sys::Promise::new(&mut cb);
+    wasm_bindgen_futures::JsFuture::from(p).await.unwrap();
+}
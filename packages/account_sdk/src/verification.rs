@@ -0,0 +1,131 @@
+use cainome::cairo_serde::{self, CairoSerde};
+use starknet::core::types::{Felt, StarknetError};
+use starknet::macros::short_string;
+use starknet::providers::{Provider, ProviderError};
+
+use crate::{
+    abigen::controller::{ControllerReader, SignerSignature},
+    controller::Controller,
+    errors::ControllerError,
+    typed_data::TypedData,
+};
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+#[path = "verification_test.rs"]
+mod verification_test;
+
+/// Value returned by `is_valid_signature` for a valid signature, as specified by SNIP-6.
+pub const VALIDATED: Felt = short_string!("VALID");
+
+/// Whether `signature` is a valid signature of `typed_data` by the account at `address`, as
+/// returned by [`Controller::sign_message`].
+///
+/// The hash of the message is checked by the account's SNIP-6 `is_valid_signature`, so the
+/// account must be deployed. A signature the account rejects, malformed ones included, is
+/// reported as invalid. Any other failure of the call, such as an account without SNIP-6, is
+/// an error.
+pub async fn verify_message<P>(
+    provider: &P,
+    address: Felt,
+    typed_data: &TypedData,
+    signature: &[Felt],
+) -> Result<bool, ControllerError>
+where
+    P: Provider + Sync,
+{
+    let hash = typed_data.encode(address)?;
+
+    let result = ControllerReader::new(address, provider)
+        .is_valid_signature(&hash, &signature.to_vec())
+        .call()
+        .await;
+
+    match result {
+        Ok(value) => Ok(value == VALIDATED),
+        Err(cairo_serde::Error::Provider(ProviderError::StarknetError(
+            StarknetError::ContractError(error),
+        ))) if is_signature_rejection(&error.revert_error) => Ok(false),
+        Err(e) => Err(ControllerError::CairoSerde(e)),
+    }
+}
+
+/// Whether `revert_error` is the account panicking on a signature it can't parse or verify,
+/// rather than e.g. not implementing SNIP-6.
+fn is_signature_rejection(revert_error: &str) -> bool {
+    SIGNATURE_REJECTIONS
+        .iter()
+        .any(|reason| revert_error.contains(reason))
+}
+
+/// Panic reasons of `is_valid_signature` for signatures it rejects. The `argent/` ones come
+/// from the validation of the signers' signatures.
+const SIGNATURE_REJECTIONS: [&str; 3] = [
+    "invalid-signature-format",
+    "invalid-signature-length",
+    "argent/invalid-",
+];
+
+/// Verifies `signature` like [`verify_message`], without querying the chain, for an account
+/// whose owners are known to have the guids `owner_guids`.
+///
+/// Only signatures made of a single owner signature by a Starknet signer can be verified
+/// offline, others are reported as invalid or unsupported. Owners added or removed since
+/// `owner_guids` was read aren't taken into account.
+pub fn verify_message_offline(
+    address: Felt,
+    typed_data: &TypedData,
+    signature: &[Felt],
+    owner_guids: &[Felt],
+) -> Result<bool, ControllerError> {
+    let hash = typed_data.encode(address)?;
+
+    let Ok(signatures) = Vec::<SignerSignature>::cairo_deserialize(signature, 0) else {
+        return Ok(false);
+    };
+    // the signature is fully consumed, and holds a single owner signature as anything after it
+    // can't be checked offline
+    if Vec::<SignerSignature>::cairo_serialized_size(&signatures) != signature.len()
+        || signatures.len() != 1
+    {
+        return Ok(false);
+    }
+
+    match &signatures[0] {
+        SignerSignature::Starknet((signer, starknet_signature)) => {
+            if !owner_guids.contains(&Felt::from(signer.clone())) {
+                return Ok(false);
+            }
+            Ok(starknet_crypto::verify(
+                signer.pubkey.inner(),
+                &hash,
+                &starknet_signature.r,
+                &starknet_signature.s,
+            )
+            .unwrap_or(false))
+        }
+        SignerSignature::Secp256k1(_) => Err(ControllerError::OfflineVerificationUnsupported(
+            "Secp256k1".to_string(),
+        )),
+        SignerSignature::Secp256r1(_) => Err(ControllerError::OfflineVerificationUnsupported(
+            "Secp256r1".to_string(),
+        )),
+        SignerSignature::Eip191(_) => Err(ControllerError::OfflineVerificationUnsupported(
+            "Eip191".to_string(),
+        )),
+        SignerSignature::Webauthn(_) => Err(ControllerError::OfflineVerificationUnsupported(
+            "Webauthn".to_string(),
+        )),
+    }
+}
+
+impl Controller {
+    /// Whether `signature` is a valid signature of `typed_data` by this Controller. See
+    /// [`verify_message`].
+    pub async fn verify_message(
+        &self,
+        typed_data: &TypedData,
+        signature: &[Felt],
+    ) -> Result<bool, ControllerError> {
+        verify_message(&self.provider, self.address, typed_data, signature).await
+    }
+}
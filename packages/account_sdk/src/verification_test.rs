@@ -0,0 +1,181 @@
+use cainome::cairo_serde::{CairoSerde, NonZero};
+use starknet::{core::types::Felt, macros::felt};
+
+use crate::{
+    abigen::controller::{SignerSignature, StarknetSignature, StarknetSigner},
+    artifacts::Version,
+    errors::ControllerError,
+    signers::{HashSigner, Owner, Signer},
+    tests::{account::FEE_TOKEN_ADDRESS, runners::katana::KatanaRunner},
+    typed_data::{PrimitiveType, TypedData},
+};
+
+use super::{verify_message, verify_message_offline};
+
+const LOGIN_MESSAGE: &str = r#"
+{
+  "types": {
+    "StarknetDomain": [
+      { "name": "name", "type": "shortstring" },
+      { "name": "version", "type": "shortstring" },
+      { "name": "chainId", "type": "shortstring" },
+      { "name": "revision", "type": "shortstring" }
+    ],
+    "Login": [
+      { "name": "player", "type": "ContractAddress" },
+      { "name": "nonce", "type": "felt" },
+      { "name": "issued_at", "type": "timestamp" }
+    ]
+  },
+  "primaryType": "Login",
+  "domain": {
+    "name": "Game",
+    "version": "1",
+    "chainId": "KATANA",
+    "revision": "1"
+  },
+  "message": {
+    "player": "0x1",
+    "nonce": "0x2a",
+    "issued_at": 1700000000
+  }
+}"#;
+
+fn login_message() -> TypedData {
+    serde_json::from_str(LOGIN_MESSAGE).unwrap()
+}
+
+async fn sign(signer: &Signer, address: Felt, typed_data: &TypedData) -> Vec<Felt> {
+    let hash = typed_data.encode(address).unwrap();
+    let signature = signer.sign(&hash).await.unwrap();
+    Vec::<SignerSignature>::cairo_serialize(&vec![signature])
+}
+
+#[tokio::test]
+async fn test_verify_message_offline() {
+    let address = felt!("0x1234");
+    let signer = Signer::new_starknet_random();
+    let guid = Felt::from(signer.clone());
+    let typed_data = login_message();
+    let signature = sign(&signer, address, &typed_data).await;
+
+    assert!(verify_message_offline(address, &typed_data, &signature, &[guid]).unwrap());
+
+    // signed by someone who isn't an owner
+    assert!(!verify_message_offline(address, &typed_data, &signature, &[Felt::ONE]).unwrap());
+
+    // signed for another account
+    assert!(!verify_message_offline(Felt::ONE, &typed_data, &signature, &[guid]).unwrap());
+
+    // signed another message
+    let mut tampered = typed_data.clone();
+    tampered.message.insert(
+        "nonce".to_string(),
+        PrimitiveType::String("0x2b".to_string()),
+    );
+    assert!(!verify_message_offline(address, &tampered, &signature, &[guid]).unwrap());
+
+    // malformed signatures
+    assert!(!verify_message_offline(address, &typed_data, &signature[1..], &[guid]).unwrap());
+    let mut trailing = signature.clone();
+    trailing.push(Felt::ONE);
+    assert!(!verify_message_offline(address, &typed_data, &trailing, &[guid]).unwrap());
+
+    // a valid owner signature followed by one that can't be checked
+    let valid = Vec::<SignerSignature>::cairo_deserialize(&signature, 0).unwrap();
+    let garbage = SignerSignature::Starknet((
+        StarknetSigner {
+            pubkey: NonZero::new(Felt::ONE).unwrap(),
+        },
+        StarknetSignature {
+            r: Felt::ONE,
+            s: Felt::TWO,
+        },
+    ));
+    let appended = Vec::<SignerSignature>::cairo_serialize(&vec![valid[0].clone(), garbage]);
+    assert!(!verify_message_offline(address, &typed_data, &appended, &[guid]).unwrap());
+}
+
+#[test]
+fn test_verify_message_offline_unsupported_signer() {
+    let typed_data = login_message();
+    let signature = Vec::<SignerSignature>::cairo_serialize(&vec![SignerSignature::Eip191((
+        crate::abigen::controller::Eip191Signer {
+            eth_address: cainome::cairo_serde::EthAddress(Felt::ONE),
+        },
+        crate::abigen::controller::Signature {
+            r: cainome::cairo_serde::U256 { low: 1, high: 0 },
+            s: cainome::cairo_serde::U256 { low: 1, high: 0 },
+            y_parity: false,
+        },
+    ))]);
+
+    assert!(matches!(
+        verify_message_offline(Felt::ONE, &typed_data, &signature, &[Felt::ONE]),
+        Err(ControllerError::OfflineVerificationUnsupported(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_verify_message() {
+    let runner = KatanaRunner::load();
+    let signer = Signer::new_starknet_random();
+    let controller = runner
+        .deploy_controller(
+            "username".to_owned(),
+            Owner::Signer(signer.clone()),
+            Version::LATEST,
+        )
+        .await;
+
+    let typed_data = login_message();
+    let signature = controller.sign_message(typed_data.clone()).await.unwrap();
+
+    assert!(
+        verify_message(runner.client(), controller.address, &typed_data, &signature)
+            .await
+            .unwrap()
+    );
+    assert!(controller
+        .verify_message(&typed_data, &signature)
+        .await
+        .unwrap());
+
+    // another key's signature is rejected by the contract
+    let other = sign(
+        &Signer::new_starknet_random(),
+        controller.address,
+        &typed_data,
+    )
+    .await;
+    assert!(
+        !verify_message(runner.client(), controller.address, &typed_data, &other)
+            .await
+            .unwrap()
+    );
+
+    // as is a malformed one
+    assert!(!verify_message(
+        runner.client(),
+        controller.address,
+        &typed_data,
+        &signature[1..]
+    )
+    .await
+    .unwrap());
+
+    // an account without SNIP-6 is an error rather than an invalid signature
+    assert!(
+        verify_message(runner.client(), *FEE_TOKEN_ADDRESS, &typed_data, &signature)
+            .await
+            .is_err()
+    );
+
+    assert!(verify_message_offline(
+        controller.address,
+        &typed_data,
+        &signature,
+        &[controller.owner_guid()]
+    )
+    .unwrap());
+}